@@ -0,0 +1,279 @@
+//! The rules of the autograder on top of `spreadsheet_core`.
+//!
+//! The autograder only takes a small part of the formula language: an integer, a cell, two of them joined by one
+//! of `+ - * /`, `SLEEP` of one of them, or `SUM`, `AVG`, `MIN`, `MAX` or `STDEV` of a range. Such a line is
+//! parsed and evaluated by the core like any other assignment. Every value is an integer, so division, `AVG` and
+//! `STDEV`, which the core works out as floats, are evaluated here instead (see `integer_rules`).
+use logos::Logos;
+use spreadsheet_core::SheetStorage;
+use spreadsheet_core::parse_assign;
+use spreadsheet_core::ast::{Addr, Command, DisplayCommand, Expr, InfixFunction, ParentType, RangeFunction};
+use spreadsheet_core::cell_operations::{CellError, CellFunc, ErrorKind, Sheet, ValueType};
+use spreadsheet_core::evaluate_operations::{evaluate, set_eval_override};
+use spreadsheet_core::tokensexpr::Token;
+use std::cell::RefCell;
+use std::cmp;
+use std::rc::Rc;
+
+/// Height and width of the part of the sheet that is printed.
+pub const WINDOW: u32 = 10;
+
+/// Returns true if a token is a cell of the autograder, ie. one to three letters and a row of up to three digits,
+/// with no `$`.
+fn is_cell(token: &(Token, &str)) -> bool {
+    let (Token::LocalCell(_), text) = token else { return false };
+    let letters = text.chars().take_while(|c| c.is_ascii_uppercase()).count();
+    letters <= 3 && text.len() - letters <= 3 && text[letters..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns true if the tokens are an atom: an integer that fits in an `i32`, the same with a `-` before it, or a cell.
+fn is_atom(tokens: &[(Token, &str)]) -> bool {
+    match tokens {
+        [(Token::Integer(n), _)] | [(Token::OperatorSub, _), (Token::Integer(n), _)] => *n <= i32::MAX as i64,
+        [token] => is_cell(token),
+        _ => false,
+    }
+}
+
+/// Returns true if the tokens are the right hand side of an assignment of the autograder.
+fn is_formula(tokens: &[(Token, &str)]) -> bool {
+    match tokens {
+        [(Token::Sleep, _), (Token::LParen, _), arg @ .., (Token::RParen, _)] => is_atom(arg),
+        [(Token::Sum | Token::Avg | Token::Min | Token::Max | Token::Stdev, _), (Token::LParen, _), start, (Token::Colon, _), end, (Token::RParen, _)] => {
+            is_cell(start) && is_cell(end)
+        }
+        // An atom, or two joined by an operator. The left atom is one or two tokens long.
+        _ => is_atom(tokens) || (1..=2).filter(|i| *i < tokens.len()).any(|i| {
+            matches!(tokens[i].0, Token::OperatorAdd | Token::OperatorSub | Token::OperatorMul | Token::OperatorDiv)
+                && is_atom(&tokens[..i]) && is_atom(&tokens[i + 1..])
+        }),
+    }
+}
+
+/// Parses a display command or `q`. `scroll_to` may be written with or without a space before the cell.
+fn parse_display(input: &str) -> Option<(Command, Vec<ParentType>)> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let d_cmd = match words.as_slice() {
+        ["q"] => return Some((Command::Quit, vec![])),
+        ["w"] => DisplayCommand::MoveUp,
+        ["a"] => DisplayCommand::MoveLeft,
+        ["s"] => DisplayCommand::MoveDown,
+        ["d"] => DisplayCommand::MoveRight,
+        ["enable_output"] => DisplayCommand::EnableOut,
+        ["disable_output"] => DisplayCommand::DisableOut,
+        ["scroll_to", target] => scroll_to(target)?,
+        [word] => scroll_to(word.strip_prefix("scroll_to")?)?,
+        _ => return None,
+    };
+    let deps = match &d_cmd {
+        DisplayCommand::ScrollTo(addr) => vec![ParentType::Single(addr.clone())],
+        _ => vec![],
+    };
+    Some((Command::DisplayCmd(d_cmd), deps))
+}
+
+fn scroll_to(target: &str) -> Option<DisplayCommand> {
+    let tokens: Vec<(Token, &str)> = Token::lexer(target).spanned().map(|(t, span)| Some((t.ok()?, &target[span]))).collect::<Option<_>>()?;
+    match tokens.as_slice() {
+        [token @ (Token::LocalCell((col, row, _, _)), _)] if is_cell(token) => Some(DisplayCommand::ScrollTo(Addr::new(0, *row, *col))),
+        _ => None,
+    }
+}
+
+/// Parses one line of input to the autograder.
+///
+/// **Arguments:**
+/// - `input`: The line, eg. `A1=B1/2`, `scroll_to B34` or `w`. Spaces between tokens are ignored.
+/// - `sheetstore`: The store the assignment is parsed against.
+///
+/// **Returns:**
+/// - `Ok((Command, Vec<ParentType>))` with the command and the addresses it refers to.
+/// - `Err(String)` if the line is not a command of the autograder.
+pub fn parse_command(input: &str, sheetstore: &SheetStorage) -> Result<(Command, Vec<ParentType>), String> {
+    if let Some(cmd) = parse_display(input) {
+        return Ok(cmd);
+    }
+    let mut tokens = vec![];
+    for (token, span) in Token::lexer(input).spanned() {
+        let token = token.map_err(|e| format!("Invalid token: {}", e))?;
+        tokens.push((token, &input[span]));
+    }
+    match tokens.as_slice() {
+        [target, (Token::Assign, _), rest @ ..] if is_cell(target) && is_formula(rest) => {
+            parse_assign(input, 0, sheetstore).map_err(|e| format!("{:?}", e))
+        }
+        _ => Err(String::from("Invalid command")),
+    }
+}
+
+/// Reads the integer in a cell, or the error of the cell. A cell that was never made holds 0.
+fn cell_value(sheets: &[Rc<RefCell<Sheet>>], addr: &Addr) -> Result<i64, CellError> {
+    let sheet = sheets[addr.sheet as usize].borrow();
+    let column = sheet.data[addr.col as usize].borrow();
+    let Some(cell) = column.get(addr.row as usize) else { return Ok(0) };
+    let cell = cell.borrow();
+    match &cell.value {
+        ValueType::IntegerValue(n) => Ok(*n),
+        ValueType::Error(e) => Err(e.clone()),
+        _ => Err(CellError::new(ErrorKind::TypeMismatch, "Only integers can be used")),
+    }
+}
+
+fn atom_value(expr: &Expr, sheets: &[Rc<RefCell<Sheet>>]) -> Option<Result<i64, CellError>> {
+    match expr {
+        Expr::Integer(n) => Some(Ok(*n)),
+        Expr::Cell(addr) => Some(cell_value(sheets, addr)),
+        _ => None,
+    }
+}
+
+/// Turns a result worked out in `i128` into a value, or an overflow error if it does not fit in an `i64` or is
+/// `None` from an overflow on the way.
+fn integer(n: Option<i128>) -> Result<ValueType, CellError> {
+    n.and_then(|n| i64::try_from(n).ok()).map(ValueType::IntegerValue).ok_or(CellError::new(ErrorKind::Num, "Integer overflow"))
+}
+
+/// The integer rules of the autograder, put in front of the core's rules while a `Spreadsheet` exists.
+///
+/// - `a/b` rounds towards zero.
+/// - `AVG` is the sum divided by the number of cells, rounded towards zero. Empty cells count as 0.
+/// - `STDEV` is the square root of the mean squared distance from that average, rounded to the nearest integer.
+fn integer_rules(expr: &Expr, sheets: &[Rc<RefCell<Sheet>>]) -> Option<Result<ValueType, CellError>> {
+    match expr {
+        Expr::InfixOp(left, InfixFunction::Div, right) => {
+            let (left, right) = (atom_value(left, sheets)?, atom_value(right, sheets)?);
+            Some(left.and_then(|n| {
+                let m = right?;
+                if m == 0 {
+                    return Err(CellError::new(ErrorKind::DivByZero, "Division by zero"));
+                }
+                integer(Some(n as i128 / m as i128))
+            }))
+        }
+        Expr::RangeOp{op: op @ (RangeFunction::Avg | RangeFunction::Stdev), start, end, ..} => {
+            let mut values = vec![];
+            for col in start.col..=end.col {
+                for row in start.row..=end.row {
+                    match cell_value(sheets, &Addr::new(start.sheet, row, col)) {
+                        Ok(n) => values.push(n as i128),
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+            }
+            let count = values.len() as i128;
+            let mean = values.iter().sum::<i128>() / count;
+            if let RangeFunction::Avg = op {
+                return Some(integer(Some(mean)));
+            }
+            let sum_squared_diff = values.iter().try_fold(0i128, |sum, n| sum.checked_add((n - mean).checked_pow(2)?));
+            Some(integer(sum_squared_diff.map(|sum| (sum as f64 / count as f64).sqrt().round() as i128)))
+        }
+        _ => None,
+    }
+}
+
+/// A single sheet driven by the commands of the autograder, with the window that is printed.
+pub struct Spreadsheet {
+    pub sheetstore: SheetStorage,
+    pub curr_row: u32,
+    pub curr_col: u32,
+    pub show_window: bool,
+    pub exit: bool,
+}
+
+impl Spreadsheet {
+    /// Creates a spreadsheet with one empty sheet, and sets the integer rules for this thread.
+    pub fn new(rows: u32, cols: u32) -> Self {
+        set_eval_override(Some(integer_rules));
+        let mut sheetstore = SheetStorage::new();
+        sheetstore.new_sheet("sheet0", cols as usize, rows as usize);
+        Spreadsheet { sheetstore, curr_row: 0, curr_col: 0, show_window: true, exit: false }
+    }
+
+    /// Number of rows and columns of the sheet.
+    pub fn size(&self) -> (u32, u32) {
+        let sheet = self.sheetstore.data[0].borrow();
+        (sheet.rows, sheet.columns)
+    }
+
+    /// Returns the text printed for a cell: its integer, or `err` if it holds an error.
+    pub fn value_text(&self, col: u32, row: u32) -> String {
+        let sheet = self.sheetstore.data[0].borrow();
+        match sheet.val_at(col as usize, row as usize) {
+            ValueType::Error(_) => String::from("err"),
+            value => value.to_string(),
+        }
+    }
+
+    /// Parses and runs one line of input.
+    ///
+    /// An error in the value of a cell, eg. a division by zero, is shown in the cell and does not fail the line.
+    /// A cycle or a negative `SLEEP` puts the old formula back and fails it.
+    ///
+    /// **Returns:**
+    /// - `Ok(())` if the line ran.
+    /// - `Err(String)` with the reason it did not.
+    pub fn execute(&mut self, inp: &str) -> Result<(), String> {
+        let (cmd, deps) = parse_command(inp, &self.sheetstore)?;
+        let (rows, cols) = self.size();
+        match cmd {
+            Command::DisplayCmd(d_cmd) => match d_cmd {
+                DisplayCommand::EnableOut => self.show_window = true,
+                DisplayCommand::DisableOut => self.show_window = false,
+                DisplayCommand::ScrollTo(addr) => {
+                    if addr.row >= rows || addr.col >= cols {
+                        return Err(String::from("Address out of bounds"));
+                    }
+                    self.curr_row = addr.row;
+                    self.curr_col = addr.col;
+                }
+                DisplayCommand::MoveUp => self.curr_row = self.curr_row.saturating_sub(WINDOW),
+                DisplayCommand::MoveDown => self.curr_row = cmp::min(self.curr_row.saturating_add(WINDOW), rows.saturating_sub(WINDOW)),
+                DisplayCommand::MoveRight => self.curr_col = cmp::min(self.curr_col.saturating_add(WINDOW), cols.saturating_sub(WINDOW)),
+                DisplayCommand::MoveLeft => self.curr_col = self.curr_col.saturating_sub(WINDOW),
+                DisplayCommand::Find(_) => return Err(String::from("Invalid command")),
+            },
+            Command::Quit => self.exit = true,
+            Command::AssignCmd(a, b_ex) => self.assign(a, *b_ex, deps)?,
+            Command::OtherCmd(_) | Command::DefineName(..) => return Err(String::from("Invalid command")),
+        }
+        Ok(())
+    }
+
+    fn assign(&mut self, a: Addr, expr: Expr, deps: Vec<ParentType>) -> Result<(), String> {
+        let (rows, cols) = self.size();
+        let in_sheet = |addr: &Addr| addr.row < rows && addr.col < cols;
+        if !in_sheet(&a) {
+            return Err(String::from("Target address out of range"));
+        }
+        let sheet = self.sheetstore.data[0].borrow();
+        let mut needed = vec![(a.clone(), a.clone())];
+        for dep in deps {
+            match dep {
+                ParentType::Single(a_1) => needed.push((a_1.clone(), a_1)),
+                ParentType::Range(a_1, a_2) => {
+                    if a_1.col > a_2.col || a_1.row > a_2.row {
+                        return Err(String::from("Range start comes after its end"));
+                    }
+                    needed.push((a_1, a_2));
+                }
+            }
+        }
+        for (a_1, a_2) in &needed {
+            if !in_sheet(a_1) || !in_sheet(a_2) {
+                return Err(String::from("Address out of range"));
+            }
+        }
+        for (a_1, a_2) in &needed {
+            sheet.extend_range(a_1, a_2);
+        }
+        let target_cell_rc = Rc::clone(&sheet.data[a.col as usize].borrow()[a.row as usize]);
+        drop(sheet);
+        let old_func = target_cell_rc.borrow_mut().cell_func.replace(CellFunc::new(expr));
+        match evaluate(&mut self.sheetstore.data, &a, &old_func) {
+            // The core has put the old formula back.
+            Err(strr) if strr.contains("Cyclic dependency detected") || strr.contains("Negative sleep time") => Err(strr),
+            _ => Ok(()),
+        }
+    }
+}
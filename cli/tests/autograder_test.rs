@@ -0,0 +1,183 @@
+use spreadsheet::autograder::{parse_command, Spreadsheet};
+use spreadsheet_core::ast::{Addr, Command, DisplayCommand, ParentType};
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+
+    // Helper function that runs the lines and returns the printed values of the first `rows` cells of a column.
+    fn column(sheet: &mut Spreadsheet, lines: &[&str], col: u32, rows: u32) -> Vec<String> {
+        for line in lines {
+            sheet.execute(line).unwrap();
+        }
+        (0..rows).map(|row| sheet.value_text(col, row)).collect()
+    }
+
+    #[test]
+    fn test_integer_arithmetic() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        let values = column(&mut sheet, &["A1=7", "B1=A1+3", "B2=A1-10", "B3=A1*-2", "B4=A1/2", "B5=-7/2"], 1, 5);
+        assert_eq!(values, ["10", "-3", "-14", "3", "-3"]);
+    }
+
+    #[test]
+    fn test_errors_show_in_the_cell() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        // Division by zero fails the cell but not the line, and the error reaches the cells that use it.
+        let values = column(&mut sheet, &["A1=5", "A2=A1/0", "A3=A2+1", "A4=SUM(A1:A3)"], 0, 4);
+        assert_eq!(values, ["5", "err", "err", "err"]);
+
+        let values = column(&mut sheet, &["A2=A1/5"], 0, 4);
+        assert_eq!(values, ["5", "1", "2", "8"]);
+    }
+
+    #[test]
+    fn test_range_functions() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        let lines = ["A1=5", "A2=3", "B1=7", "B2=2", "C1=MIN(A1:B2)", "C2=MAX(A1:B2)", "C3=SUM(A1:B2)", "C4=AVG(A1:B2)", "C5=STDEV(A1:B2)"];
+        assert_eq!(column(&mut sheet, &lines, 2, 5), ["2", "7", "17", "4", "2"]);
+
+        // Empty cells count as 0, and the standard deviation is taken from the average rounded towards zero.
+        let lines = ["D3=1", "E1=AVG(D1:D3)", "E2=STDEV(D1:D3)"];
+        assert_eq!(column(&mut sheet, &lines, 4, 2), ["0", "1"]);
+    }
+
+    #[test]
+    fn test_sleep_and_cycles_put_the_old_formula_back() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        column(&mut sheet, &["A1=2", "B1=A1+1"], 0, 1);
+
+        assert!(sheet.execute("A1=SLEEP(-1)").is_err());
+        assert!(sheet.execute("D1=SLEEP(-1)").is_err());
+        assert!(sheet.execute("A1=B1").is_err());
+        assert_eq!(sheet.value_text(0, 0), "2");
+        assert_eq!(sheet.value_text(1, 0), "3");
+        assert_eq!(sheet.value_text(3, 0), "0");
+    }
+
+    #[test]
+    fn test_operators_with_an_erroring_operand() {
+        for op in ["+", "-", "*", "/"] {
+            let mut sheet = Spreadsheet::new(5, 5);
+            let values = column(&mut sheet, &["A1=5", "B2=1/0", &format!("C1=A1{}B2", op), &format!("C2=B2{}A1", op)], 2, 2);
+            assert_eq!(values, ["err", "err"], "{}", op);
+        }
+    }
+
+    #[test]
+    fn test_range_functions_with_an_erroring_cell() {
+        for func in ["MIN", "MAX", "SUM", "AVG", "STDEV"] {
+            let mut sheet = Spreadsheet::new(5, 5);
+            let lines = ["A1=5", "A2=3", "B1=7", "B2=2/0", &format!("C1={}(A1:B2)", func)];
+            assert_eq!(column(&mut sheet, &lines, 2, 1), ["err"], "{}", func);
+
+            // The cell comes back once the error is gone.
+            sheet.execute("B2=2").unwrap();
+            assert_ne!(sheet.value_text(2, 0), "err", "{}", func);
+        }
+    }
+
+    #[test]
+    fn test_sleep() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        let values = column(&mut sheet, &["A1=1", "A2=SLEEP(A1)", "A3=SLEEP(0)"], 0, 3);
+        assert_eq!(values, ["1", "1", "0"]);
+
+        // A negative time from a cell fails the line like a negative integer does.
+        column(&mut sheet, &["B1=-5"], 1, 1);
+        assert!(sheet.execute("A2=SLEEP(B1)").is_err());
+        assert_eq!(sheet.value_text(0, 1), "1");
+    }
+
+    #[test]
+    fn test_circular_dependency() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        column(&mut sheet, &["A1=5", "B2=A1+1", "C3=SUM(A1:B2)"], 0, 1);
+        assert!(sheet.execute("A1=A1+1").is_err());
+        assert!(sheet.execute("A1=B2*2").is_err());
+        assert!(sheet.execute("A1=MAX(B1:C3)").is_err());
+        assert_eq!(sheet.value_text(0, 0), "5");
+        assert_eq!(sheet.value_text(1, 1), "6");
+        assert_eq!(sheet.value_text(2, 2), "11");
+
+        // Taking the cycle apart works again.
+        sheet.execute("B2=7").unwrap();
+        sheet.execute("A1=B2*2").unwrap();
+        assert_eq!(sheet.value_text(0, 0), "14");
+    }
+
+    #[test]
+    fn test_addresses_out_of_the_sheet() {
+        let mut sheet = Spreadsheet::new(5, 5);
+        assert!(sheet.execute("F1=1").is_err());
+        assert!(sheet.execute("A1=A6").is_err());
+        assert!(sheet.execute("A1=SUM(B2:A1)").is_err());
+        assert!(sheet.execute("scroll_to E6").is_err());
+    }
+
+    #[test]
+    fn test_display_commands() {
+        let mut sheet = Spreadsheet::new(30, 25);
+        sheet.execute("s").unwrap();
+        sheet.execute("s").unwrap();
+        sheet.execute("d").unwrap();
+        assert_eq!((sheet.curr_row, sheet.curr_col), (20, 10));
+        // Moving down stops when the last rows fill the window.
+        sheet.execute("s").unwrap();
+        sheet.execute("d").unwrap();
+        assert_eq!((sheet.curr_row, sheet.curr_col), (20, 15));
+        sheet.execute("w").unwrap();
+        sheet.execute("a").unwrap();
+        assert_eq!((sheet.curr_row, sheet.curr_col), (10, 5));
+
+        sheet.execute("scroll_to B3").unwrap();
+        assert_eq!((sheet.curr_row, sheet.curr_col), (2, 1));
+        sheet.execute("disable_output").unwrap();
+        assert!(!sheet.show_window);
+        sheet.execute("q").unwrap();
+        assert!(sheet.exit);
+    }
+
+    #[test]
+    fn test_dependencies_of_commands() {
+        let sheet = Spreadsheet::new(5, 5);
+        let (cmd, deps) = parse_command("C1=A1*B2", &sheet.sheetstore).unwrap();
+        assert!(matches!(cmd, Command::AssignCmd(Addr{row: 0, col: 2, ..}, _)));
+        assert_eq!(format!("{:?}", deps), format!("{:?}", [ParentType::Single(Addr::new(0, 0, 0)), ParentType::Single(Addr::new(0, 1, 1))]));
+        let (_, deps) = parse_command("C1=AVG(A1:B2)", &sheet.sheetstore).unwrap();
+        assert!(deps.iter().all(|dep| matches!(dep, ParentType::Range(Addr{row: 0, col: 0, ..}, Addr{row: 1, col: 1, ..}))));
+
+        // Integers, on their own or with an operator or SLEEP, depend on nothing. Each cell is one dependency.
+        let cases = [("C1=23425*-243", 0), ("C1=5", 0), ("C1=SLEEP(3)", 0), ("C1=B2", 1), ("C1=SLEEP(B2)", 1), ("C1=B2+3", 1), ("C1=3-B2", 1), ("C1=MAX(A1:E5)", 1)];
+        for (line, count) in cases {
+            let (_, deps) = parse_command(line, &sheet.sheetstore).unwrap();
+            assert_eq!(deps.len(), count, "{}", line);
+        }
+        let (cmd, deps) = parse_command("scroll_to C2", &sheet.sheetstore).unwrap();
+        assert!(matches!(cmd, Command::DisplayCmd(DisplayCommand::ScrollTo(Addr{row: 1, col: 2, ..}))));
+        assert_eq!(deps.len(), 1);
+    }
+
+    #[test]
+    fn test_parser() {
+        let sheet = Spreadsheet::new(999, 18278);
+        let file = File::open("./tests/IOtestcases.txt").expect("Error opening test_cases file.");
+        let reader = BufReader::new(file);
+
+        for line_result in reader.lines() {
+            let line = line_result.expect("Issue in running test.");
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue; // Ignore empty and comment lines
+            } else if let Some(rest) = trimmed.strip_prefix('!') {
+                if parse_command(rest.trim(), &sheet.sheetstore).is_ok() {
+                    panic!("{}", rest.trim())
+                }
+            } else if let Err(e) = parse_command(trimmed, &sheet.sheetstore) {
+                panic!("{}: {}", trimmed, e)
+            }
+        }
+    }
+}
@@ -2,7 +2,8 @@
 // use std::io;
 use std::cmp;
 
-use crate::cell_operations::{Sheet, ValueType};
+use spreadsheet_core::ast::Addr;
+use spreadsheet_core::cell_operations::{self, Align, CellColor, CellFormat, Sheet, ValueType};
 
 // use crossterm::{
 //     execute,
@@ -17,7 +18,7 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Table, Row, Cell, Block, Borders, Paragraph, ScrollbarState, Scrollbar, ScrollbarOrientation, Tabs, Axis, Chart, GraphType, Wrap},
     layout::{Constraint, Rect, Position},
-    style::{Style, Color, palette::tailwind, Stylize},
+    style::{Style, Color, Modifier, palette::tailwind, Stylize},
     symbols::Marker
 };
 
@@ -264,7 +265,7 @@ impl CellDetailsWidget {
             .style(styleguide.cell_details_header);
 
         let curr_cell_col = sheet.data[col].borrow();
-        let data = if row >= curr_cell_col.cells.len() {
+        let data = if row >= curr_cell_col.len() {
             vec![
                 vec!["Column".to_string(), col.to_string()],
                 vec!["Row".to_string(), row.to_string()],
@@ -272,21 +273,23 @@ impl CellDetailsWidget {
                 vec!["Expression".to_string(), "~".to_string()],
             ]
         } else {
-            let curr_cell = curr_cell_col.cells[row].borrow();
+            let curr_cell = match curr_cell_col.get(row) {
+                Some(cell) => cell.borrow().clone(),
+                None => cell_operations::Cell::new(Addr::new(sheet.sheet_idx, row as u32, col as u32)),
+            };
     
             vec![
                 vec!["Column".to_string(), curr_cell.addr.col.to_string()],
                 vec!["Row".to_string(), curr_cell.addr.row.to_string()],
                 vec!["Value".to_string(),{
-                    if curr_cell.valid {
-                        match &curr_cell.value {
-                            ValueType::BoolValue(b) => b.to_string(),
-                            ValueType::IntegerValue(x) => x.to_string(),
-                            ValueType::FloatValue(n) => n.to_string(),
-                            ValueType::String(s) => s.clone(),
-                        }
-                    } else {
-                        "~".to_string()
+                    match &curr_cell.value {
+                        ValueType::BoolValue(b) => b.to_string(),
+                        ValueType::IntegerValue(x) => x.to_string(),
+                        ValueType::FloatValue(n) => n.to_string(),
+                        ValueType::String(s) => s.clone(),
+                        ValueType::DateValue(d) => d.to_string(),
+                        ValueType::DecimalValue(d) => d.to_string(),
+                        ValueType::Error(e) => e.to_string(),
                     }
                 }],
 
@@ -370,8 +373,6 @@ impl OutputsWidget {
         let mut max_val1: f64 = f64::MIN;
         let mut max_val2: f64 = f64::MIN;
         for i in 0..=self.row_end1-self.row_start1 {
-            let val1: f64;
-            let val2: f64;
 
             if self.col1 >= sheet.data.len() {
                 invalid = true;
@@ -384,30 +385,26 @@ impl OutputsWidget {
                 },
                 Ok(x) => x
             };
-            if self.row_start1 + i >= colref1.cells.len() {
+            if self.row_start1 + i >= colref1.len() {
                 invalid = true;
                 break
             }
 
-            let cell1 = match colref1.cells[self.row_start1 + i].try_borrow() {
-                Err(_) => {
+            // Rows without a cell read as 0.
+            let val1 = match colref1.get(self.row_start1 + i).map(|cell| cell.try_borrow()) {
+                None => 0.0,
+                Some(Err(_)) => {
                     invalid = true;
                     break
                 },
-                Ok(x) => x
+                Some(Ok(cell1)) => match &cell1.value {
+                    ValueType::IntegerValue(x) => *x as f64,
+                    ValueType::FloatValue(n) => *n,
+                    ValueType::DecimalValue(d) => d.to_f64(),
+                    _ => 0.0,
+                },
             };
 
-            if cell1.valid {
-                let val =  &cell1.value;
-                match val {
-                    ValueType::BoolValue(_) => val1 = 0.0,
-                    ValueType::IntegerValue(x) => val1 = *x as f64,
-                    ValueType::FloatValue(n) => val1 = *n,
-                    ValueType::String(_) => val1 = 0.0,
-                }
-            }
-            else { val1 = 0.0 };
-
             if self.col2 >= sheet.data.len() {
                 invalid = true;
                 break
@@ -419,28 +416,25 @@ impl OutputsWidget {
                 },
                 Ok(x) => x
             };
-            if self.row_start2 + i >= colref2.cells.len() {
+            if self.row_start2 + i >= colref2.len() {
                 invalid = true;
                 break
             }
 
-            let cell2 = match colref2.cells[self.row_start2 + i].try_borrow() {
-                Err(_) => {
+            // Rows without a cell read as 0.
+            let val2 = match colref2.get(self.row_start2 + i).map(|cell| cell.try_borrow()) {
+                None => 0.0,
+                Some(Err(_)) => {
                     invalid = true;
                     break
                 },
-                Ok(x) => x
+                Some(Ok(cell2)) => match &cell2.value {
+                    ValueType::IntegerValue(x) => *x as f64,
+                    ValueType::FloatValue(n) => *n,
+                    ValueType::DecimalValue(d) => d.to_f64(),
+                    _ => 0.0,
+                },
             };
-            if cell2.valid {
-                let val =  &cell2.value;
-                match val {
-                    ValueType::BoolValue(_) => val2 = 0.0,
-                    ValueType::IntegerValue(x) => val2 = *x as f64,
-                    ValueType::FloatValue(n) => val2 = *n,
-                    ValueType::String(_) => val2 = 0.0,
-                }
-            }
-            else { val2 = 0.0 };
             min_val1 = if min_val1 < val1 {min_val1} else {val1};
             min_val2 = if min_val2 < val2 {min_val2} else {val2};
             max_val1 = if max_val1 > val1 {max_val1} else {val1};
@@ -503,17 +497,67 @@ impl OutputsWidget {
 
 
 
-pub fn draw_table(col: usize, row: usize, sheet: &Sheet, title: &str, area: Rect, frame: &mut Frame, styleguide: &StyleGuide) {
+fn terminal_color(color: CellColor) -> Color {
+    match color {
+        CellColor::Black => Color::Black,
+        CellColor::Red => Color::Red,
+        CellColor::Green => Color::Green,
+        CellColor::Yellow => Color::Yellow,
+        CellColor::Blue => Color::Blue,
+        CellColor::Magenta => Color::Magenta,
+        CellColor::Cyan => Color::Cyan,
+        CellColor::Gray => Color::Gray,
+        CellColor::White => Color::White,
+        CellColor::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+/// Builds the table cell showing `value` as `format` says. Colors the format leaves unset keep those of the row.
+fn formatted_cell<'a>(value: &ValueType, format: &CellFormat) -> Cell<'a> {
+    let mut style = Style::default();
+    if let Some(color) = format.fg {
+        style = style.fg(terminal_color(color));
+    }
+    if let Some(color) = format.bg {
+        style = style.bg(terminal_color(color));
+    }
+    if format.bold {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    let line = Line::from(format.render(value));
+    let line = match format.align {
+        Some(Align::Center) => line.centered(),
+        Some(Align::Right) => line.right_aligned(),
+        _ => line,
+    };
+    Cell::from(line).style(style)
+}
+
+/// Draws the sheet from the cell at `col`, `row` on. When the sheet is filtered, `shown_rows` lists the rows its
+/// filter keeps: only those are drawn, and the bottom border tells how many of the rows of the sheet they are.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_table(col: usize, row: usize, sheet: &Sheet, shown_rows: Option<&[u32]>, title: &str, area: Rect, frame: &mut Frame, styleguide: &StyleGuide) {
 
     let column_width = 5;
     // let mut area = f.area();
     // Table block (outer border + title)
-    let block = Block::default()
+    let mut block = Block::default()
         .title(title)
         .borders(Borders::ALL);
 
-    let row_max = cmp::min(row + area.height.saturating_sub(2) as usize, sheet.rows as usize);
-    let col_max = cmp::min(col + area.width.saturating_sub(column_width+2).saturating_div(column_width+2) as usize, sheet.columns as usize);
+    let height = area.height.saturating_sub(2) as usize;
+    let row_list: Vec<usize> = match shown_rows {
+        Some(shown) => {
+            block = block.title_bottom(format!("{} of {} rows", shown.len(), sheet.rows));
+            let first = shown.partition_point(|r| (*r as usize) < row);
+            shown[first..].iter().take(height).map(|r| *r as usize).collect()
+        }
+        None => (row..cmp::min(row + height, sheet.rows as usize)).collect(),
+    };
+    let row_max = row_list.last().map_or(row, |r| r + 1);
+    // Row numbers can be much wider than a cell once sheets go past 99999 rows.
+    let label_width = cmp::max(column_width, row_max.to_string().len() as u16);
+    let col_max = cmp::min(col + area.width.saturating_sub(label_width+2).saturating_div(column_width+2) as usize, sheet.columns as usize);
 
     // Header row
     let mut row_heads_vec: Vec<String> = vec![String::from("")];
@@ -537,30 +581,24 @@ pub fn draw_table(col: usize, row: usize, sheet: &Sheet, title: &str, area: Rect
         .style(styleguide.table_header);
 
 
-    let mut data: Vec<Vec<String>> = vec![];
-    for i in row..row_max {
-        let mut curr_row_vec = vec![(i+1).to_string()];
+    let mut data: Vec<Vec<Cell>> = vec![];
+    for i in row_list {
+        let mut curr_row_vec = vec![Cell::from((i+1).to_string())];
         for j in col..col_max {
             let colref = sheet.data[j].borrow();
-            if i >= colref.cells.len()
+            if i >= colref.len()
             {
-                curr_row_vec.push(String::from("~"));
+                curr_row_vec.push(Cell::from("~"));
                 continue
             } 
             else
             {
-                let cell = colref.cells[i].borrow();
-                if cell.valid {
-                    let val =  &cell.value;
-                    match val {
-                        ValueType::BoolValue(b) => curr_row_vec.push(b.to_string()),
-                        ValueType::IntegerValue(x) => curr_row_vec.push(x.to_string()),
-                        ValueType::FloatValue(n) => curr_row_vec.push(n.to_string()),
-                        ValueType::String(s) => curr_row_vec.push(s.to_string()),
+                match colref.get(i) {
+                    Some(cell_rc) => {
+                        let cell = cell_rc.borrow();
+                        curr_row_vec.push(formatted_cell(&cell.value, &cell.shown_format()));
                     }
-                }
-                else {
-                    curr_row_vec.push(String::from("ERR"));
+                    None => curr_row_vec.push(formatted_cell(&colref.val_at(i), &CellFormat::default())),
                 }
             }
         };
@@ -578,12 +616,12 @@ pub fn draw_table(col: usize, row: usize, sheet: &Sheet, title: &str, area: Rect
             } else {
                 styleguide.table_odd_row
             };
-            let cells = row.into_iter().map(Cell::from);
-            Row::new(cells).style(style)
+            Row::new(row).style(style)
         })
         .collect();
 
-    let widths = vec![Constraint::Length(column_width); num_cols];
+    let mut widths = vec![Constraint::Length(column_width); num_cols];
+    widths[0] = Constraint::Length(label_width);
     // Build the table
     let table = Table::new(rows, widths)
         .header(header)
@@ -0,0 +1,285 @@
+//! Undo and redo. Every command that changes the sheets is kept as one `Edit`, which knows how to reverse and
+//! replay itself, so a command that changes many cells or whole sheets is undone in a single step.
+use spreadsheet_core::SheetStorage;
+use spreadsheet_core::ast::{Addr, Expr, NameDef, ParentType};
+use spreadsheet_core::cell_operations::{Cell, CellFormat, CellFunc, Sheet, ValueType};
+use spreadsheet_core::evaluate_operations::{evaluate, reevaluate_sheets};
+use spreadsheet_core::sheet_operations::{define_name, invalidate_children, remove_name};
+use std::collections::BTreeMap;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Number of edits kept for undo when no depth is set.
+pub const DEFAULT_UNDO_DEPTH: usize = 10;
+
+/// What a cell holds and how it is shown. The value is only used for cells without a formula, the others are
+/// evaluated again.
+#[derive(Clone)]
+pub struct CellState {
+    func: Option<CellFunc>,
+    formula: String,
+    value: ValueType,
+    format: CellFormat,
+}
+
+/// A cell changed by a command, with what it held before and after.
+pub struct CellChange {
+    addr: Addr,
+    before: CellState,
+    after: CellState,
+}
+
+/// One undoable step.
+pub enum Edit {
+    /// Cells changed by an assignment, a copy, an autofill or a change of format.
+    Cells(Vec<CellChange>),
+    /// A sheet added by `add_sheet`, `dup_sheet` or `load_csv`. The sheet is kept so that redo puts back the same one.
+    AddSheet { name: String, num: usize, position: usize, sheet: Rc<RefCell<Sheet>> },
+    /// A sheet removed by `remove_sheet`.
+    RemoveSheet { name: String, num: usize, position: usize, sheet: Rc<RefCell<Sheet>> },
+    RenameSheet { old: String, new: String },
+    /// A name defined with `name=...`. `before` is `None` for a new name.
+    Name { name: String, before: Option<NameDef>, after: NameDef },
+    /// A resize, a sort, a change of conditional formats or filter, or an insertion or removal of rows or columns.
+    /// The sheets these change, and the names, are kept as they were before and after.
+    Layout { before: Layout, after: Layout },
+}
+
+/// Copies of some sheets, with their numbers, and every name of a store, see `snapshot_layout`.
+pub struct Layout {
+    sheets: Vec<(usize, Sheet)>,
+    names: BTreeMap<String, NameDef>,
+}
+
+/// Reads the state of every cell in the rectangle from `start` to `end`. A cell that is not stored yet has the state
+/// of a new cell.
+pub fn cell_states(sheetstore: &SheetStorage, start: &Addr, end: &Addr) -> Vec<(Addr, CellState)> {
+    let sheet = sheetstore.data[start.sheet as usize].borrow();
+    let state = |cell: &Cell| CellState{func: cell.cell_func.clone(), formula: cell.formula.clone(), value: cell.value.clone(), format: cell.format.clone()};
+    let mut states = vec![];
+    for col in start.col..=end.col {
+        let column = sheet.data[col as usize].borrow();
+        for row in start.row..=end.row {
+            let addr = Addr::new(start.sheet, row, col);
+            match column.get(row as usize) {
+                Some(cell_rc) => states.push((addr, state(&cell_rc.borrow()))),
+                None => states.push((addr.clone(), state(&Cell::new(addr)))),
+            }
+        }
+    }
+    states
+}
+
+/// Pairs the states of the same cells read before and after a command, leaving out the cells it did not change.
+pub fn cell_changes(before: Vec<(Addr, CellState)>, after: Vec<(Addr, CellState)>) -> Vec<CellChange> {
+    before.into_iter().zip(after)
+        .filter(|((_, b), (_, a))| b.formula != a.formula || (a.func.is_none() && b.func.is_some()) || b.format != a.format)
+        .map(|((addr, before), (_, after))| CellChange{addr, before, after})
+        .collect()
+}
+
+/// Copies the sheets numbered `sheets` and every name of the store so that they can be put back by `Edit::Layout`.
+pub fn snapshot_layout(sheetstore: &SheetStorage, sheets: &[usize]) -> Layout {
+    Layout {
+        sheets: sheets.iter().map(|num| (*num, sheetstore.data[*num].borrow().snapshot())).collect(),
+        names: sheetstore.names.clone(),
+    }
+}
+
+/// Lists the sheets that inserting or removing rows or columns of the sheet `num` changes: the sheet itself and
+/// those with a formula, conditional format or filter pointing into it.
+pub fn shifted_sheets(sheetstore: &SheetStorage, num: usize) -> Vec<usize> {
+    let points_into = |exp: &Expr| exp.get_dependency_list().iter().any(|dep| match dep {
+        ParentType::Single(addr) => addr.sheet as usize == num,
+        ParentType::Range(start, end) => start.sheet as usize == num || end.sheet as usize == num,
+    });
+    sheetstore.map.iter().map(|(_, other)| *other).filter(|other| {
+        let sheet = sheetstore.data[*other].borrow();
+        *other == num
+            || sheet.rules.iter().any(|rule| points_into(&rule.cond))
+            || sheet.filter.as_ref().is_some_and(|filter| points_into(&filter.cond))
+            || sheet.data.iter().any(|column| column.borrow().iter()
+                .any(|cell_rc| cell_rc.borrow().cell_func.as_ref().is_some_and(|func| points_into(&func.expression))))
+    }).collect()
+}
+
+/// Writes `state` into the cell at `addr` and evaluates it and the cells depending on it.
+fn apply_state(sheetstore: &mut SheetStorage, addr: &Addr, state: &CellState) -> Result<(), String> {
+    let old_func = {
+        let sheet = sheetstore.data[addr.sheet as usize].borrow();
+        sheet.extend_to(addr.col as usize, addr.row as usize);
+        let column = sheet.data[addr.col as usize].borrow();
+        let mut cell = column[addr.row as usize].borrow_mut();
+        let old_func = cell.cell_func.take();
+        cell.cell_func = state.func.clone();
+        cell.formula = state.formula.clone();
+        cell.format = state.format.clone();
+        if state.func.is_none() {
+            cell.value = state.value.clone();
+        }
+        old_func
+    };
+    evaluate(&mut sheetstore.data, addr, &old_func)
+}
+
+/// Takes the sheet `name` out of the store, like `remove_sheet`.
+fn take_sheet(sheetstore: &mut SheetStorage, name: &str) -> Result<(), String> {
+    let num = sheetstore.num_from_name(name).ok_or_else(|| format!("Sheet name \"{}\" not found.", name))?;
+    let removed = Rc::clone(&sheetstore.data[num]);
+    sheetstore.remove_sheet(name);
+    invalidate_children(&mut sheetstore.data, &removed.borrow());
+    Ok(())
+}
+
+/// Puts a sheet taken out by `take_sheet` back and builds the dependencies to and from it again.
+fn put_sheet(sheetstore: &mut SheetStorage, name: &str, num: usize, position: usize, sheet: &Rc<RefCell<Sheet>>) -> Result<(), String> {
+    sheetstore.restore_sheet(name, num, position, Rc::clone(sheet))
+        .ok_or_else(|| format!("Sheet name \"{}\" already exists.", name))?;
+    reevaluate_sheets(&mut sheetstore.data, &[num]);
+    Ok(())
+}
+
+/// Writes copies of the sheets and names of `layout` over those of the store, then evaluates the sheets put back and
+/// the sheets referring to them again.
+fn put_layout(sheetstore: &mut SheetStorage, layout: &Layout) {
+    for (num, sheet) in &layout.sheets {
+        *sheetstore.data[*num].borrow_mut() = sheet.snapshot();
+    }
+    sheetstore.names = layout.names.clone();
+    let nums: Vec<usize> = layout.sheets.iter().map(|(num, _)| *num).collect();
+    reevaluate_sheets(&mut sheetstore.data, &nums);
+}
+
+/// Gives `name` the definition `def`, or removes it when `def` is `None`.
+fn put_name(sheetstore: &mut SheetStorage, name: &str, def: &Option<NameDef>) -> Result<(), String> {
+    match def {
+        Some(def) => define_name(sheetstore, name, def.clone()).map(|_| ()),
+        None => remove_name(sheetstore, name).map(|_| ()),
+    }
+}
+
+impl Edit {
+    /// Reverses the edit.
+    ///
+    /// **Returns:**
+    /// - `Ok(())` if the edit was reversed.
+    /// - `Err(String)` if a restored formula evaluates to an error or a sheet cannot be put back.
+    fn undo(&self, sheetstore: &mut SheetStorage) -> Result<(), String> {
+        match self {
+            Edit::Cells(changes) => {
+                let mut res = Ok(());
+                for change in changes.iter().rev() {
+                    if let Err(e) = apply_state(sheetstore, &change.addr, &change.before) {
+                        res = Err(e);
+                    }
+                }
+                res
+            }
+            Edit::AddSheet { name, .. } => take_sheet(sheetstore, name),
+            Edit::RemoveSheet { name, num, position, sheet } => put_sheet(sheetstore, name, *num, *position, sheet),
+            Edit::RenameSheet { old, new } => sheetstore.rename_sheet(new, old).map(|_| ())
+                .ok_or_else(|| format!("Either Sheet name \"{}\" not found OR Sheet name \"{}\" already exists.", new, old)),
+            Edit::Name { name, before, .. } => put_name(sheetstore, name, before),
+            Edit::Layout { before, .. } => {
+                put_layout(sheetstore, before);
+                Ok(())
+            }
+        }
+    }
+
+    /// Makes the edit again after it was undone.
+    ///
+    /// **Returns:**
+    /// - `Ok(())` if the edit was made again.
+    /// - `Err(String)` if a formula evaluates to an error or a sheet cannot be put back.
+    fn redo(&self, sheetstore: &mut SheetStorage) -> Result<(), String> {
+        match self {
+            Edit::Cells(changes) => {
+                let mut res = Ok(());
+                for change in changes {
+                    if let Err(e) = apply_state(sheetstore, &change.addr, &change.after) {
+                        res = Err(e);
+                    }
+                }
+                res
+            }
+            Edit::AddSheet { name, num, position, sheet } => put_sheet(sheetstore, name, *num, *position, sheet),
+            Edit::RemoveSheet { name, .. } => take_sheet(sheetstore, name),
+            Edit::RenameSheet { old, new } => sheetstore.rename_sheet(old, new).map(|_| ())
+                .ok_or_else(|| format!("Either Sheet name \"{}\" not found OR Sheet name \"{}\" already exists.", old, new)),
+            Edit::Name { name, after, .. } => put_name(sheetstore, name, &Some(after.clone())),
+            Edit::Layout { after, .. } => {
+                put_layout(sheetstore, after);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The undo and redo stacks. At most `depth` edits are kept, the oldest ones are dropped first.
+pub struct History {
+    undo_stack: Vec<Edit>,
+    redo_stack: Vec<Edit>,
+    depth: usize,
+}
+
+impl History {
+    pub fn new(depth: usize) -> Self {
+        History{undo_stack: vec![], redo_stack: vec![], depth}
+    }
+
+    /// Records an edit that was just made. Anything that could be redone is dropped.
+    pub fn push(&mut self, edit: Edit) {
+        self.redo_stack.clear();
+        self.undo_stack.push(edit);
+        self.trim();
+    }
+
+    /// Sets how many edits are kept. A depth of 0 turns undo off.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.trim();
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    fn trim(&mut self) {
+        for stack in [&mut self.undo_stack, &mut self.redo_stack] {
+            if stack.len() > self.depth {
+                stack.drain(..stack.len() - self.depth);
+            }
+        }
+    }
+
+    /// Undoes the last edit and moves it to the redo stack.
+    ///
+    /// **Returns:**
+    /// - `Ok(())` if the edit was undone.
+    /// - `Err(String)` if there is nothing to undo or the edit failed. Cell edits are kept even when a formula
+    ///   evaluates to an error, a sheet edit that could not be undone is dropped.
+    pub fn undo(&mut self, sheetstore: &mut SheetStorage) -> Result<(), String> {
+        let edit = self.undo_stack.pop().ok_or_else(|| "Already at the earliest change".to_string())?;
+        let res = edit.undo(sheetstore);
+        if res.is_ok() || matches!(edit, Edit::Cells(_)) {
+            self.redo_stack.push(edit);
+        }
+        res
+    }
+
+    /// Redoes the last undone edit and moves it back to the undo stack.
+    ///
+    /// **Returns:**
+    /// - `Ok(())` if the edit was made again.
+    /// - `Err(String)` if there is nothing to redo or the edit failed, which is handled like in `undo`.
+    pub fn redo(&mut self, sheetstore: &mut SheetStorage) -> Result<(), String> {
+        let edit = self.redo_stack.pop().ok_or_else(|| "Already at the latest change".to_string())?;
+        let res = edit.redo(sheetstore);
+        if res.is_ok() || matches!(edit, Edit::Cells(_)) {
+            self.undo_stack.push(edit);
+        }
+        res
+    }
+}
@@ -0,0 +1,57 @@
+//! Script mode: runs a file of commands without the terminal interface, eg. in CI.
+use crate::session::Session;
+use std::io::{self, BufRead, Write};
+
+/// Runs every line of a script through `Session::execute` and reports the status of each one.
+///
+/// Blank lines and lines starting with `#` are skipped. `:q` stops the script.
+///
+/// **Arguments:**
+/// - `session`: The session the commands run in.
+/// - `input`: The script, read line by line.
+/// - `out`: Where the status lines and the sheets are written.
+/// - `print_sheets`: Whether to print the contents of every sheet at the end.
+///
+/// **Returns:**
+/// - `Ok(usize)` with the number of lines that failed.
+/// - `Err(io::Error)` if the script cannot be read or the output cannot be written.
+pub fn run_script(session: &mut Session, input: impl BufRead, out: &mut impl Write, print_sheets: bool) -> io::Result<usize> {
+    let mut failed = 0;
+    for (num, line) in input.lines().enumerate() {
+        let line = line?;
+        let inp = line.trim();
+        if inp.is_empty() || inp.starts_with('#') {
+            continue;
+        }
+        let status = session.execute(inp);
+        if status != "ok" {
+            failed += 1;
+        }
+        writeln!(out, "{}: {} -> {}", num + 1, inp, status)?;
+        if session.exit {
+            break;
+        }
+    }
+    if print_sheets {
+        write_sheets(session, out)?;
+    }
+    Ok(failed)
+}
+
+/// Writes the values of every sheet as comma separated rows, up to the last row and column holding a cell.
+fn write_sheets(session: &Session, out: &mut impl Write) -> io::Result<()> {
+    for (name, num) in &session.sheetstore.map {
+        let sheet = session.sheetstore.data[*num].borrow();
+        writeln!(out, "[{}]", name)?;
+        let columns = sheet.data.iter().rposition(|col| !col.borrow().is_empty()).map_or(0, |c| c + 1);
+        let rows = sheet.data.iter().map(|col| col.borrow().len()).max().unwrap_or(0);
+        for row in 0..rows {
+            let values: Vec<String> = (0..columns).map(|col| {
+                let column = sheet.data[col].borrow();
+                if row < column.len() { column.val_at(row).to_string() } else { String::new() }
+            }).collect();
+            writeln!(out, "{}", values.join(","))?;
+        }
+    }
+    Ok(())
+}
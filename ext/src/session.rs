@@ -0,0 +1,873 @@
+//! Parsing and running of the commands typed by the user. The terminal interface and the script mode both go
+//! through `Session::execute`, so a script behaves exactly like the same lines typed in the editor.
+use crate::graphic_interface::OutputsWidget;
+use crate::{grammarcmds, tokenscmds};
+use lalrpop_util::ParseError;
+use logos::Logos;
+use spreadsheet_core::{ast, tokensexpr, SheetStorage, parse_assign};
+use spreadsheet_core::ast::Addr;
+use spreadsheet_core::cell_operations::{CellFunc, FormatRule, RowFilter};
+use spreadsheet_core::evaluate_operations::{evaluate, filtered_rows, range_sheets};
+use spreadsheet_core::csv_operations::{import_csv, export_csv};
+use spreadsheet_core::workbook_operations::{save_workbook, load_workbook};
+use spreadsheet_core::sheet_operations::{copy_cell_value, copy_range_value, copy_cell_function, copy_range_function, autofill_ap, autofill_gp, invalidate_children, duplicate_sheet, shift_sheet, define_name, add_format_rule, clear_format_rules, sort_range, pivot, find_cells, replace_in_formulas, FindSpec, SheetAxis};
+use crate::history::{cell_changes, cell_states, shifted_sheets, snapshot_layout, Edit, History, DEFAULT_UNDO_DEPTH};
+use std::rc::Rc;
+use std::cmp;
+
+/// Everything a command can change: the sheets, the cursor, the chart and the undo history.
+pub struct Session {
+    pub sheetstore: SheetStorage,
+    pub curr_sheet_number: usize,
+    pub curr_col: usize,
+    pub curr_row: usize,
+    pub outputs_widget: OutputsWidget,
+    pub show_graph: bool,
+    pub exit: bool,
+    history: History,
+    find: Option<FindSpec>, //Search of the last `:find`, which `find_next` goes on with
+    shown: Option<(usize, Option<Vec<u32>>)>, //Sheet number and the rows its filter shows, until a command changes the sheets
+}
+
+impl Session {
+    /// Creates a session with a single sheet named `sheet0`.
+    pub fn new(rows: u32, cols: u32) -> Self {
+        let mut sheetstore = SheetStorage::new();
+        sheetstore.new_sheet("sheet0", cols as usize, rows as usize);
+        Session {
+            sheetstore,
+            curr_sheet_number: 0,
+            curr_col: 0,
+            curr_row: 0,
+            outputs_widget: OutputsWidget::new(),
+            show_graph: false,
+            exit: false,
+            history: History::new(DEFAULT_UNDO_DEPTH),
+            find: None,
+            shown: None,
+        }
+    }
+
+    /// Parses and runs one line of input, either an assignment like `A1=B1+1` or a command starting with `:`.
+    ///
+    /// **Arguments:**
+    /// - `inp`: The line typed by the user.
+    ///
+    /// **Returns:**
+    /// - `"ok"` if the line ran successfully, otherwise the error message.
+    pub fn execute(&mut self, inp: &str) -> String {
+            let mut last_err_msg = String::from("ok");
+            let ast;
+            let dep_vec;
+
+            if inp.is_empty() {
+                return last_err_msg
+            }
+            if inp.starts_with(':') {
+                let inp_smol = inp.chars().skip(1).collect::<String>();
+                let lexer = tokenscmds::Token::lexer(&inp_smol).spanned()
+                .map(|(token_result, span)| {
+                    let token = token_result?; // Propagate LexicalError
+                    Ok((span.start, token, span.end)) // (usize, Token, usize)
+                });
+                let parser = grammarcmds::CommandParser::new();
+                (ast, dep_vec) = match parser.parse(self.curr_sheet_number as u32, &self.sheetstore, lexer) {  //NOTE: Error messages are temporary.
+                    Ok(x) => x,
+                    Err(ParseError::User{error: tokenscmds::LexicalError::InvalidToken}) => 
+                    {
+                        return String::from("Invalid Token"); 
+                    },
+                    Err(ParseError::User{error: tokenscmds::LexicalError::InvalidInteger(x)}) => 
+                    {   
+                        return format!("Invalid Integer {:?}", x); 
+                    }, 
+                    Err(ParseError::User{error: tokenscmds::LexicalError::AddressOutOfRange(x)}) => 
+                    {   
+                        return format!("Cell reference {} is beyond the largest row or column", x); 
+                    }, 
+                    Err(ParseError::User{error: tokenscmds::LexicalError::SheetNotFoundErr(x)}) => 
+                    {   
+                        return format!("Sheet name \"{}\" not found.", x); 
+                    }, 
+                    Err(ParseError::User{error: tokenscmds::LexicalError::RangeErr(msg)}) => 
+                    {   
+                        return msg; 
+                    }, 
+                    Err(ParseError::User{error: tokenscmds::LexicalError::FormatErr(msg)}) => 
+                    {   
+                        return msg; 
+                    }, 
+                    Err(e) => 
+                    {
+                        return format!("This error: {:?}", e); 
+                    }
+                };
+            }
+            else
+            {
+                (ast, dep_vec) = match parse_assign(inp, self.curr_sheet_number as u32, &self.sheetstore) {  //NOTE: Error messages are temporary.
+                    Ok(x) => x,
+                    Err(ParseError::User{error: tokensexpr::LexicalError::InvalidToken}) => 
+                    {
+                        return String::from("Invalid Token"); 
+                    },
+                    Err(ParseError::User{error: tokensexpr::LexicalError::InvalidInteger(x)}) => 
+                    {   
+                        return format!("Invalid Integer {:?}", x); 
+                    }, 
+                    Err(ParseError::User{error: tokensexpr::LexicalError::NumberTooLarge(x)}) => 
+                    {   
+                        return format!("Number {} is out of range", x); 
+                    }, 
+                    Err(ParseError::User{error: tokensexpr::LexicalError::AddressOutOfRange(x)}) => 
+                    {   
+                        return format!("Cell reference {} is beyond the largest row or column", x); 
+                    }, 
+                    Err(ParseError::User{error: tokensexpr::LexicalError::SheetNotFoundErr(x)}) => 
+                    {   
+                        return format!("Sheet name \"{}\" not found.", x); 
+                    }, 
+                    Err(ParseError::User{error: tokensexpr::LexicalError::FunctionErr(msg)}) => 
+                    {   
+                        return msg; 
+                    }, 
+                    Err(ParseError::User{error: tokensexpr::LexicalError::NameErr(msg)}) => 
+                    {   
+                        return msg; 
+                    }, 
+                    Err(e) => 
+                    {
+                        return format!("This error: {:?}", e); 
+                    }
+                };
+            }
+
+
+            // println!("{:?}", dep_vec);
+            // println!("{:?}", ast);
+
+            // Any command but moving the cursor may change the values or the filter the shown rows come from.
+            if !matches!(ast, ast::Command::DisplayCmd(_)) {
+                self.shown = None;
+            }
+            // start = Instant::now();
+            match ast.clone() {
+                ast::Command::OtherCmd(cmd) => { 
+                    for dep in &dep_vec {
+                        match dep {
+                            ast::ParentType::Single(a_1) => {
+                                let cell_sheet = &self.sheetstore.data[a_1.sheet as usize].borrow();
+                                if a_1.row >= cell_sheet.rows {
+                                    return String::from("Address row out of range"); //NOTE: Error messages are temporary.
+                                }
+                                if a_1.col >= cell_sheet.columns {
+                                    return String::from("Address column out of range"); //NOTE: Error messages are temporary.
+                                }
+                                cell_sheet.extend_to(a_1.col as usize, a_1.row as usize);
+                            },
+                            ast::ParentType::Range(a_1, a_2) => {
+                            
+                                let cell_sheet = { 
+                                    if a_1.sheet == a_2.sheet {
+                                        &self.sheetstore.data[a_1.sheet as usize].borrow()
+                                    }
+                                    else {
+                                        return String::from("Range addresses must belong to the same sheet.");
+                                    }
+                                };
+
+                                if a_1.row >= cell_sheet.rows {
+                                    return String::from("Range start address row out of range"); //NOTE: Error messages are temporary.
+                                }
+                                if a_1.col >= cell_sheet.columns {
+                                    return String::from("Range start address column out of range"); //NOTE: Error messages are temporary.
+                                }
+                                if a_2.row >= cell_sheet.rows {
+                                    return String::from("Range end address row out of range"); //NOTE: Error messages are temporary.
+                                }
+                                if a_2.col >= cell_sheet.columns {
+                                    return String::from("Range end address column out of range"); //NOTE: Error messages are temporary.
+                                }
+                                if a_1.col > a_2.col {
+                                    return String::from("Range start column higher than end column"); //NOTE: Error messages are temporary.
+                                }
+                                if a_1.row > a_2.row {
+                                    return String::from("Range start row higher than end row"); //NOTE: Error messages are temporary.
+                                }
+                                cell_sheet.extend_range(a_1, a_2);
+                            },
+                        }
+                    }
+
+
+                    // What the command changes is read before it runs, so that it can be undone.
+                    let cells_before = cell_target(&cmd).map(|(start, end)| cell_states(&self.sheetstore, &start, &end));
+                    let layout_sheets = self.layout_target(&cmd);
+                    let layout_before = layout_sheets.as_ref().map(|sheets| snapshot_layout(&self.sheetstore, sheets));
+                    let mut edit: Option<Edit> = None;
+
+                    match cmd.clone()
+                    {
+                        ast::OtherCommand::AddSheet(s, c, r) => {
+                            if self.sheetstore.map.len() >= 12 {
+                                last_err_msg = String::from("Number of active sheets limit is set to 12")
+                            }
+                            else if self.sheetstore.data.len() >= 50 {
+                                last_err_msg = String::from("Total sheets (active or removed) created in session has limit set to 50.")
+                            }
+                            else if c==0 || r==0 {
+                                last_err_msg = String::from("Column and row size cannot be zero.")
+                            }
+                            else if s.chars().count() > 15 {
+                                last_err_msg = format!("Sheet name \"{}\" is larger than 15 characters.", s);
+                            }
+                            else {
+                                let res = self.sheetstore.new_sheet(s.as_str(), c, r);
+                                if res.is_none() {
+                                    last_err_msg = format!("Sheet name \"{}\" already exists.", s);
+                                }
+                                else {
+                                    edit = self.added_sheet(&s);
+                                    last_err_msg = String::from("ok")
+                                }
+                            }
+                        }
+                        ast::OtherCommand::RemoveSheet(s) => {
+                            if self.sheetstore.map.len() == 1 {
+                                last_err_msg = String::from("Cannot use remove sheet when only one sheet remains.");
+                            }
+                            else {
+                                let removed_sheet = self.sheetstore.num_from_name(s.as_str()).map(|sheet_num| Rc::clone(&self.sheetstore.data[sheet_num]));
+                                let position = self.sheetstore.map.iter().position(|(name, _)| *name == s);
+                                let res = self.sheetstore.remove_sheet(s.as_str());
+                                match (res, removed_sheet, position) {
+                                    (Some(n), Some(removed), Some(position)) => { 
+                                        invalidate_children(&mut self.sheetstore.data, &removed.borrow());
+                                        edit = Some(Edit::RemoveSheet{name: s.clone(), num: n, position, sheet: removed});
+                                        last_err_msg = String::from("ok");
+                                        self.curr_sheet_number = self.sheetstore.map[0].1;
+                                        self.curr_col = 0;
+                                        self.curr_row = 0;
+                                        if n == self.outputs_widget.sheetnum {
+                                            self.show_graph = false
+                                        }
+                                    }
+                                    _ => {
+                                    last_err_msg = format!("Sheet name \"{}\" not found.", s);
+                                    },
+                                }
+                            }
+                        }
+                        ast::OtherCommand::RenameSheet(s, snew) => {
+                            if snew.chars().count() > 15 {
+                                last_err_msg = format!("Sheet name \"{}\" is larger than 15 characters.", snew);
+                            }
+                            else {
+                                let res = self.sheetstore.rename_sheet(s.as_str(), snew.as_str());
+                                if res.is_none() {
+                                    last_err_msg = format!("Either Sheet name \"{}\" not found OR Sheet name \"{}\" already exists.", s, snew);
+                                } else {
+                                    edit = Some(Edit::RenameSheet{old: s, new: snew});
+                                    last_err_msg = String::from("ok")
+                                }
+                            }
+                        }
+                        ast::OtherCommand::DuplicateSheet(s, snew_op) => 
+                        {
+                            let snew = match snew_op {
+                                Some(x) => x,
+                                None => format!("{}-copy", s)
+                            };
+                            let res = self.sheetstore.num_from_name(s.as_str());
+                            if self.sheetstore.map.len() >= 12 {
+                                last_err_msg = String::from("Number of active sheets limit is set to 12")
+                            }
+                            else if self.sheetstore.data.len() >= 50 {
+                                last_err_msg = String::from("Total sheets (active or removed) created in session has limit set to 50.")
+                            }
+                            else if snew.chars().count() > 15 {
+                                last_err_msg = format!("Sheet name \"{}\" is larger than 15 characters.", snew);
+                            }
+                            else if let Some(sheet_num) = res
+                            {
+                                let res2 = duplicate_sheet(&mut self.sheetstore.data, sheet_num);
+                                if let Ok(new_sheet) = res2 
+                                {
+                                    last_err_msg = String::from("ok");
+                                    // self.sheetstore.renameSheet(s.as_str(), &snew).unwrap();
+                                    self.sheetstore.add_sheet(snew.as_str(), new_sheet); 
+                                    edit = self.added_sheet(&snew);
+                                } 
+                                else 
+                                {
+                                    last_err_msg = format!("Error occured during duplication: {}", res2.err().unwrap())
+                                }
+                            }
+                            else 
+                            {
+                                last_err_msg = format!("Sheet name \"{}\" not found.", s);
+                            }
+                        },
+                        ast::OtherCommand::Undo =>
+                        {
+                            let res = self.history.undo(&mut self.sheetstore);
+                            self.fix_view();
+                            if let Err(e) = res {
+                                last_err_msg = e;
+                            }
+                        }
+                        ast::OtherCommand::Redo =>
+                        {
+                            let res = self.history.redo(&mut self.sheetstore);
+                            self.fix_view();
+                            if let Err(e) = res {
+                                last_err_msg = e;
+                            }
+                        }
+                        ast::OtherCommand::UndoDepth(depth) =>
+                        {
+                            self.history.set_depth(depth);
+                        }
+                        ast::OtherCommand::ExportCsv(s) => {
+                            let s_num = self.sheetstore.num_from_name(s.as_str());
+                            match s_num {
+                                Some(x) => {
+
+                                    let imp_result = export_csv(&self.sheetstore.data[x].borrow(), s.as_str());
+                                    match imp_result {
+                                        Ok(()) => {//Since we have alreayd verified that name does not exist already, this should happen successfully
+                                            last_err_msg = String::from("ok");
+                                        },
+                                        Err(e) => last_err_msg = format!("Error occured during import: {}", e)
+                                    }
+                                }
+                                None => last_err_msg = format!("Sheet name \"{}\" not found.", s)
+                            }
+                        }
+                        ast::OtherCommand::SaveWorkbook(path) => {
+                            match save_workbook(&self.sheetstore, path.as_str()) {
+                                Ok(()) => last_err_msg = String::from("ok"),
+                                Err(e) => last_err_msg = format!("Error occured during save: {}", e)
+                            }
+                        }
+                        ast::OtherCommand::LoadWorkbook(path) => {
+                            match load_workbook(path.as_str()) {
+                                Ok(new_store) => {
+                                    self.sheetstore = new_store;
+                                    self.curr_sheet_number = self.sheetstore.map[0].1;
+                                    self.curr_col = 0;
+                                    self.curr_row = 0;
+                                    self.show_graph = false;
+                                    self.history.clear();
+                                    last_err_msg = String::from("ok");
+                                }
+                                Err(e) => last_err_msg = format!("Error occured during load: {}", e)
+                            }
+                        }
+                        ast::OtherCommand::LoadCsv(path, opt_s, options) => 
+                        {                        
+                            if self.sheetstore.map.len() >= 12 {
+                                last_err_msg = String::from("Number of active sheets limit is set to 12")
+                            }
+                            else if self.sheetstore.data.len() >= 50 {
+                                last_err_msg = String::from("Total sheets (active or removed) created in session has limit set to 50.")
+                            }
+                            else {
+                                match opt_s {
+                                    None => {
+                                        let name_opt = path.strip_suffix(".csv");
+                                        match name_opt {
+                                            Some(name) => {
+                                                if name.chars().count() > 15 {
+                                                    last_err_msg = format!("Sheet name \"{}\" is larger than 15 characters.", name);
+                                                }
+                                                else if self.sheetstore.num_from_name(name).is_none() {
+                                                    let imp_result = import_csv(&path, self.sheetstore.data.len() as u32, &options);
+                                                    match imp_result {
+                                                        Ok(x) => {
+                                                            self.sheetstore.add_sheet(name, x); //Since we have alreayd verified that name does not exist already, this should happen successfully
+                                                            edit = self.added_sheet(name);
+                                                            last_err_msg = String::from("ok");
+                                                        },
+                                                        Err(e) => last_err_msg = format!("Error occured during import: {}", e)
+                                                    }
+                                                }
+                                                else {
+                                                    last_err_msg = format!("Sheet name \"{}\" already exist.", name)
+                                                }
+                                            },
+                                            None => last_err_msg = format!("Invalid filepath (does not end in .csv): \"{}\"", path)
+                                        }
+                                    },
+                                    Some(name) => {
+                                        if self.sheetstore.num_from_name(name.as_str()).is_none() {
+                                            let imp_result = import_csv(&path, self.sheetstore.data.len() as u32, &options);
+                                            match imp_result {
+                                                Ok(x) => {
+                                                    self.sheetstore.add_sheet(name.as_str(), x); //Since we have alreayd verified that name does not exist already, this should happen successfully
+                                                    edit = self.added_sheet(&name);
+                                                    last_err_msg = String::from("ok");
+                                                },
+                                                Err(e) => last_err_msg = format!("Error occured during import: {}", e)
+                                            }
+                                        }
+                                        else {
+                                            last_err_msg = format!("Sheet name \"{}\" already exist.", name)
+                                        };
+                                    }
+                                }
+                            }
+                        },
+                        ast::OtherCommand::AddRow(..) | ast::OtherCommand::AddCol(..) | ast::OtherCommand::RemoveRow(..) | ast::OtherCommand::RemoveCol(..) => {
+                            let (a, n, axis, insert) = match &cmd {
+                                ast::OtherCommand::AddRow(a, n) => (a, *n, SheetAxis::Row, true),
+                                ast::OtherCommand::AddCol(a, n) => (a, *n, SheetAxis::Col, true),
+                                ast::OtherCommand::RemoveRow(a, n) => (a, *n, SheetAxis::Row, false),
+                                ast::OtherCommand::RemoveCol(a, n) => (a, *n, SheetAxis::Col, false),
+                                _ => unreachable!(),
+                            };
+                            let at = if axis == SheetAxis::Row { a.row } else { a.col };
+                            match shift_sheet(&mut self.sheetstore, a.sheet, axis, at, n as u32, insert) {
+                                Ok(_) => {
+                                    let sheet = self.sheetstore.data[self.curr_sheet_number].borrow();
+                                    self.curr_row = self.curr_row.min(sheet.rows as usize - 1);
+                                    self.curr_col = self.curr_col.min(sheet.columns as usize - 1);
+                                    last_err_msg = String::from("ok");
+                                }
+                                Err(e) => last_err_msg = format!("Error occured during {}: {}", if insert { "insertion" } else { "removal" }, e)
+                            }
+                        },
+                        ast::OtherCommand::Resize(s, c, r) => {
+                            if c==0 || r==0 {
+                                last_err_msg = String::from("Column and row size cannot be zero.")
+                            }
+                            else {
+                                match self.sheetstore.num_from_name(s.as_str()) {
+                                    Some(sheet_num) => {
+                                        self.sheetstore.data[sheet_num].borrow_mut().resize(r, c);  //NOTE: r aur c ka order har jag asame kar dena chahiye ajeeb lag raha
+                                        last_err_msg = String::from("ok");
+                                    } 
+                                    None => last_err_msg = format!("Sheet name \"{}\" not found.", s)
+                                }
+                            }
+                        },
+                        ast::OtherCommand::CopyCellVals(addr1, addr2) =>
+                        {
+                            copy_cell_value(addr1, addr2,&self.sheetstore.data);
+                            last_err_msg = String::from("ok");
+                        },
+                        ast::OtherCommand::CopyCellFormulae(addr1, addr2) =>
+                        {
+                            match copy_cell_function(addr1, addr2,&mut self.sheetstore)
+                            {
+                                Ok(_) => 
+                                {
+                                    last_err_msg = String::from("ok");
+                                }
+                                Err(e) => 
+                                {
+                                    last_err_msg = format!("Error occured during copy: {}", e);
+                                }
+                            }
+                        },
+                        ast::OtherCommand::CopyRangeFormulae(addr1,addr2, addr3 ) =>
+                        {
+                            match copy_range_function(addr1, addr2, addr3, &mut self.sheetstore)
+                            {
+                                Ok(_) => last_err_msg = String::from("ok"),
+                                Err(e) => last_err_msg = format!("Error occured during copy: {}", e)
+                            }
+                        },
+                        ast::OtherCommand::CopyRangeVals(addr1,addr2, addr3) =>
+                        {
+                            match copy_range_value(addr1, addr2, addr3, &self.sheetstore.data)
+                            {
+                                Ok(_) => last_err_msg = String::from("ok"),
+                                Err(e) => last_err_msg = format!("Error occured during copy: {}", e)
+                            }
+                        },
+                        ast::OtherCommand::AutofillAp(addr1,addr2) =>
+                        {
+                            let res = autofill_ap(addr1, addr2, &mut self.sheetstore);
+                            match res {
+                                Ok(_) => last_err_msg = String::from("ok"),
+                                Err(e) => last_err_msg = format!("Error occured during autofill: {}", e)
+                            }
+                        },
+                        ast::OtherCommand::AutofillGp(addr1, addr2) =>
+                        {
+                            let res = autofill_gp(addr1, addr2, &mut self.sheetstore);
+                            match res {
+                                Ok(_) => last_err_msg = String::from("ok"),
+                                Err(e) => last_err_msg = format!("Error occured during autofill: {}", e)
+                            }
+                        },
+                        ast::OtherCommand::Format(start, end, options) =>
+                        {
+                            self.sheetstore.data[start.sheet as usize].borrow().format_range(&start, &end, Some(&options));
+                        }
+                        ast::OtherCommand::ClearFormat(start, end) =>
+                        {
+                            self.sheetstore.data[start.sheet as usize].borrow().format_range(&start, &end, None);
+                        }
+                        ast::OtherCommand::CondFormat(start, end, cond, options) =>
+                        {
+                            add_format_rule(&self.sheetstore.data, FormatRule{start, end, cond, options});
+                        }
+                        ast::OtherCommand::ClearCondFormat(start, end) =>
+                        {
+                            if clear_format_rules(&self.sheetstore.data, &start, &end) == 0 {
+                                last_err_msg = String::from("No conditional format in the range");
+                            }
+                        }
+                        ast::OtherCommand::Sort(start, end, keys) =>
+                        {
+                            if let Err(e) = sort_range(&mut self.sheetstore, &start, &end, &keys) {
+                                last_err_msg = e;
+                            }
+                        }
+                        ast::OtherCommand::Pivot(start, end, spec, name) =>
+                        {
+                            if self.sheetstore.map.len() >= 12 {
+                                last_err_msg = String::from("Number of active sheets limit is set to 12")
+                            }
+                            else if self.sheetstore.data.len() >= 50 {
+                                last_err_msg = String::from("Total sheets (active or removed) created in session has limit set to 50.")
+                            }
+                            else if name.chars().count() > 15 {
+                                last_err_msg = format!("Sheet name \"{}\" is larger than 15 characters.", name);
+                            }
+                            else {
+                                match pivot(&mut self.sheetstore, &start, &end, &spec, &name) {
+                                    Ok(_) => edit = self.added_sheet(&name),
+                                    Err(e) => last_err_msg = e,
+                                }
+                            }
+                        }
+                        ast::OtherCommand::Replace(spec, with) =>
+                        {
+                            let found = find_cells(&self.sheetstore, self.curr_sheet_number as u32, &spec);
+                            if found.is_empty() {
+                                last_err_msg = format!("No cell matches \"{}\"", spec.text);
+                            }
+                            else {
+                                // The cells may be on several sheets, so they are read one by one for undo.
+                                let states = |store: &SheetStorage| found.iter().flat_map(|addr| cell_states(store, addr, addr)).collect::<Vec<_>>();
+                                let before = states(&self.sheetstore);
+                                let res = replace_in_formulas(&mut self.sheetstore, &found, &spec, &with);
+                                let changes = cell_changes(before, states(&self.sheetstore));
+                                if !changes.is_empty() {
+                                    edit = Some(Edit::Cells(changes));
+                                }
+                                if let Err(e) = res {
+                                    last_err_msg = e;
+                                }
+                            }
+                        }
+                        ast::OtherCommand::Filter(start, cond) =>
+                        {
+                            let sheet_num = start.sheet as usize;
+                            self.sheetstore.data[sheet_num].borrow_mut().filter = Some(RowFilter{start, cond});
+                        }
+                        ast::OtherCommand::ClearFilter(sheet_num) =>
+                        {
+                            if self.sheetstore.data[sheet_num as usize].borrow_mut().filter.take().is_none() {
+                                last_err_msg = String::from("No filter on the sheet");
+                            }
+                        }
+                        ast::OtherCommand::MakeChart(addr1,addr2,addr3,addr4 ) =>
+                        {
+                            if addr1.sheet == addr2.sheet &&  addr2.sheet == addr3.sheet && addr3.sheet == addr4.sheet 
+                            {
+                                if addr1.col == addr2.col && addr3.col == addr4.col
+                                {
+                                    if addr2.row - addr1.row == addr4.row - addr3.row 
+                                    {
+                                        self.outputs_widget.col1=addr1.col as usize;
+                                        self.outputs_widget.col2=addr3.col as usize;
+                                        self.outputs_widget.row_start1=addr1.row as usize;
+                                        self.outputs_widget.row_end1=addr2.row as usize;
+                                        self.outputs_widget.row_start2=addr3.row as usize;
+                                        self.outputs_widget.row_end2=addr4.row as usize;
+                                        self.outputs_widget.sheetnum = addr1.sheet as usize;
+                                        self.show_graph = true;
+                                        last_err_msg = String::from("ok");
+                                    }
+                                    else 
+                                    {
+                                        last_err_msg = String::from("The given ranges are not of the same length")
+                                    }
+                                }
+                                else 
+                                {
+                                    last_err_msg = String::from("The given ranges are not 1 dimensional")
+                                }
+                            }
+                            else
+                            {
+                                last_err_msg = String::from("The given ranges are not in the same sheet")
+                            }
+                        }
+                
+                    };
+
+                    if last_err_msg == "ok" {
+                        if let (Some(before), Some((start, end))) = (cells_before, cell_target(&cmd)) {
+                            let changes = cell_changes(before, cell_states(&self.sheetstore, &start, &end));
+                            if !changes.is_empty() {
+                                edit = Some(Edit::Cells(changes));
+                            }
+                        }
+                        if let (Some(before), Some(sheets)) = (layout_before, &layout_sheets) {
+                            edit = Some(Edit::Layout{before, after: snapshot_layout(&self.sheetstore, sheets)});
+                        }
+                    }
+                    if let Some(edit) = edit {
+                        self.history.push(edit);
+                    }
+                }
+                ast::Command::DisplayCmd(ast::DisplayCommand::Find(spec)) => {
+                    self.find = Some(spec);
+                    return self.find_next(true);
+                }
+                ast::Command::DisplayCmd(d_cmd) => {
+                    self.update_shown_rows();
+                    let curr_sheet = &self.sheetstore.data[self.curr_sheet_number].borrow();
+                    match d_cmd {
+                        ast::DisplayCommand::EnableOut => {},
+                        ast::DisplayCommand::DisableOut => {},
+                        ast::DisplayCommand::ScrollTo(addr) => 
+                        {
+                            if (addr.row >= curr_sheet.rows) | (addr.col >= curr_sheet.columns) {
+                                return String::from("Address out of bounds");
+                            }
+                            self.curr_sheet_number = addr.sheet as usize;
+                            let curr_sheet = &self.sheetstore.data[self.curr_sheet_number].borrow();
+                            self.curr_row = cmp::min(addr.row, curr_sheet.rows.saturating_sub(1)) as usize;
+                            self.curr_col = cmp::min(addr.col, curr_sheet.columns.saturating_sub(1)) as usize; 
+                        },
+
+                        // A filtered sheet moves between the rows it shows.
+                        ast::DisplayCommand::MoveUp => self.curr_row = match self.shown_rows() {
+                            Some(shown) => step_shown(shown, self.curr_row, 10, true),
+                            None => self.curr_row.saturating_sub(10),
+                        },
+                        ast::DisplayCommand::MoveDown => self.curr_row = match self.shown_rows() {
+                            Some(shown) => step_shown(shown, self.curr_row, 10, false),
+                            None => cmp::min(self.curr_row.saturating_add(10) , curr_sheet.rows.saturating_sub(10) as usize),
+                        },
+                        ast::DisplayCommand::MoveRight => self.curr_col = cmp::min(self.curr_col.saturating_add(10) , curr_sheet.columns.saturating_sub(10) as usize),
+                        ast::DisplayCommand::MoveLeft => self.curr_col = self.curr_col.saturating_sub(10),
+                        ast::DisplayCommand::Find(_) => unreachable!(),
+                    };
+                    return String::from("ok");
+                },
+                ast::Command::Quit => self.exit = true,
+                ast::Command::DefineName(name, def) => {
+                    match define_name(&mut self.sheetstore, &name, def.clone()) {
+                        Ok(before) => self.history.push(Edit::Name{name, before, after: def}),
+                        Err(e) => return e,
+                    }
+                }
+                ast::Command::AssignCmd(a, b_ex) => {  //NOTE: All validity checks for addresses will be more complicated when we implement multiple sheets.
+
+                    let old_func: Option<CellFunc>;                 // mut is doubtful
+                    let before;
+                    let old_formula: String;
+                    {
+                        let cell_sheet = &self.sheetstore.data[a.sheet as usize].borrow();
+                        if a.row >= cell_sheet.rows {
+                            return String::from("Target address row out of range"); //NOTE: Error messages are temporary.
+                        }
+                        if a.col >= cell_sheet.columns {
+                            return String::from("Target address column out of range"); //NOTE: Error messages are temporary.
+                        }
+                        cell_sheet.extend_to(a.col as usize, a.row as usize);
+
+                        for dep in &dep_vec {
+                            match dep {
+                                ast::ParentType::Single(a_1) => {
+                                    let cell_sheet = &self.sheetstore.data[a_1.sheet as usize].borrow();
+                                    if a_1.row >= cell_sheet.rows {
+                                        return String::from("Address row out of range"); //NOTE: Error messages are temporary.
+                                    }
+                                    if a_1.col >= cell_sheet.columns {
+                                        return String::from("Address column out of range"); //NOTE: Error messages are temporary.
+                                    }
+                                    cell_sheet.extend_to(a_1.col as usize, a_1.row as usize);
+                                },
+                                ast::ParentType::Range(a_1, a_2) => {
+                                    if a_1.sheet > a_2.sheet {
+                                        return String::from("Range start sheet comes after end sheet");
+                                    }
+                                    // A range between two sheets must fit in every sheet it covers.
+                                    for sheet_num in range_sheets(&self.sheetstore.data, a_1, a_2) {
+                                        let cell_sheet = &self.sheetstore.data[sheet_num as usize].borrow();
+                                        if a_1.row >= cell_sheet.rows {
+                                            return String::from("Range start address row out of range"); //NOTE: Error messages are temporary.
+                                        }
+                                        if a_1.col >= cell_sheet.columns {
+                                            return String::from("Range start address column out of range"); //NOTE: Error messages are temporary.
+                                        }
+                                        if a_2.row >= cell_sheet.rows {
+                                            return String::from("Range end address row out of range"); //NOTE: Error messages are temporary.
+                                        }
+                                        if a_2.col >= cell_sheet.columns {
+                                            return String::from("Range end address column out of range"); //NOTE: Error messages are temporary.
+                                        }
+                                        if a_1.col > a_2.col {
+                                            return String::from("Range start column higher than end column"); //NOTE: Error messages are temporary.
+                                        }
+                                        if a_1.row > a_2.row {
+                                            return String::from("Range start row higher than end row"); //NOTE: Error messages are temporary.
+                                        }
+                                        cell_sheet.extend_range(a_1, a_2);
+                                    }
+                                },
+                            }
+                        }
+
+                        before = cell_states(&self.sheetstore, &a, &a);
+                        let target_sheet = &self.sheetstore.data[a.sheet as usize].borrow();
+                        let target_cell_rc = Rc::clone(& (target_sheet.data[a.col as usize].borrow_mut()[a.row as usize]));
+                        let mut target_cell_ref = target_cell_rc.borrow_mut();
+                        old_func = (target_cell_ref).cell_func.clone();
+                        old_formula = target_cell_ref.formula.clone();
+
+                        (target_cell_ref).cell_func = Some(CellFunc{expression: *b_ex});
+                        // println!("{}", target_cell_rc.try_borrow_mut().is_ok());
+                        if let Some(eq_index) = inp.find('=') {
+                            target_cell_ref.formula = inp[eq_index + 1..].trim().to_string();
+                        }
+                        drop(target_cell_ref);
+
+                    }
+                    let result = evaluate(&mut self.sheetstore.data, &a, &old_func);
+                    if let Err(strr) = &result {
+                        // A cycle puts the old formula back, so there is nothing to undo.
+                        if strr.contains("Cyclic dependency detected") || strr.contains("Negative sleep time") {
+                            let target_sheet = self.sheetstore.data[a.sheet as usize].borrow();
+                            target_sheet.data[a.col as usize].borrow()[a.row as usize].borrow_mut().formula = old_formula;
+                            return strr.clone();
+                        }
+                    }
+                    let changes = cell_changes(before, cell_states(&self.sheetstore, &a, &a));
+                    if !changes.is_empty() {
+                        self.history.push(Edit::Cells(changes));
+                    }
+                    if let Err(strr) = result
+                    {
+                        return strr;   
+                    }
+                }
+            }
+            last_err_msg
+    }
+
+    /// Moves the cursor to the next cell that the last `:find` matches, or to the previous one when `forward` is
+    /// false. The cells are searched again every time, in the order of `find_cells`, and the search starts over
+    /// when it runs past the last (or first) match.
+    ///
+    /// **Returns:**
+    /// - `"ok"` if the cursor moved to a match, otherwise the error message.
+    pub fn find_next(&mut self, forward: bool) -> String {
+        let Some(spec) = &self.find else {
+            return String::from("Nothing to find");
+        };
+        let found = find_cells(&self.sheetstore, self.curr_sheet_number as u32, spec);
+        let position = |sheet: usize| self.sheetstore.list_index_from_num(sheet).unwrap_or(0);
+        let key = |addr: &Addr| (position(addr.sheet as usize), addr.row as usize, addr.col as usize);
+        let cursor = (position(self.curr_sheet_number), self.curr_row, self.curr_col);
+        let next = if forward {
+            found.iter().find(|addr| key(addr) > cursor).or(found.first())
+        } else {
+            found.iter().rev().find(|addr| key(addr) < cursor).or(found.last())
+        };
+        match next {
+            Some(addr) => {
+                self.curr_sheet_number = addr.sheet as usize;
+                self.curr_row = addr.row as usize;
+                self.curr_col = addr.col as usize;
+                String::from("ok")
+            }
+            None => format!("No cell matches \"{}\"", spec.text),
+        }
+    }
+
+    /// Returns the numbers of the sheets a command changes as a whole, which `Edit::Layout` keeps for undo, or
+    /// `None` for the other commands.
+    fn layout_target(&self, cmd: &ast::OtherCommand) -> Option<Vec<usize>> {
+        match cmd {
+            ast::OtherCommand::Resize(name, ..) => Some(self.sheetstore.num_from_name(name).into_iter().collect()),
+            ast::OtherCommand::AddRow(a, _) | ast::OtherCommand::AddCol(a, _)
+            | ast::OtherCommand::RemoveRow(a, _) | ast::OtherCommand::RemoveCol(a, _) => Some(shifted_sheets(&self.sheetstore, a.sheet as usize)),
+            ast::OtherCommand::CondFormat(a, ..) | ast::OtherCommand::ClearCondFormat(a, _)
+            | ast::OtherCommand::Sort(a, ..) | ast::OtherCommand::Filter(a, _) => Some(vec![a.sheet as usize]),
+            ast::OtherCommand::ClearFilter(num) => Some(vec![*num as usize]),
+            _ => None,
+        }
+    }
+
+    /// Works out the rows the filter of the current sheet shows, unless they are known from an earlier call and no
+    /// command ran since.
+    pub fn update_shown_rows(&mut self) {
+        if !matches!(self.shown, Some((sheet, _)) if sheet == self.curr_sheet_number) {
+            self.shown = Some((self.curr_sheet_number, filtered_rows(&self.sheetstore.data, self.curr_sheet_number as u32)));
+        }
+    }
+
+    /// Returns the rows the filter of the current sheet shows as of the last `update_shown_rows`, or `None` if the
+    /// sheet has no filter.
+    pub fn shown_rows(&self) -> Option<&[u32]> {
+        match &self.shown {
+            Some((sheet, rows)) if *sheet == self.curr_sheet_number => rows.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Builds the edit for the sheet `name` that was just added, which undo removes again.
+    fn added_sheet(&self, name: &str) -> Option<Edit> {
+        let num = self.sheetstore.num_from_name(name)?;
+        let position = self.sheetstore.list_index_from_num(num)?;
+        Some(Edit::AddSheet{name: name.to_string(), num, position, sheet: Rc::clone(&self.sheetstore.data[num])})
+    }
+
+    /// Moves the cursor and the chart off sheets and cells that an undo or redo took away.
+    fn fix_view(&mut self) {
+        if self.sheetstore.name_from_num(self.curr_sheet_number).is_none() {
+            self.curr_sheet_number = self.sheetstore.map[0].1;
+            self.curr_col = 0;
+            self.curr_row = 0;
+        }
+        if self.sheetstore.name_from_num(self.outputs_widget.sheetnum).is_none() {
+            self.show_graph = false;
+        }
+        let sheet = self.sheetstore.data[self.curr_sheet_number].borrow();
+        self.curr_row = self.curr_row.min(sheet.rows.saturating_sub(1) as usize);
+        self.curr_col = self.curr_col.min(sheet.columns.saturating_sub(1) as usize);
+    }
+}
+
+/// Returns the row `count` shown rows above or below `row`, stopping at the first or last shown row. The cursor
+/// stays when no shown row lies that way.
+pub fn step_shown(shown: &[u32], row: usize, count: usize, up: bool) -> usize {
+    let (before, after): (Vec<usize>, Vec<usize>) = shown.iter().map(|r| *r as usize).filter(|r| *r != row).partition(|r| *r < row);
+    match up {
+        true if !before.is_empty() => before[before.len().saturating_sub(count)],
+        false if !after.is_empty() => after[count.min(after.len()) - 1],
+        _ => row,
+    }
+}
+
+/// Returns the rectangle of cells that a command writes to, for the commands that change cells.
+fn cell_target(cmd: &ast::OtherCommand) -> Option<(Addr, Addr)> {
+    match cmd {
+        ast::OtherCommand::CopyCellVals(_, dest) | ast::OtherCommand::CopyCellFormulae(_, dest) => Some((dest.clone(), dest.clone())),
+        ast::OtherCommand::CopyRangeVals(start, end, dest) | ast::OtherCommand::CopyRangeFormulae(start, end, dest) => {
+            Some((dest.clone(), Addr::new(dest.sheet, dest.row + end.row - start.row, dest.col + end.col - start.col)))
+        }
+        ast::OtherCommand::AutofillAp(start, end) | ast::OtherCommand::AutofillGp(start, end)
+        | ast::OtherCommand::Format(start, end, _) | ast::OtherCommand::ClearFormat(start, end) => Some((start.clone(), end.clone())),
+        _ => None,
+    }
+}
@@ -7,6 +7,9 @@ pub enum LexicalError {
     InvalidInteger(ParseIntError),
     InternalError(String),
     SheetNotFoundErr(String),
+    AddressOutOfRange(String),
+    RangeErr(String),
+    FormatErr(String),
     #[default]
     InvalidToken,
     
@@ -20,33 +23,28 @@ impl From<ParseIntError> for LexicalError {
 
 
 
-fn parse_local_cell(s: &str) -> Option<(u32, u32)> {
+/// Parses a cell like `B3` into (column, row). Anything past the `u32` range is an `AddressOutOfRange` error.
+fn parse_local_cell(s: &str) -> Result<(u32, u32), LexicalError> {
+    let out_of_range = || LexicalError::AddressOutOfRange(s.to_string());
     let (letters, digits) = s.chars()
         .partition::<String, _>(|c| c.is_ascii_alphabetic());
 
-    let row = digits.parse::<u32>().ok()? - 1;
+    let row = digits.parse::<u32>().map_err(|_| out_of_range())? - 1;
     let mut col: u32 = 0;
     for c in letters.chars() {
-        col = col*26 + (c as u8 - b'A' + 1) as u32; 
+        col = col.checked_mul(26)
+            .and_then(|col| col.checked_add((c as u8 - b'A' + 1) as u32))
+            .ok_or_else(out_of_range)?;
     }
     col -=1; //Zero based indexing
-    Some((col, row))
+    Ok((col, row))
 }
 
-fn parse_global_cell(s: &str) -> Option<(String, u32, u32)> {
+fn parse_global_cell(s: &str) -> Result<(String, u32, u32), LexicalError> {
 
     let (sheet, addr) = s.split_once(".").unwrap(); //NOTE: Source of panic, ensure it is correct.
-
-    let (letters, digits) = addr.chars()
-        .partition::<String, _>(|c| c.is_ascii_alphabetic());
-
-    let row = digits.parse::<u32>().ok()? - 1;
-    let mut col: u32 = 0;
-    for c in letters.chars() {
-        col = col*26 + (c as u8 - b'A' + 1) as u32; 
-    }
-    col -=1; //Zero based indexing
-    Some((sheet.to_string(), col, row))
+    let (col, row) = parse_local_cell(addr)?;
+    Ok((sheet.to_string(), col, row))
 }
 
 #[derive(Logos, Debug, Clone, PartialEq)]
@@ -72,9 +70,9 @@ pub enum Token {
     #[token("_")]
     Wildcard,
   
-    #[regex("[A-Z]{1,3}[1-9][0-9]{0,2}", |lex| parse_local_cell(lex.slice()))]
+    #[regex("[A-Z]+[1-9][0-9]*", |lex| parse_local_cell(lex.slice()))]
     LocalCell((u32, u32)),
-    #[regex("[a-z0-9_]+\\.[A-Z]{1,3}[1-9][0-9]{0,2}", |lex| parse_global_cell(lex.slice()))] //NOTE: Sheet names must be lower case in this implementation
+    #[regex("[a-z0-9_]+\\.[A-Z]+[1-9][0-9]*", |lex| parse_global_cell(lex.slice()))] //NOTE: Sheet names must be lower case in this implementation
     GlobalCell((String, u32, u32)),
 
     #[token("w")]
@@ -99,10 +97,25 @@ pub enum Token {
     LoadCsv,
     #[token("export_csv")]
     ExportCsv,
+    #[token("decimal")]
+    Decimal, //Option of load_csv
+    #[token("save")]
+    SaveWorkbook,
+    #[token("load")]
+    LoadWorkbook,
     
     #[token("resize")]
     Resize,
     
+    #[token("add_row")]
+    AddRow,
+    #[token("add_col")]
+    AddCol,
+    #[token("remove_row")]
+    RemoveRow,
+    #[token("remove_col")]
+    RemoveCol,
+
     #[token("add_sheet")]
     AddSheet,
     #[token("remove_sheet")]
@@ -119,8 +132,30 @@ pub enum Token {
     Undo,
     #[token("redo")]
     Redo,
+    #[token("undo_depth")]
+    UndoDepth,
     #[token("make_chart")]
     MakeChart,
+    #[token("format")]
+    Format,
+    #[token("clear_format")]
+    ClearFormat,
+    #[token("cond_format")]
+    CondFormat,
+    #[token("clear_cond_format")]
+    ClearCondFormat,
+    #[token("sort")]
+    Sort,
+    #[token("filter")]
+    Filter,
+    #[token("clear_filter")]
+    ClearFilter,
+    #[token("pivot")]
+    Pivot,
+    #[token("find")]
+    Find,
+    #[token("replace")]
+    Replace,
     #[token("copy_cell_value")]
     CopyCellValue,
     #[token("copy_cell_formula")]
@@ -0,0 +1,180 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str], script: &str) -> (bool, String) {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ext"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Could not start ext");
+        child.stdin.take().unwrap().write_all(script.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        (output.status.success(), String::from_utf8(output.stdout).unwrap())
+    }
+
+    #[test]
+    fn test_script_from_stdin() {
+        let script = "# two sheets\nA1=2\nB1=A1*3\n\n:add_sheet \"other\" 2 2\nother.A1=sheet0.B1+1\n";
+        let (ok, out) = run(&["3", "2", "--script", "-", "--print"], script);
+        assert!(ok);
+        assert_eq!(out, "2: A1=2 -> ok\n3: B1=A1*3 -> ok\n5: :add_sheet \"other\" 2 2 -> ok\n6: other.A1=sheet0.B1+1 -> ok\n[sheet0]\n2,6\n[other]\n7\n");
+    }
+
+    #[test]
+    fn test_script_reports_failures() {
+        let script = "A1=1\nA9=1\nB1=NOSUCH(A1)\n:q\nA2=1\n";
+        let path = std::env::temp_dir().join("ext_script_mode_test.cmds");
+        std::fs::write(&path, script).unwrap();
+        let (ok, out) = run(&["3", "2", "--script", path.to_str().unwrap()], "");
+        std::fs::remove_file(&path).unwrap();
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], "2: A9=1 -> Target address row out of range");
+        assert_eq!(lines[2], "3: B1=NOSUCH(A1) -> Unknown function NOSUCH");
+        assert_eq!(lines[3], "4: :q -> ok");
+    }
+
+    #[test]
+    fn test_undo_redo_every_command() {
+        let script = "A1=1\nA2=2\n:autofill_ap A1:A3\n:copy_range_values A1:A3 B1\n:undo\n:undo\n:redo\n\
+            :add_sheet \"other\" 2 2\nother.A1=sheet0.A3*2\n:undo\n:undo\n:redo\n:redo\n:remove_sheet \"other\"\n:undo\n\
+            :add_row A1\n:undo\nA3=5\n:undo_depth 1\n:undo\n:undo\n";
+        let (ok, out) = run(&["3", "2", "--script", "-", "--print"], script);
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[20], "21: :undo -> Already at the earliest change");
+        assert_eq!(lines[21..].join("\n"), "[sheet0]\n1,0\n2,0\n3,0\n[other]\n6");
+    }
+
+    #[test]
+    fn test_undo_layout_puts_back_the_sheets_it_changed() {
+        let script = "A1=1\nA2=2\nA3=3\n:add_sheet \"other\" 2 2\n:add_sheet \"plain\" 1 1\nother.A1=sheet0.A3*2\nplain.A1=7\n\
+            :add_row A1\n:sort A2:A4 \"A desc\"\n:undo\n:undo\nA3=10\n";
+        let (ok, out) = run(&["3", "1", "--script", "-", "--print"], script);
+        assert!(ok);
+        // The formula in the other sheet follows the inserted row and goes back with the undo.
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[12..], ["[sheet0]", "1", "2", "10", "[other]", "20", "[plain]", "7"]);
+    }
+
+    #[test]
+    fn test_undo_copy_into_cells_not_stored_yet() {
+        let script = "A1=1\nA2=2\n:copy_range_values A1:A2 C5\n:undo\n:redo\nD1=SUM(C5:C6)\n";
+        let (ok, out) = run(&["6", "4", "--script", "-", "--print"], script);
+        assert!(ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[6..8], ["[sheet0]", "1,,0,3"]);
+    }
+
+    #[test]
+    fn test_format_commands() {
+        let path = std::env::temp_dir().join("ext_script_mode_format_test.wb");
+        let path = path.to_str().unwrap();
+        let script = format!(":format A1:B2 \"decimals=2,bold\"\n:format A1 \"bg=#ff0000\"\n:clear_format B1:B2\n:undo\n\
+            :format A1 \"decimals=many\"\n:format A1:other.B2 \"bold\"\n:save \"{}\"\n", path);
+        let (ok, out) = run(&["3", "2", "--script", "-"], &script);
+        let saved = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[4], "5: :format A1 \"decimals=many\" -> Invalid format option \"decimals=many\"");
+        assert_eq!(lines[5], "6: :format A1:other.B2 \"bold\" -> Sheet name \"other\" not found.");
+        let formats: Vec<&str> = saved.lines().filter(|line| line.starts_with("FORMAT")).collect();
+        assert_eq!(formats, ["FORMAT\tA1\tdecimals=2,bg=#ff0000,bold", "FORMAT\tA2\tdecimals=2,bold",
+            "FORMAT\tB1\tdecimals=2,bold", "FORMAT\tB2\tdecimals=2,bold"]);
+    }
+
+    #[test]
+    fn test_conditional_format_commands() {
+        let path = std::env::temp_dir().join("ext_script_mode_cond_format_test.wb");
+        let path = path.to_str().unwrap();
+        let script = format!("A1=150\n:cond_format A1:B2 \"_>100\" \"bg=red\"\n:cond_format A1:A2 \"_<0\" \"fg=blue\"\n:undo\n\
+            :cond_format A1:B2 \"_>\" \"bold\"\n:clear_cond_format C1:C3\n:add_row A1\n:save \"{}\"\n", path);
+        let (ok, out) = run(&["3", "3", "--script", "-"], &script);
+        let saved = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[4], "5: :cond_format A1:B2 \"_>\" \"bold\" -> Invalid condition \"_>\"");
+        assert_eq!(lines[5], "6: :clear_cond_format C1:C3 -> No conditional format in the range");
+        let rules: Vec<&str> = saved.lines().filter(|line| line.starts_with("RULE")).collect();
+        assert_eq!(rules, ["RULE\tA2:B3\t_>100\tbg=red"]);
+    }
+
+    #[test]
+    fn test_sort_command() {
+        let script = "A1=3\nA2=1\nA3=2\nB1=A1*10\nB2=A2*10\nB3=A3*10\nC1=B1\n:sort A1:B3 \"A desc\"\n:sort A1:B3 \"C\"\n\
+            :sort A1:B3 \"A\"\n:undo\n";
+        let (ok, out) = run(&["3", "3", "--script", "-", "--print"], script);
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[8], "9: :sort A1:B3 \"C\" -> Sort column C is outside the range");
+        assert_eq!(lines[9], "10: :sort A1:B3 \"A\" -> ok");
+        assert_eq!(lines[11..], ["[sheet0]", "3,30,30", "2,20,", "1,10,"]);
+    }
+
+    #[test]
+    fn test_filter_commands() {
+        let script = "A1=1\nA2=200\n:filter A2 \"_>\"\n:filter A2 \"_>100\"\n:clear_filter\n:clear_filter\n:undo\n:clear_filter\n";
+        let (ok, out) = run(&["2", "3", "--script", "-"], script);
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[2..], ["3: :filter A2 \"_>\" -> Invalid condition \"_>\"", "4: :filter A2 \"_>100\" -> ok",
+            "5: :clear_filter -> ok", "6: :clear_filter -> No filter on the sheet", "7: :undo -> ok", "8: :clear_filter -> ok"]);
+    }
+
+    #[test]
+    fn test_pivot_command() {
+        let script = "A1=\"b\"\nA2=\"a\"\nA3=\"b\"\nB1=1\nB2=2\nB3=3\n:pivot A1:B3 \"rows=A,sum=B\" \"totals\"\nB1=10\n\
+            :pivot A1:B3 \"rows=A,sum=C\" \"other\"\n:pivot A1:B3 \"rows=A,avg=B\" \"totals\"\n:pivot A1:B3 \"rows=A,avg=B\" \"means\"\n:undo\n";
+        let (ok, out) = run(&["3", "2", "--script", "-", "--print"], script);
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[8..12], ["9: :pivot A1:B3 \"rows=A,sum=C\" \"other\" -> Pivot column C is outside the range",
+            "10: :pivot A1:B3 \"rows=A,avg=B\" \"totals\" -> Sheet name \"totals\" already exists.",
+            "11: :pivot A1:B3 \"rows=A,avg=B\" \"means\" -> ok", "12: :undo -> ok"]);
+        assert_eq!(lines[16..], ["[totals]", "A,SUM of B", "a,2", "b,13"]);
+    }
+
+    #[test]
+    fn test_find_and_replace_commands() {
+        let script = "A1=1\nA2=A1+1\nB2=A1*A2\n:find \"3\"\n:find \"2\"\n:find \"A\" \"words\"\n\
+            :replace \"A1\" \"A3\"\n:replace \"A(\\d)\" \"B$1\" \"regex\"\n:undo\n:replace \"A3\" \"A9\"\n";
+        let (ok, out) = run(&["3", "2", "--script", "-", "--print"], script);
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[3..10], ["4: :find \"3\" -> No cell matches \"3\"", "5: :find \"2\" -> ok",
+            "6: :find \"A\" \"words\" -> Invalid find option \"words\"", "7: :replace \"A1\" \"A3\" -> ok",
+            "8: :replace \"A(\\d)\" \"B$1\" \"regex\" -> Replacing in B2: Cyclic dependency detected at cell (2, 2)",
+            "9: :undo -> ok",
+            "10: :replace \"A3\" \"A9\" -> Replacing gives an invalid formula \"A9+1\" in A2"]);
+        assert_eq!(lines[10..], ["[sheet0]", "1,0", "1,0", "0,0"]);
+    }
+
+    #[test]
+    fn test_load_csv_options() {
+        let path = std::env::temp_dir().join("ext_script_mode_test.tsv");
+        std::fs::write(&path, "name\tqty\nbolt\t12\nnut\n").unwrap();
+        let path = path.to_str().unwrap();
+        let script = format!(":load_csv \"{0}\" \"parts\" \"delimiter=tab,header\"\n:load_csv \"{0}\" \"parts\" \"delimiter=tab,ragged,header=skip\"\n\
+            :load_csv \"{0}\" \"parts\" \"tabs\"\n", path);
+        let (ok, out) = run(&["2", "2", "--script", "-", "--print"], &script);
+        std::fs::remove_file(path).unwrap();
+        assert!(!ok);
+        let lines: Vec<&str> = out.lines().map(|line| line.split_once(" -> ").map_or(line, |(_, status)| status)).collect();
+        assert_eq!(lines, ["Error occured during import: Row 3 of the csv has 1 fields but row 1 has 2, use the ragged option to allow this",
+            "ok", "Invalid csv option \"tabs\"", "[sheet0]", "[parts]", "bolt,12", "nut,"]);
+
+        // Without a sheet name the sheet is named after the file, which is relative to the working directory.
+        std::fs::write("ext_csv_opts.csv", "qty;ok\n7;true\n").unwrap();
+        let (ok, out) = run(&["2", "2", "--script", "-", "--print"], ":load_csv \"ext_csv_opts.csv\" \"\" \"delimiter=semicolon,header=skip\"\n");
+        std::fs::remove_file("ext_csv_opts.csv").unwrap();
+        assert!(ok);
+        assert_eq!(out, "1: :load_csv \"ext_csv_opts.csv\" \"\" \"delimiter=semicolon,header=skip\" -> ok\n[sheet0]\n[ext_csv_opts]\n7,true\n");
+    }
+}
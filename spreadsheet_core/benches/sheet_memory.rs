@@ -0,0 +1,55 @@
+//! Memory use of the cell storage, measured by counting the bytes the allocator hands out.
+//!
+//! Run with `cargo bench -p spreadsheet_core --bench sheet_memory`.
+use spreadsheet_core::cell_operations::{Sheet, ValueType};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct CountingAlloc;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+// The largest sheet the original assignment allowed: columns A to ZZZ and 999 rows.
+const ROWS: u32 = 999;
+const COLS: u32 = 18278;
+
+/// Builds a sheet and writes `cells` into it, then prints how many bytes stay allocated for it.
+fn measure(name: &str, cells: impl Iterator<Item = (u32, u32)>) {
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let sheet = Sheet::new(0, COLS, ROWS);
+    for (col, row) in cells {
+        sheet.extend_to(col as usize, row as usize);
+        let column = sheet.data[col as usize].borrow();
+        column[row as usize].borrow_mut().value = ValueType::IntegerValue((row + col) as i64);
+    }
+    let bytes = LIVE_BYTES.load(Ordering::Relaxed) - before;
+    println!("{:<28} {:>9} cells {:>12} bytes {:>8.1} ms", name, sheet.cell_count(), bytes, start.elapsed().as_secs_f64() * 1000.0);
+    drop(sheet);
+}
+
+fn main() {
+    println!("sheet of {} columns x {} rows", COLS, ROWS);
+    measure("empty", std::iter::empty());
+    // A few values spread over the whole sheet, including the bottom right corner.
+    measure("100 scattered values", (0..100u32).map(|i| ((i * 7919) % COLS, (i * 104_729) % ROWS)).chain([(COLS - 1, ROWS - 1)]));
+    measure("one full column", (0..ROWS).map(|row| (0, row)));
+    measure("full 100 column block", (0..100).flat_map(|col| (0..ROWS).map(move |row| (col, row))));
+    measure("bottom right corner only", std::iter::once((COLS - 1, ROWS - 1)));
+}
@@ -0,0 +1,552 @@
+#![allow(unused)]
+use std::cmp::{PartialEq, Eq, Ordering, PartialOrd, Ord};
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use crate::cell_operations::{Date, Decimal, FormatOption, ValueType};
+use crate::csv_operations::CsvOptions;
+use crate::sheet_operations::{FindSpec, PivotSpec, SortKey};
+use crate::SheetStorage;
+
+
+// pub enum CellRef {
+//     /// Reference within the current sheet
+//     Local {
+//         row: usize,
+//         col: usize,
+//     },
+//     /// Reference to a different sheet
+//     Global {
+//         sheet: usize,
+//         row: usize,
+//         col: usize,
+//     },
+// }
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    DisplayCmd(DisplayCommand),  //Note: IS Box<DisplayCommand> better? Display Command is a finite data type, but expr was not.
+    OtherCmd(OtherCommand),
+    AssignCmd(Addr, Box<Expr>),
+    DefineName(String, NameDef),
+    Quit,
+}
+
+#[derive(Debug, Clone)]
+pub enum DisplayCommand {
+    EnableOut,
+    DisableOut,
+    ScrollTo(Addr),
+    Find(FindSpec), //Moves to the next cell it matches
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+}
+
+#[derive(Debug, Clone)]
+pub enum OtherCommand {
+    AddSheet(String, usize, usize),
+    RemoveSheet(String),
+    RenameSheet(String, String),
+    DuplicateSheet(String, Option<String>),
+    Undo,
+    Redo,
+    UndoDepth(usize), //Number of commands that can be undone
+    // Help(String) //Display help for the command
+    // List //Display list of all commands
+
+    AddRow(Addr, usize), //Row of the address, number of rows
+    AddCol(Addr, usize), //Column of the address, number of columns
+    RemoveRow(Addr, usize),
+    RemoveCol(Addr, usize),
+
+    MakeChart(Addr, Addr,Addr, Addr),
+
+    Format(Addr, Addr, Vec<FormatOption>), //Range, options applied to every cell of it
+    ClearFormat(Addr, Addr),
+    CondFormat(Addr, Addr, Expr, Vec<FormatOption>), //Range, condition on `_`, options applied where it holds
+    ClearCondFormat(Addr, Addr),
+
+    Sort(Addr, Addr, Vec<SortKey>), //Range, keys in the order they are compared
+    Filter(Addr, Expr), //First filtered cell of the key column, condition on `_`
+    ClearFilter(u32), //Sheet number
+    Pivot(Addr, Addr, PivotSpec, String), //Source range, what to summarise, name of the new sheet
+    Replace(FindSpec, String), //What to replace in the formulas, replacement
+
+    CopyCellVals(Addr, Addr),
+    CopyRangeVals(Addr, Addr, Addr),
+    CopyCellFormulae(Addr, Addr),
+    CopyRangeFormulae(Addr, Addr, Addr),
+
+    AutofillAp(Addr, Addr),
+    AutofillGp(Addr, Addr),
+
+    ExportCsv(String),
+    LoadCsv(String, Option<String>, CsvOptions), //File, SheetName, how the file is read
+    SaveWorkbook(String),
+    LoadWorkbook(String),
+    Resize(String, usize, usize)
+    //Graph ke commands daal dena @ExactHarmony917
+    //ML wale commands daal dena @ExactHarmony917
+    
+
+}
+
+
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Bool(bool),
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Date(Date),
+    Decimal(Decimal),
+    Cell(Addr),
+    Wildcard,
+    MonoOp(MonoFunction, Box<Expr>),
+    RangeOp{op: RangeFunction, start: Addr, end: Addr, cond: Box<Expr>, name: Option<String>}, //Note: Should addr be under Box<>? name is set when the range was given by a defined name
+    InfixOp(Box<Expr>, InfixFunction, Box<Expr>),
+    BinOp(BinaryFunction, Box<Expr>, Box<Expr>),
+    TernaryOp(TernaryFunction, Box<Expr>, Box<Expr>, Box<Expr>),
+    LookupOp{op: LookupFunction, start: Addr, end: Addr, args: Vec<Expr>, name: Option<String>},
+    Call(String, Vec<Expr>), //Function from the registry in evaluate_operations, called by name
+    RefError, //Reference to a cell that was deleted
+    Name(String, Box<Expr>), //A defined name used as a value, with the cell or constant it stood for when the formula was parsed
+}
+
+/// What a name defined with `name=...` stands for. Addresses of names are absolute, so copying a formula
+/// does not move them.
+#[derive(Debug, Clone)]
+pub enum NameDef {
+    Cell(Addr),
+    Range(Addr, Addr),
+    Constant(Expr),
+}
+
+impl NameDef {
+    /// # Details
+    /// Prints the definition the way it is typed after `name=`, with the sheet name on every address.
+    /// A name of a cell in a removed sheet is printed as `#REF!`.
+    pub fn to_formula(&self, sheetstore: &SheetStorage) -> String {
+        let removed = |addr: &Addr| sheetstore.name_from_num(addr.sheet as usize).is_none();
+        match self {
+            NameDef::Cell(addr) | NameDef::Range(addr, _) if removed(addr) => String::from("#REF!"),
+            NameDef::Cell(addr) => addr.to_formula(u32::MAX, sheetstore),
+            NameDef::Range(start, end) => format!("{}:{}", start.to_formula(u32::MAX, sheetstore), end.to_formula(u32::MAX, sheetstore)),
+            NameDef::Constant(exp) => exp.to_formula(u32::MAX, sheetstore),
+        }
+    }
+}
+
+pub enum ParentType {
+    Single(Addr),
+    Range(Addr, Addr),
+}
+impl std::fmt::Debug for ParentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParentType:: Single(addr) => write!(f, "Single({:?})", addr),
+            ParentType::Range(start, end) => write!(f, "Range({:?}, {:?})", start, end),
+        }
+    }
+}
+impl Expr
+{
+    // pub enum ParentType {
+    //     Single(Addr),
+    //     Range(Addr, Addr),
+    // }
+
+
+    pub fn get_dependency_list (&self) -> Vec<ParentType> 
+    {
+        match self 
+        {
+            Expr::Integer(_) => vec![],
+            Expr::String(_) => vec![],
+            Expr::Bool(_) => vec![],
+            Expr::Float(_) => vec![],
+            Expr::Date(_) => vec![],
+            Expr::Decimal(_) => vec![],
+            Expr::Cell(addr) => vec![ParentType::Single(addr.clone())],
+            Expr::MonoOp(_, expr) => expr.get_dependency_list(),
+            Expr::RangeOp{start, end, ..} => vec![ParentType::Range(start.clone(), end.clone())],
+            Expr::InfixOp(left, _, right) => {
+                let mut deps = left.get_dependency_list();
+                deps.append(&mut right.get_dependency_list());
+                deps
+            }
+            Expr::BinOp(_, left, right) => {
+                let mut deps = left.get_dependency_list();
+                deps.append(&mut right.get_dependency_list());
+                deps
+            }
+            Expr::TernaryOp(_, cond, true_expr, false_expr) => {
+                let mut deps = cond.get_dependency_list();
+                deps.append(&mut true_expr.get_dependency_list());
+                deps.append(&mut false_expr.get_dependency_list());
+                deps
+            }
+            Expr::LookupOp{start, end, args, ..} => {
+                let mut deps = vec![ParentType::Range(start.clone(), end.clone())];
+                for arg in args {
+                    deps.append(&mut arg.get_dependency_list());
+                }
+                deps
+            }
+            Expr::Call(_, args) => args.iter().flat_map(|arg| arg.get_dependency_list()).collect(),
+            Expr::Wildcard => vec![], 
+            Expr::RefError => vec![],
+            Expr::Name(_, exp) => exp.get_dependency_list(),
+
+        }
+    }
+
+    /// # Details
+    /// Prints the expression back as formula text that `AssignParser` accepts. Infix operands that are
+    /// themselves infix expressions are wrapped in brackets, so the result may have more brackets than what was typed.
+    ///
+    /// **Arguments:**
+    /// - `curr_sheet`: Sheet number of the cell holding the expression. Addresses in this sheet are printed as local addresses.
+    /// - `sheetstore`: The store used to look up names of other sheets.
+    ///
+    /// **Returns:**
+    /// - `String` containing the formula.
+    pub fn to_formula(&self, curr_sheet: u32, sheetstore: &SheetStorage) -> String
+    {
+        let bracketed = |e: &Expr| -> String {
+            match e {
+                Expr::InfixOp(..) => format!("({})", e.to_formula(curr_sheet, sheetstore)),
+                _ => e.to_formula(curr_sheet, sheetstore)
+            }
+        };
+        match self 
+        {
+            Expr::Integer(n) => n.to_string(),
+            Expr::Float(n) => format!("{:?}", n),
+            Expr::Date(d) => d.to_literal(),
+            Expr::Decimal(d) => d.to_literal(),
+            Expr::Bool(b) => bool_literal(*b),
+            Expr::String(s) => string_literal(s),
+            Expr::Wildcard => String::from("_"),
+            Expr::RefError => String::from("#REF!"),
+            Expr::Cell(addr) => addr.to_formula(curr_sheet, sheetstore),
+            Expr::MonoOp(f, e) => format!("{}({})", f.name(), e.to_formula(curr_sheet, sheetstore)),
+            Expr::Name(name, _) => name.clone(),
+            Expr::RangeOp{op, start, end, cond, name} => {
+                let range = match name {
+                    Some(name) => name.clone(),
+                    None => format!("{}:{}", start.to_formula(curr_sheet, sheetstore), end.to_formula(curr_sheet, sheetstore)),
+                };
+                match cond.as_ref() {
+                    Expr::Bool(true) => format!("{}({})", op.name(), range),
+                    _ => format!("{}({}, {})", op.name(), range, cond.to_formula(curr_sheet, sheetstore))
+                }
+            },
+            Expr::InfixOp(left, f, right) => format!("{}{}{}", bracketed(left), f.symbol(), bracketed(right)),
+            Expr::BinOp(f, e1, e2) => format!("{}({}, {})", f.name(), e1.to_formula(curr_sheet, sheetstore), e2.to_formula(curr_sheet, sheetstore)),
+            Expr::TernaryOp(f, e1, e2, e3) => format!("{}({}, {}, {})", f.name(), e1.to_formula(curr_sheet, sheetstore), e2.to_formula(curr_sheet, sheetstore), e3.to_formula(curr_sheet, sheetstore)),
+            Expr::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(|e| e.to_formula(curr_sheet, sheetstore)).collect();
+                format!("{}({})", name, args.join(", "))
+            },
+            Expr::LookupOp{op, start, end, args, name} => {
+                let range = match name {
+                    Some(name) => name.clone(),
+                    None => format!("{}:{}", start.to_formula(curr_sheet, sheetstore), end.to_formula(curr_sheet, sheetstore)),
+                };
+                let args: Vec<String> = args.iter().map(|e| e.to_formula(curr_sheet, sheetstore)).collect();
+                match op {
+                    LookupFunction::Index | LookupFunction::Key | LookupFunction::GroupBy(_) => format!("{}({}, {})", op.name(), range, args.join(", ")),
+                    _ => format!("{}({}, {}{})", op.name(), args[0], range, args[1..].iter().map(|a| format!(", {}", a)).collect::<String>()),
+                }
+            },
+        }
+    }
+
+}
+
+// pub enum Addr {
+//     Local { row: u32, col: u32}, //NOTE: check all different int datatypes used a at different places and verify.
+//     Global {sheet: u32, row: u32, col: u32} //NOTE: sheet should be String or str or &str or something else?!?.
+// }
+
+/// The address of a cell. `abs_row` and `abs_col` record a `$` before the row or column in a formula; they only
+/// matter when a formula is copied, so comparing and hashing addresses ignores them.
+#[derive(Debug, Clone)]
+pub struct Addr {
+    pub sheet: u32,
+    pub row: u32,
+    pub col: u32,
+    pub abs_row: bool,
+    pub abs_col: bool,
+}
+
+impl Addr {
+    /// # Details
+    /// Creates a relative address.
+    pub fn new(sheet: u32, row: u32, col: u32) -> Self {
+        Addr { sheet, row, col, abs_row: false, abs_col: false }
+    }
+
+    /// # Details
+    /// Moves the relative parts of the address by an offset, used when a formula is copied to another cell.
+    ///
+    /// **Returns:**
+    /// - `Some(Addr)` with the moved address.
+    /// - `None` if the moved address would be before the first row or column.
+    pub fn translate(&self, row_off: i64, col_off: i64) -> Option<Addr> {
+        let mut addr = self.clone();
+        if !self.abs_row {
+            addr.row = u32::try_from(self.row as i64 + row_off).ok()?;
+        }
+        if !self.abs_col {
+            addr.col = u32::try_from(self.col as i64 + col_off).ok()?;
+        }
+        Some(addr)
+    }
+
+    /// # Details
+    /// Prints the address the way it is typed in a formula (eg. `B3` or `sheet1.B3`).
+    ///
+    /// **Arguments:**
+    /// - `curr_sheet`: Sheet number of the cell holding the formula. Addresses in this sheet are printed without a sheet name.
+    /// - `sheetstore`: The store used to look up the sheet name.
+    ///
+    /// **Returns:**
+    /// - `String` containing the address.
+    pub fn to_formula(&self, curr_sheet: u32, sheetstore: &SheetStorage) -> String {
+        let dollar = |abs: bool| if abs { "$" } else { "" };
+        let local = format!("{}{}{}{}", dollar(self.abs_col), col_to_name(self.col), dollar(self.abs_row), self.row + 1);
+        if self.sheet == curr_sheet {
+            return local;
+        }
+        match sheetstore.name_from_num(self.sheet as usize) {
+            Some(name) => format!("{}.{}", name, local),
+            None => local
+        }
+    }
+}
+
+/// # Details
+/// Converts a zero based column number to its letters (0 -> `A`, 26 -> `AA`).
+pub fn col_to_name(col: u32) -> String {
+    let mut curr = String::new();
+    let mut curr_col = col + 1;
+    while curr_col > 0
+    {
+        curr.push(((b'A') + ((curr_col-1) % 26) as u8) as char);
+        curr_col -= 1;
+        curr_col /= 26;
+    }
+    curr.chars().rev().collect::<String>()
+}
+
+/// Gives the formula text of a boolean, `True` or `False`.
+pub fn bool_literal(b: bool) -> String {
+    String::from(if b { "True" } else { "False" })
+}
+
+/// Gives the formula text of a string: quoted with `'` if it holds a `"` but no `'`, otherwise quoted with `"` and
+/// any `"` inside doubled.
+pub fn string_literal(s: &str) -> String {
+    if s.contains('"') && !s.contains('\'') { format!("'{}'", s) } else { format!("\"{}\"", s.replace('"', "\"\"")) }
+}
+
+/// Converts the letters of a column to its zero based number (`A` -> 0, `AA` -> 26), or returns `None` if they are
+/// not upper case letters or the column is too large.
+pub fn name_to_col(name: &str) -> Option<u32> {
+    if name.is_empty() || !name.bytes().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let mut col: u32 = 0;
+    for c in name.bytes() {
+        col = col.checked_mul(26)?.checked_add((c - b'A' + 1) as u32)?;
+    }
+    Some(col - 1)
+}
+
+impl PartialOrd for Addr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Addr {
+    fn eq(&self, other: &Self) -> bool {
+        self.sheet == other.sheet && self.row == other.row && self.col == other.col
+    }
+}
+
+impl Eq for Addr {}
+
+impl Hash for Addr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.sheet, self.row, self.col).hash(state);
+    }
+}
+
+impl Ord for Addr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sheet
+            .cmp(&other.sheet)
+            .then(self.row.cmp(&other.row))
+            .then(self.col.cmp(&other.col))
+    }
+}
+
+
+// #[derive(Debug)]
+// pub enum Range {
+
+// }
+
+#[derive(Debug, Clone)]
+pub enum MonoFunction {
+    Sleep,
+    Not,
+    IsError,
+}
+
+impl MonoFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MonoFunction::Sleep => "SLEEP",
+            MonoFunction::Not => "NOT",
+            MonoFunction::IsError => "ISERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeFunction {
+    Sum,
+    Avg,
+    Max,
+    Min,
+    Stdev,
+    Count
+}
+
+impl RangeFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RangeFunction::Sum => "SUM",
+            RangeFunction::Avg => "AVG",
+            RangeFunction::Max => "MAX",
+            RangeFunction::Min => "MIN",
+            RangeFunction::Stdev => "STDEV",
+            RangeFunction::Count => "COUNT",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BinaryFunction {
+    Round,
+    IsSubstr,
+    IfError,
+}
+
+impl BinaryFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BinaryFunction::Round => "ROUND",
+            BinaryFunction::IsSubstr => "isSubstr",
+            BinaryFunction::IfError => "IFERROR",
+        }
+    }
+}
+
+/// Functions that look up a value in a table. The range is kept apart from the other arguments:
+/// `VLOOKUP(key, range, col)`, `HLOOKUP(key, range, row)`, `INDEX(range, row, col)` and `MATCH(key, range)`.
+#[derive(Debug, Clone)]
+pub enum LookupFunction {
+    VLookup,
+    HLookup,
+    Index,
+    Match,
+    GroupBy(RangeFunction), //Aggregate of a column over the rows whose key columns match given keys
+    Key, //The n-th distinct value of a column, in sorted order
+}
+
+impl LookupFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LookupFunction::VLookup => "VLOOKUP",
+            LookupFunction::HLookup => "HLOOKUP",
+            LookupFunction::Index => "INDEX",
+            LookupFunction::Match => "MATCH",
+            LookupFunction::Key => "KEY",
+            LookupFunction::GroupBy(f) => match f {
+                RangeFunction::Sum => "SUMBY",
+                RangeFunction::Avg => "AVGBY",
+                RangeFunction::Max => "MAXBY",
+                RangeFunction::Min => "MINBY",
+                RangeFunction::Stdev => "STDEVBY",
+                RangeFunction::Count => "COUNTBY",
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TernaryFunction {
+    IfThenElse
+}
+
+impl TernaryFunction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TernaryFunction::IfThenElse => "IFELSE",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum InfixFunction {
+    Mul,
+    Div,
+    Add,
+    Sub,
+    Pow,
+    FloorDiv,
+    Mod,
+
+    Eq,
+    Neq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+    And,
+    Or,
+    // Not,
+    
+    Concat,
+    
+}
+
+impl InfixFunction {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            InfixFunction::Mul => "*",
+            InfixFunction::Div => "/",
+            InfixFunction::Add => "+",
+            InfixFunction::Sub => "-",
+            InfixFunction::Pow => "**",
+            InfixFunction::FloorDiv => "//",
+            InfixFunction::Mod => "%",
+            InfixFunction::Eq => "==",
+            InfixFunction::Neq => "!=",
+            InfixFunction::Gt => ">",
+            InfixFunction::GtEq => ">=",
+            InfixFunction::Lt => "<",
+            InfixFunction::LtEq => "<=",
+            InfixFunction::And => "&&",
+            InfixFunction::Or => "||",
+            InfixFunction::Concat => "^",
+        }
+    }
+}
@@ -0,0 +1,1136 @@
+///////////////// ONLY COMPLETED TOKENS FOR NUMERAL CELL FUNCS; STRING CELL FUNCS NOT DONE
+///////////////// HAVE TO MAKE LEXER BY OWN 😢  FOR COMPLEX FUNCTIONS AS PROPOSED       // ban gaya yay
+use crate::ast::{Expr, Addr};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Index;
+// #[allow(unused_imports)]
+use std::rc::Rc;
+use std::vec;
+
+
+
+/// Kinds of errors a formula can evaluate to. Each kind is shown with its own code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorKind
+{
+    DivByZero,
+    TypeMismatch,
+    BadRef,
+    Cycle,
+    DeletedSheet,
+    Num,
+    NotFound,
+}
+
+impl ErrorKind
+{
+    /// Returns the code shown in place of the value of a cell with this error.
+    pub fn code(&self) -> &'static str
+    {
+        match self
+        {
+            ErrorKind::DivByZero => "#DIV/0!",
+            ErrorKind::TypeMismatch => "#VALUE!",
+            ErrorKind::BadRef => "#REF!",
+            ErrorKind::Cycle => "#CYCLE!",
+            ErrorKind::DeletedSheet => "#SHEET!",
+            ErrorKind::Num => "#NUM!",
+            ErrorKind::NotFound => "#N/A",
+        }
+    }
+}
+
+/// An error value of a cell. The message says where the error came from and is shown in the cell details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellError
+{
+    pub kind: ErrorKind,
+    pub msg: String,
+}
+
+impl CellError
+{
+    pub fn new(kind: ErrorKind, msg: impl Into<String>) -> Self
+    {
+        CellError { kind, msg: msg.into() }
+    }
+}
+
+impl std::fmt::Display for CellError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{} {}", self.kind.code(), self.msg)
+    }
+}
+
+/// A date, with an optional time of day. Kept as days since 1970-01-01 and seconds into the day, so dates compare
+/// and subtract as plain numbers. Years go from 1 to 9999.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date
+{
+    pub days: i32,
+    pub secs: u32,
+}
+
+const SECS_PER_DAY: i64 = 86400;
+
+fn is_leap_year(year: i32) -> bool
+{
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32
+{
+    match month
+    {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl Date
+{
+    /// Makes a date from a year, month and day, or returns `None` if there is no such day.
+    pub fn from_ymd(year: i32, month: u32, day: u32) -> Option<Date>
+    {
+        if !(1..=9999).contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month)
+        {
+            return None;
+        }
+        // Counts days in years starting on 1st March, so the leap day is the last day of a year.
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let year_of_era = y.rem_euclid(400);
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era as u32 * 365 + year_of_era as u32 / 4 - year_of_era as u32 / 100 + day_of_year;
+        Some(Date { days: era * 146097 + day_of_era as i32 - 719468, secs: 0 })
+    }
+
+    /// Returns the year, month and day of the date.
+    pub fn ymd(&self) -> (i32, u32, u32)
+    {
+        let z = self.days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097) as u32;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era as i32 + era * 400;
+        (if month <= 2 { year + 1 } else { year }, month, day)
+    }
+
+    /// Returns the current date in UTC.
+    pub fn today() -> Date
+    {
+        let secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Date { days: (secs / SECS_PER_DAY as u64) as i32, secs: 0 }
+    }
+
+    /// Parses `YYYY-MM-DD`, optionally followed by a time `HH:MM` or `HH:MM:SS` after a `T` or a space.
+    pub fn parse(s: &str) -> Option<Date>
+    {
+        let (date, time) = match s.split_once(['T', ' ']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (s, None),
+        };
+        let mut parts = date.splitn(3, '-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if year.len() != 4 || month.len() != 2 || day.len() != 2
+        {
+            return None;
+        }
+        let mut result = Date::from_ymd(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)?;
+        if let Some(time) = time
+        {
+            let fields: Vec<u32> = time.split(':').map(|f| if f.len() == 2 { f.parse().ok() } else { None }).collect::<Option<_>>()?;
+            let (hour, min, sec) = match fields[..] {
+                [hour, min] => (hour, min, 0),
+                [hour, min, sec] => (hour, min, sec),
+                _ => return None,
+            };
+            if hour > 23 || min > 59 || sec > 59
+            {
+                return None;
+            }
+            result.secs = hour * 3600 + min * 60 + sec;
+        }
+        Some(result)
+    }
+
+    /// Moves the date by a number of days. A fraction of a day moves the time of day.
+    /// Returns `None` if the result falls outside the years 1 to 9999.
+    pub fn add_days(&self, days: f64) -> Option<Date>
+    {
+        let total = self.days as i64 * SECS_PER_DAY + self.secs as i64 + (days * SECS_PER_DAY as f64).round() as i64;
+        let result = Date { days: total.div_euclid(SECS_PER_DAY) as i32, secs: total.rem_euclid(SECS_PER_DAY) as u32 };
+        (1..=9999).contains(&result.ymd().0).then_some(result)
+    }
+
+    /// Returns the number of days from `other` to this date, with the time of day as a fraction.
+    pub fn days_since(&self, other: &Date) -> f64
+    {
+        (self.days - other.days) as f64 + (self.secs as f64 - other.secs as f64) / SECS_PER_DAY as f64
+    }
+
+    /// Returns the date as it is written in a formula, with a `T` before the time of day.
+    pub fn to_literal(&self) -> String
+    {
+        self.to_string().replace(' ', "T")
+    }
+}
+
+impl std::fmt::Display for Date
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let (year, month, day) = self.ymd();
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if self.secs != 0
+        {
+            write!(f, " {:02}:{:02}:{:02}", self.secs / 3600, self.secs / 60 % 60, self.secs % 60)?;
+        }
+        Ok(())
+    }
+}
+
+/// Most digits a decimal keeps after the point. Products and quotients that need more are rounded to this many.
+pub const DECIMAL_MAX_SCALE: u32 = 18;
+
+/// An exact decimal number for sums of money and the like, where `0.1+0.2` must be `0.3`. Kept as an integer
+/// `mantissa` and the number of digits after the point, so `12.50` is 1250 with scale 2 and keeps its trailing zero.
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal
+{
+    mantissa: i128,
+    scale: u32,
+}
+
+fn pow10(exp: u32) -> Option<i128>
+{
+    10i128.checked_pow(exp)
+}
+
+/// Divides and rounds half away from zero, so 2.5 rounds to 3 and -2.5 to -3.
+fn div_round(n: i128, d: i128) -> i128
+{
+    let (q, r) = (n / d, n % d);
+    if r.unsigned_abs() >= d.unsigned_abs() - r.unsigned_abs()
+    {
+        if (n < 0) != (d < 0) { q - 1 } else { q + 1 }
+    }
+    else
+    {
+        q
+    }
+}
+
+impl Decimal
+{
+    /// Makes the decimal `mantissa / 10^scale`, or returns `None` if the scale is above `DECIMAL_MAX_SCALE`.
+    pub fn new(mantissa: i128, scale: u32) -> Option<Decimal>
+    {
+        (scale <= DECIMAL_MAX_SCALE).then_some(Decimal { mantissa, scale })
+    }
+
+    pub fn from_int(n: i64) -> Decimal
+    {
+        Decimal { mantissa: n as i128, scale: 0 }
+    }
+
+    /// Parses an optional `-`, digits and optionally a point and more digits, as in `-12.50`.
+    pub fn parse(s: &str) -> Option<Decimal>
+    {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) || (digits.contains('.') && frac.is_empty())
+        {
+            return None;
+        }
+        let mantissa: i128 = format!("{}{}", int, frac).parse().ok()?;
+        Decimal::new(if negative { -mantissa } else { mantissa }, frac.len() as u32)
+    }
+
+    pub fn to_f64(&self) -> f64
+    {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// Returns the same number with `scale` digits after the point, rounding if digits are dropped.
+    fn rescale(&self, scale: u32) -> Option<Decimal>
+    {
+        if scale >= self.scale
+        {
+            Decimal::new(self.mantissa.checked_mul(pow10(scale - self.scale)?)?, scale)
+        }
+        else
+        {
+            Decimal::new(div_round(self.mantissa, pow10(self.scale - scale)?), scale)
+        }
+    }
+
+    /// Drops trailing zeros after the point, but keeps at least `min_scale` digits.
+    fn trim(mut self, min_scale: u32) -> Decimal
+    {
+        while self.scale > min_scale && self.mantissa % 10 == 0
+        {
+            self.mantissa /= 10;
+            self.scale -= 1;
+        }
+        self
+    }
+
+    /// Returns `None` on overflow, as do the other arithmetic methods.
+    pub fn checked_add(&self, other: &Decimal) -> Option<Decimal>
+    {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.rescale(scale)?.mantissa.checked_add(other.rescale(scale)?.mantissa)?, scale)
+    }
+
+    pub fn checked_sub(&self, other: &Decimal) -> Option<Decimal>
+    {
+        self.checked_add(&Decimal { mantissa: other.mantissa.checked_neg()?, scale: other.scale })
+    }
+
+    pub fn checked_mul(&self, other: &Decimal) -> Option<Decimal>
+    {
+        let product = Decimal { mantissa: self.mantissa.checked_mul(other.mantissa)?, scale: self.scale + other.scale };
+        product.rescale(product.scale.min(DECIMAL_MAX_SCALE))
+    }
+
+    /// Divides to `DECIMAL_MAX_SCALE` digits, or fewer if the quotient is too large for that. Trailing zeros past the
+    /// scale of `self` are dropped, so `1.00/4` is `0.25` and `10.00/4` is `2.50`. Returns `None` when dividing by zero,
+    /// or if even the whole part of the quotient is too large.
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal>
+    {
+        if other.mantissa == 0
+        {
+            return None;
+        }
+        (0..=DECIMAL_MAX_SCALE).rev().find_map(|scale| {
+            // Shifting the dividend first leaves `scale` digits after the point, or more if `self` already has more.
+            let shift = (scale + other.scale).saturating_sub(self.scale);
+            let quotient = div_round(self.mantissa.checked_mul(pow10(shift)?)?, other.mantissa);
+            Decimal::new(quotient, self.scale + shift - other.scale)?.rescale(scale)
+        }).map(|quotient| quotient.trim(self.scale))
+    }
+
+    /// Raises the decimal to a whole power, rounding to `DECIMAL_MAX_SCALE` digits.
+    pub fn checked_pow(&self, exp: u32) -> Option<Decimal>
+    {
+        let (mut result, mut base, mut exp) = (Decimal::from_int(1), *self, exp);
+        while exp > 0
+        {
+            if exp & 1 == 1
+            {
+                result = result.checked_mul(&base)?;
+            }
+            exp >>= 1;
+            if exp > 0
+            {
+                base = base.checked_mul(&base)?;
+            }
+        }
+        Some(result)
+    }
+
+    /// Rounds half away from zero to `places` digits after the point.
+    pub fn round(&self, places: u32) -> Decimal
+    {
+        if places >= self.scale { *self } else { Decimal { mantissa: div_round(self.mantissa, 10i128.pow(self.scale - places)), scale: places } }
+    }
+
+    /// Returns the largest whole number that is not above this one.
+    pub fn floor(&self) -> Decimal
+    {
+        Decimal { mantissa: self.mantissa.div_euclid(10i128.pow(self.scale)), scale: 0 }
+    }
+
+    /// Returns the decimal as it is written in a formula, with a `d` after the digits.
+    pub fn to_literal(&self) -> String
+    {
+        format!("{}d", self)
+    }
+}
+
+impl Ord for Decimal
+{
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering
+    {
+        // Whole parts first, so that large numbers need not be scaled up to compare.
+        let (unit1, unit2) = (10i128.pow(self.scale), 10i128.pow(other.scale));
+        let whole = self.mantissa.div_euclid(unit1).cmp(&other.mantissa.div_euclid(unit2));
+        let frac1 = self.mantissa.rem_euclid(unit1) * 10i128.pow(other.scale);
+        let frac2 = other.mantissa.rem_euclid(unit2) * 10i128.pow(self.scale);
+        whole.then(frac1.cmp(&frac2))
+    }
+}
+
+impl PartialOrd for Decimal
+{
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering>
+    {
+        Some(self.cmp(other))
+    }
+}
+
+/// Decimals are equal by value, so `1.50` equals `1.5`.
+impl PartialEq for Decimal
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Decimal {}
+
+impl std::ops::Neg for Decimal
+{
+    type Output = Decimal;
+
+    fn neg(self) -> Decimal
+    {
+        Decimal { mantissa: -self.mantissa, scale: self.scale }
+    }
+}
+
+impl std::fmt::Display for Decimal
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let digits = format!("{:0>width$}", digits, width = self.scale as usize + 1);
+        let (int, frac) = digits.split_at(digits.len() - self.scale as usize);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        if frac.is_empty() { write!(f, "{}{}", sign, int) } else { write!(f, "{}{}.{}", sign, int, frac) }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueType 
+{
+    BoolValue(bool),
+    IntegerValue(i64),
+    FloatValue(f64),
+    String(String),
+    DateValue(Date),
+    DecimalValue(Decimal),
+    Error(CellError),
+}
+impl std::fmt::Display for ValueType 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
+    {
+        match self 
+        {
+            ValueType::BoolValue(b) => write!(f, "{}", b),
+            ValueType::IntegerValue(n) => write!(f, "{}", n),
+            ValueType::FloatValue(n) => write!(f, "{}", n),
+            ValueType::String(s) => write!(f, "{}", s),
+            ValueType::DateValue(d) => write!(f, "{}", d),
+            ValueType::DecimalValue(d) => write!(f, "{}", d),
+            ValueType::Error(e) => write!(f, "{}", e.kind.code()),
+        }
+    }
+}
+
+/// Rank of the type of a value when values of different types are sorted together.
+fn sort_rank(value: &ValueType) -> u8
+{
+    match value
+    {
+        ValueType::IntegerValue(_) | ValueType::FloatValue(_) | ValueType::DecimalValue(_) => 0,
+        ValueType::DateValue(_) => 1,
+        ValueType::String(_) => 2,
+        ValueType::BoolValue(_) => 3,
+        ValueType::Error(_) => 4,
+    }
+}
+
+/// Orders two values for sorting. Values of different types go numbers first, then dates, text, booleans and
+/// errors. Numbers compare by value whatever their type, text compares ignoring case, and all errors are equal.
+pub fn compare_values(a: &ValueType, b: &ValueType) -> std::cmp::Ordering
+{
+    use ValueType::*;
+    match (a, b)
+    {
+        (IntegerValue(x), IntegerValue(y)) => x.cmp(y),
+        (DecimalValue(x), DecimalValue(y)) => x.cmp(y),
+        (IntegerValue(x), DecimalValue(y)) => Decimal::from_int(*x).cmp(y),
+        (DecimalValue(x), IntegerValue(y)) => x.cmp(&Decimal::from_int(*y)),
+        (FloatValue(x), FloatValue(y)) => x.total_cmp(y),
+        (FloatValue(x), IntegerValue(y)) => x.total_cmp(&(*y as f64)),
+        (IntegerValue(x), FloatValue(y)) => (*x as f64).total_cmp(y),
+        (FloatValue(x), DecimalValue(y)) => x.total_cmp(&y.to_f64()),
+        (DecimalValue(x), FloatValue(y)) => x.to_f64().total_cmp(y),
+        (DateValue(x), DateValue(y)) => x.cmp(y),
+        (String(x), String(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        (BoolValue(x), BoolValue(y)) => x.cmp(y),
+        _ => sort_rank(a).cmp(&sort_rank(b)),
+    }
+}
+
+/// Horizontal alignment of the text of a cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align
+{
+    Left,
+    Center,
+    Right,
+}
+
+/// A text or background color of a cell, either one of the basic terminal colors or `#rrggbb`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellColor
+{
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+    White,
+    Rgb(u8, u8, u8),
+}
+
+const COLOR_NAMES: [(&str, CellColor); 9] = [
+    ("black", CellColor::Black), ("red", CellColor::Red), ("green", CellColor::Green), ("yellow", CellColor::Yellow),
+    ("blue", CellColor::Blue), ("magenta", CellColor::Magenta), ("cyan", CellColor::Cyan), ("gray", CellColor::Gray),
+    ("white", CellColor::White),
+];
+
+impl CellColor
+{
+    /// Reads a color name like `red`, or `#rrggbb`.
+    pub fn parse(s: &str) -> Option<CellColor>
+    {
+        if let Some(hex) = s.strip_prefix('#')
+        {
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit())
+            {
+                return None;
+            }
+            let part = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            return Some(CellColor::Rgb(part(0)?, part(2)?, part(4)?));
+        }
+        COLOR_NAMES.iter().find(|(name, _)| *name == s).map(|(_, color)| *color)
+    }
+}
+
+impl std::fmt::Display for CellColor
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            CellColor::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            color => write!(f, "{}", COLOR_NAMES.iter().find(|(_, c)| c == color).map_or("", |(name, _)| *name)),
+        }
+    }
+}
+
+/// One setting of a cell format, as given to `:format`. See `parse_format_options` for how they are written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatOption
+{
+    Decimals(u8),
+    Percent,
+    Thousands,
+    Align(Align),
+    Fg(CellColor),
+    Bg(CellColor),
+    Bold,
+}
+
+/// Most digits after the point a format can show.
+pub const FORMAT_MAX_DECIMALS: u8 = 18;
+
+/// Reads a list of format options separated by commas, like `decimals=2,thousands,align=right,bg=#ffe0e0`.
+///
+/// **Returns:**
+/// - `Ok(Vec<FormatOption>)` with the options in the order they were written.
+/// - `Err(String)` naming the first option that is not valid.
+pub fn parse_format_options(spec: &str) -> Result<Vec<FormatOption>, String>
+{
+    spec.split(',').map(str::trim).filter(|opt| !opt.is_empty()).map(|opt| {
+        let invalid = || format!("Invalid format option \"{}\"", opt);
+        let option = match opt.split_once('=') {
+            None => match opt {
+                "percent" => FormatOption::Percent,
+                "thousands" => FormatOption::Thousands,
+                "bold" => FormatOption::Bold,
+                _ => return Err(invalid()),
+            },
+            Some(("decimals", n)) => FormatOption::Decimals(n.parse().ok().filter(|n| *n <= FORMAT_MAX_DECIMALS).ok_or_else(invalid)?),
+            Some(("align", "left")) => FormatOption::Align(Align::Left),
+            Some(("align", "center")) => FormatOption::Align(Align::Center),
+            Some(("align", "right")) => FormatOption::Align(Align::Right),
+            Some(("fg", color)) => FormatOption::Fg(CellColor::parse(color).ok_or_else(invalid)?),
+            Some(("bg", color)) => FormatOption::Bg(CellColor::parse(color).ok_or_else(invalid)?),
+            _ => return Err(invalid()),
+        };
+        Ok(option)
+    }).collect()
+}
+
+/// How the value of a cell is shown. A format only changes the text drawn for a cell, never its value, so
+/// formulas reading the cell are not affected by it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CellFormat
+{
+    /// Digits shown after the point, rounding the number. `None` shows the number as it is.
+    pub decimals: Option<u8>,
+    /// Shows the number times 100, followed by `%`.
+    pub percent: bool,
+    /// Separates groups of three digits with commas.
+    pub thousands: bool,
+    pub align: Option<Align>,
+    pub fg: Option<CellColor>,
+    pub bg: Option<CellColor>,
+    pub bold: bool,
+}
+
+/// Puts a comma between every group of three digits in the whole part of a number written out in `text`.
+fn group_thousands(text: &str) -> String
+{
+    let (sign, digits) = text.strip_prefix('-').map_or(("", text), |rest| ("-", rest));
+    let (whole, frac) = digits.find('.').map_or((digits, ""), |i| digits.split_at(i));
+    let mut grouped = String::new();
+    for (i, c) in whole.chars().enumerate()
+    {
+        if i > 0 && (whole.len() - i) % 3 == 0
+        {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("{}{}{}", sign, grouped, frac)
+}
+
+impl CellFormat
+{
+    pub fn is_default(&self) -> bool
+    {
+        *self == CellFormat::default()
+    }
+
+    /// Changes the setting given by `option`, leaving the others as they were.
+    pub fn apply(&mut self, option: &FormatOption)
+    {
+        match option
+        {
+            FormatOption::Decimals(n) => self.decimals = Some(*n),
+            FormatOption::Percent => self.percent = true,
+            FormatOption::Thousands => self.thousands = true,
+            FormatOption::Align(align) => self.align = Some(*align),
+            FormatOption::Fg(color) => self.fg = Some(*color),
+            FormatOption::Bg(color) => self.bg = Some(*color),
+            FormatOption::Bold => self.bold = true,
+        }
+    }
+
+    /// Returns the options that give this format when applied to the default one.
+    pub fn options(&self) -> Vec<FormatOption>
+    {
+        let mut options = vec![];
+        if let Some(n) = self.decimals { options.push(FormatOption::Decimals(n)) }
+        if self.percent { options.push(FormatOption::Percent) }
+        if self.thousands { options.push(FormatOption::Thousands) }
+        if let Some(align) = self.align { options.push(FormatOption::Align(align)) }
+        if let Some(color) = self.fg { options.push(FormatOption::Fg(color)) }
+        if let Some(color) = self.bg { options.push(FormatOption::Bg(color)) }
+        if self.bold { options.push(FormatOption::Bold) }
+        options
+    }
+
+    /// Returns the text shown for `value`. Numbers are rounded, scaled and grouped as the format says, other
+    /// values are shown as they are.
+    pub fn render(&self, value: &ValueType) -> String
+    {
+        let places = self.decimals.map(u32::from);
+        let text = match (value, self.percent) {
+            (ValueType::IntegerValue(n), false) => match places {
+                Some(places) if places > 0 => format!("{}.{}", n, "0".repeat(places as usize)),
+                _ => n.to_string(),
+            },
+            (ValueType::IntegerValue(n), true) => match places {
+                Some(places) => format!("{:.*}", places as usize, *n as f64 * 100.0),
+                None => (*n as f64 * 100.0).to_string(),
+            },
+            (ValueType::FloatValue(x), percent) => {
+                let x = if percent { x * 100.0 } else { *x };
+                match places {
+                    Some(places) => format!("{:.*}", places as usize, x),
+                    None => x.to_string(),
+                }
+            }
+            (ValueType::DecimalValue(d), percent) => {
+                let d = if percent { d.checked_mul(&Decimal::from_int(100)).unwrap_or(*d) } else { *d };
+                match places {
+                    Some(places) => d.round(places).rescale(places).unwrap_or(d).to_string(),
+                    None => d.to_string(),
+                }
+            }
+            _ => return value.to_string(),
+        };
+        let text = if self.thousands { group_thousands(&text) } else { text };
+        if self.percent { format!("{}%", text) } else { text }
+    }
+}
+
+/// Written as in `parse_format_options`.
+impl std::fmt::Display for FormatOption
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            FormatOption::Decimals(n) => write!(f, "decimals={}", n),
+            FormatOption::Percent => write!(f, "percent"),
+            FormatOption::Thousands => write!(f, "thousands"),
+            FormatOption::Align(Align::Left) => write!(f, "align=left"),
+            FormatOption::Align(Align::Center) => write!(f, "align=center"),
+            FormatOption::Align(Align::Right) => write!(f, "align=right"),
+            FormatOption::Fg(color) => write!(f, "fg={}", color),
+            FormatOption::Bg(color) => write!(f, "bg={}", color),
+            FormatOption::Bold => write!(f, "bold"),
+        }
+    }
+}
+
+/// Writes a list of options so that `parse_format_options` reads it back, eg. `decimals=2,bold`.
+pub fn format_options_to_string(options: &[FormatOption]) -> String
+{
+    options.iter().map(FormatOption::to_string).collect::<Vec<String>>().join(",")
+}
+
+/// Written like the options it is made of, see `CellFormat::options`.
+impl std::fmt::Display for CellFormat
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{}", format_options_to_string(&self.options()))
+    }
+}
+
+/// A conditional format: the cells from `start` to `end` for which `cond` is true are shown with `options`
+/// applied to their format. Inside `cond`, `_` stands for the value of the cell, like in the condition of `SUM`.
+#[derive(Debug, Clone)]
+pub struct FormatRule
+{
+    pub start: Addr,
+    pub end: Addr,
+    pub cond: Expr,
+    pub options: Vec<FormatOption>,
+}
+
+impl FormatRule
+{
+    pub fn covers(&self, addr: &Addr) -> bool
+    {
+        (self.start.row..=self.end.row).contains(&addr.row) && (self.start.col..=self.end.col).contains(&addr.col)
+    }
+}
+
+/// A filter view of a sheet: from the row of `start` down, only the rows whose cell in the column of `start`
+/// makes `cond` true are shown. `_` stands for the value of that cell, like in a conditional format. The rows
+/// above `start`, eg. a header, are always shown. A filter only changes what is drawn, never the cells.
+#[derive(Debug, Clone)]
+pub struct RowFilter
+{
+    pub start: Addr,
+    pub cond: Expr,
+}
+
+#[derive(Clone)]
+pub struct CellFunc 
+{
+    // pub dependency_list: Vec<Weak<RefCell<Cell>>>,
+    pub expression: Expr,
+    // pub destination: Weak<RefCell<Cell>>, // USE OF Weak<T> is DOUBTFUL // @viraaz11: kyu chahiye      // @Pianissimo3115: HATA SKTE AS WELL
+    // pub value: ValueType,           // HATA SKTE  //Hata diya
+}
+
+impl std::fmt::Display for CellFunc 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
+    {
+        write!(f, "{:?}", self.expression)
+    }
+}
+
+impl std::fmt::Debug for CellFunc 
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
+    {
+        writeln!(f, "CellFunc {{")?;
+
+        writeln!(f, "\texpression: {:?},", self.expression)?;
+        // writeln!(f, "\tvalue: {:?},", self.value)?;
+
+        // write!(f, "\tdestination: ")?;
+        // if let Some(dest_rc) = self.destination.upgrade() 
+        // {
+        //     let dest = dest_rc.borrow();
+        //     writeln!(f, "Cell({}, {})", dest.addr.row, dest.addr.col)?;
+        // } 
+        // else 
+        // {
+        //     writeln!(f, "None (dropped)")?;
+        // }
+
+        writeln!(f, "}}")
+    }
+}
+impl CellFunc
+{
+    pub fn new(expression: Expr) -> Self 
+    {
+        CellFunc 
+        {
+            expression,
+            // destination,
+            // value: ValueType::IntegerValue(0), // Default value; will be updated later //NOTE: Removed cause upar likha tha ki nahi chahiye
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cell 
+{
+    pub addr: Addr,
+    pub formula: String, 
+    pub value: ValueType,
+    pub cell_func: Option<CellFunc>,
+    pub children: BTreeSet<Addr>, // USE OF Weak<T> is DOUBTFUL
+    pub format: CellFormat,
+    pub rule_options: Vec<FormatOption>, //Options of the conditional format rules that held when the cell was last evaluated
+}
+
+impl Cell 
+{
+    /// Returns true if the value of the cell is an error value.
+    pub fn is_error(&self) -> bool
+    {
+        matches!(self.value, ValueType::Error(_))
+    }
+
+    pub fn new(addr: Addr) -> Self 
+    {
+        Cell 
+        {
+            addr,
+            formula: "0".to_string(), 
+            value: ValueType::IntegerValue(0),
+            cell_func: None,
+            children: BTreeSet::new(),
+            format: CellFormat::default(),
+            rule_options: vec![],
+        }
+    }
+
+    /// Returns the format the cell is shown with: its own format, changed by the conditional format rules that hold.
+    pub fn shown_format(&self) -> CellFormat
+    {
+        let mut format = self.format.clone();
+        self.rule_options.iter().for_each(|option| format.apply(option));
+        format
+    }
+}
+
+/// The cells of one column, keyed by their row. Only cells that hold a formula or are referenced by one exist,
+/// so a big sheet with a few scattered values only pays for those cells.
+#[derive(Debug, Clone)]
+pub struct Column
+{
+    cells: BTreeMap<u32, Rc<RefCell<Cell>>>,
+    pub sheet_number: u32,
+    pub col_number: u32
+}
+
+// impl IndexMut<usize> for Column //NOTE IMPORTANT: Make sure no mutable reference is used whn, for example, printing the contents as it will crete unnencesary cells. Use this carefully.
+// {
+
+//     fn index_mut(&mut self, ind: usize) -> &mut Rc<RefCell<Cell>> { //NOTE: Rc ko as mut bhejna hota hai kya
+//         if self.cells.len() <= ind
+//         {
+//             let mut p = self.cells.len() as u32;
+//             self.cells.resize_with(ind+1, || {p += 1; Rc::new(RefCell::new(Cell::new(Addr{sheet: self.sheet_number, row: p, col: self.col_number})))});
+//         }
+//         &mut self.cells[ind]   //NOTE: Ye mut, & mut waherah dekh lena theek se koi please. (┬┬﹏┬┬)
+//     }   
+// }
+
+impl Index<usize> for Column   //NOTE: This is not needed for my part, I guessed this would be needed in other part so left this here. 
+{
+    type Output = Rc<RefCell<Cell>>;
+
+    fn index(&self, ind: usize) -> &Self::Output {
+        // if self.cells.len() <= ind
+        // {
+        //     return &Rc::new(RefCell::new(Cell::new(Addr{sheet: self.sheet_number, row: ind as u32, col: self.col_number}))); //NOTE: Ye mut, & mut waherah dekh lena theek se koi please. (┬┬﹏┬┬)}
+        // }
+        &self.cells[&(ind as u32)]
+    }   
+}
+
+impl Column
+{
+    fn new(col_number: u32, sheet_number: u32) -> Self {
+        Column{cells: BTreeMap::new(), col_number, sheet_number}
+    }
+
+    fn truncate(&mut self, new_len: usize) {
+        self.cells.split_off(&(new_len as u32));
+    }
+
+    /// Returns one more than the last row holding a cell, or 0 if the column has no cells.
+    pub fn len(&self) -> usize {  
+        self.cells.last_key_value().map_or(0, |(row, _)| *row as usize + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the number of cells that exist in the column.
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns the cell at `row`, or `None` if it was never created.
+    pub fn get(&self, row: usize) -> Option<&Rc<RefCell<Cell>>> {
+        u32::try_from(row).ok().and_then(|row| self.cells.get(&row))
+    }
+
+    /// Returns the cell at `row`, creating an empty one first if needed.
+    pub fn get_or_create(&mut self, row: usize) -> &Rc<RefCell<Cell>> {
+        let addr = Addr::new(self.sheet_number, row as u32, self.col_number);
+        self.cells.entry(row as u32).or_insert_with(|| Rc::new(RefCell::new(Cell::new(addr))))
+    }
+
+    /// Puts `cell` at the row of its address, replacing any cell already there.
+    pub fn insert(&mut self, cell: Cell) {
+        self.cells.insert(cell.addr.row, Rc::new(RefCell::new(cell)));
+    }
+
+    /// Returns a copy of the column whose cells are new, so changing one column leaves the other as it was.
+    pub fn snapshot(&self) -> Column {
+        let cells = self.cells.iter().map(|(row, cell)| (*row, Rc::new(RefCell::new(cell.borrow().clone())))).collect();
+        Column{cells, sheet_number: self.sheet_number, col_number: self.col_number}
+    }
+
+    /// Iterates over the cells that exist, from the top row down.
+    pub fn iter(&self) -> impl Iterator<Item = &Rc<RefCell<Cell>>> {
+        self.cells.values()
+    }
+
+    pub fn val_at(&self, row: usize) -> ValueType { //NOTE: usize?
+        match self.get(row) {
+            Some(cell) => cell.borrow().value.clone(), //NOTE: Doing clone here cause bohot koshish ke baad mujhse references nahi bheja gaya. Chota struct hai to farak nahi padna chahiye.
+            None => ValueType::IntegerValue(0),
+        }
+    }
+
+    // fn expr_at(&self,row: usize, formula_width: usize)
+    // {
+    //     if row >= self.cells.len()
+    //     {
+    //         print!("");
+    //     }
+    //     if let Some(cell_func) = &self.cells[row].borrow().cell_func 
+    //     {
+    //         print!("{:>formula_width$}", cell_func.expression);
+    //     } 
+    //     else 
+    //     {
+    //         print!(""); 
+    //     }
+    // }
+}
+
+/// Largest number of columns a sheet can grow to by inserting columns, the columns `A` to `ZZZ`.
+pub const MAX_COLS: u32 = 18278;
+/// Largest number of rows a sheet can grow to by inserting rows.
+pub const MAX_ROWS: u32 = 1_000_000;
+
+#[derive(Debug, Clone)]
+pub struct Sheet
+{
+    pub data: Vec<RefCell<Column>>,
+    pub rows: u32,
+    pub columns: u32,
+    pub sheet_idx: u32,
+    pub rules: Vec<FormatRule>, //Conditional format rules, in the order they were added. Later rules win.
+    pub filter: Option<RowFilter>,
+}
+impl Sheet
+{
+    pub fn new(sheet_idx: u32, cols: u32, rows: u32) -> Self 
+    {
+        let mut s = Sheet 
+        {
+            data: vec![], //NOTE: Empty vector new se banana chahiye?
+            rows: 0, //Number of rows in each column
+            columns: 0, //Number of columns
+            sheet_idx,
+            rules: vec![],
+            filter: None,
+        };
+        s.resize(rows as usize, cols as usize);  //NOTE: Ye u32 and usize wali cheez sort kar lena please
+        s
+    }
+    pub fn resize(&mut self, row_num: usize, col_num: usize)   
+    {
+        {
+            let mut p = self.columns;  //Assuming self.columns == self.data.len() //NOTE: Hope to god that this does not cause issues??
+            self.data.resize_with(col_num, || { p += 1; RefCell::new(Column::new(p-1, self.sheet_idx))}); //NOTE!!! : Defaulting sheet number to 0 for now. Must be changed.
+        }
+        self.columns = col_num as u32;  //NOTE: self.rows and (neeche,) self.columns u32 hai to unko "as usize" use karna pada. Har jaga otherwise usize lagega. If possible, sheet struct mai usize kar dena inko.
+
+        if row_num < (self.rows as usize) {
+            for i in 0..col_num
+            {
+                let mut col = self.data[i].borrow_mut();   
+                if col.len() > row_num {              //NOTE: This check is redundant, as truncate does not do anything if given length is greater than current length. Left this for readability. Needed???
+                    col.truncate(row_num) 
+                }
+            }
+        }
+        self.rows = row_num as u32;
+    }
+
+    
+    pub fn val_at(&self, col: usize, row: usize) -> ValueType {  
+        self.data[col].borrow().val_at(row)
+    }
+
+    /// Inserts `count` empty rows before row `at`. Cells below move down and their addresses are updated.
+    pub fn insert_rows(&mut self, at: usize, count: usize)
+    {
+        for column_ref in &self.data
+        {
+            let mut column = column_ref.borrow_mut();
+            let moved = column.cells.split_off(&(at as u32));
+            column.cells.extend(moved.into_iter().map(|(row, cell)| (row + count as u32, cell)));
+        }
+        self.rows += count as u32;
+        self.renumber();
+    }
+
+    /// Removes `count` rows starting from row `at`. Cells below move up and their addresses are updated.
+    pub fn remove_rows(&mut self, at: usize, count: usize)
+    {
+        for column_ref in &self.data
+        {
+            let mut column = column_ref.borrow_mut();
+            let mut removed = column.cells.split_off(&(at as u32));
+            let moved = removed.split_off(&((at + count) as u32));
+            column.cells.extend(moved.into_iter().map(|(row, cell)| (row - count as u32, cell)));
+        }
+        self.rows -= count as u32;
+        self.renumber();
+    }
+
+    /// Inserts `count` empty columns before column `at`. Columns to the right move and their addresses are updated.
+    pub fn insert_cols(&mut self, at: usize, count: usize)
+    {
+        let new_cols = (0..count).map(|_| RefCell::new(Column::new(0, self.sheet_idx)));
+        self.data.splice(at..at, new_cols);
+        self.columns += count as u32;
+        self.renumber();
+    }
+
+    /// Removes `count` columns starting from column `at`. Columns to the right move and their addresses are updated.
+    pub fn remove_cols(&mut self, at: usize, count: usize)
+    {
+        self.data.drain(at..at + count);
+        self.columns -= count as u32;
+        self.renumber();
+    }
+
+    /// Sets the column numbers and cell addresses to match their position in `data`.
+    fn renumber(&mut self)
+    {
+        for (col, column_ref) in self.data.iter().enumerate()
+        {
+            let mut column = column_ref.borrow_mut();
+            column.col_number = col as u32;
+            column.sheet_number = self.sheet_idx;
+            for (row, cell_rc) in column.cells.iter()
+            {
+                cell_rc.borrow_mut().addr = Addr::new(self.sheet_idx, *row, col as u32);
+            }
+        }
+    }
+
+    /// Creates the cell at `col`, `row` if it does not exist yet, so that `data[col].borrow()[row]` can be indexed.
+    pub fn extend_to(&self, col: usize, row: usize) {
+        self.data[col].borrow_mut().get_or_create(row);
+    }
+
+    /// Creates every missing cell in the rectangle from `start` to `end`, eg. so a range can hold its children.
+    pub fn extend_range(&self, start: &Addr, end: &Addr) {
+        for col in start.col..=end.col
+        {
+            let mut column = self.data[col as usize].borrow_mut();
+            for row in start.row..=end.row
+            {
+                column.get_or_create(row as usize);
+            }
+        }
+    }
+
+    /// Changes the format of every cell in the rectangle from `start` to `end` by applying `options`, or resets
+    /// the cells to the default format when `options` is `None`. Missing cells are created to hold the format.
+    pub fn format_range(&self, start: &Addr, end: &Addr, options: Option<&[FormatOption]>) {
+        self.extend_range(start, end);
+        for col in start.col..=end.col
+        {
+            let column = self.data[col as usize].borrow();
+            for row in start.row..=end.row
+            {
+                let mut cell = column[row as usize].borrow_mut();
+                match options {
+                    Some(options) => options.iter().for_each(|option| cell.format.apply(option)),
+                    None => cell.format = CellFormat::default(),
+                }
+            }
+        }
+    }
+
+    /// Returns a copy of the sheet that shares no cells with it, unlike `clone` which shares them.
+    /// Used to keep the state of a sheet, eg. for undo.
+    pub fn snapshot(&self) -> Sheet {
+        Sheet {
+            data: self.data.iter().map(|column| RefCell::new(column.borrow().snapshot())).collect(),
+            rows: self.rows,
+            columns: self.columns,
+            sheet_idx: self.sheet_idx,
+            rules: self.rules.clone(),
+            filter: self.filter.clone(),
+        }
+    }
+
+    /// Returns the number of cells that exist in the sheet, which is what its memory use grows with.
+    pub fn cell_count(&self) -> usize {
+        self.data.iter().map(|column| column.borrow().cell_count()).sum()
+    }
+
+    // pub fn expr_at(&self, col: usize, row: usize, formula_width : usize)
+    // {
+    //     self.data[col].borrow().expr_at(row, formula_width);
+    // }
+
+}
\ No newline at end of file
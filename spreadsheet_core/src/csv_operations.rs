@@ -0,0 +1,326 @@
+use crate::ast::{bool_literal, col_to_name, name_to_col, string_literal, Addr, Expr};
+use crate::cell_operations::{self, Date, Decimal, Sheet, ValueType};
+use csv::ReaderBuilder;
+use std::io::{Write, BufWriter};
+use std::fs::File;
+
+/// The type every value of a column is read as, forced with an option like `B:date` of `parse_csv_options`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType
+{
+    Text,
+    Int,
+    Float,
+    Decimal,
+    Bool,
+    Date,
+}
+
+/// Text of a csv file before it is split into fields.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsvEncoding
+{
+    Utf8,
+    Latin1,
+}
+
+/// How `import_csv` reads a file. The default reads a comma separated UTF-8 file without a header, with values
+/// in double quotes and every row as wide as the first, and guesses the type of every value.
+#[derive(Debug, Clone)]
+pub struct CsvOptions
+{
+    pub delimiter: u8,
+    pub quote: Option<u8>, //None reads quotes as any other character
+    pub header: bool, //Whether the first row (after the skipped ones) holds labels, which are kept as text
+    pub skip_header: bool, //Whether those labels are left out of the sheet
+    pub skip_rows: usize, //Rows at the top of the file that are not read at all
+    pub ragged: bool, //Whether rows may have fewer or more fields than others
+    pub decimals: bool, //Whether numbers with a fractional part are read as exact decimals instead of floats
+    pub encoding: CsvEncoding,
+    pub types: Vec<(u32, ColumnType)>, //Columns whose values are read as one type instead of being guessed
+}
+
+impl Default for CsvOptions
+{
+    fn default() -> Self
+    {
+        CsvOptions{delimiter: b',', quote: Some(b'"'), header: false, skip_header: false, skip_rows: 0, ragged: false,
+            decimals: false, encoding: CsvEncoding::Utf8, types: vec![]}
+    }
+}
+
+/// Reads the single ASCII character of an option, or its name for the characters hard to type in a command.
+fn option_char(value: &str) -> Option<u8>
+{
+    match value {
+        "comma" => Some(b','),
+        "tab" => Some(b'\t'),
+        "semicolon" => Some(b';'),
+        "pipe" => Some(b'|'),
+        "space" => Some(b' '),
+        _ if value.len() == 1 && value.is_ascii() => Some(value.as_bytes()[0]),
+        _ => None,
+    }
+}
+
+/// Parses the options of `load_csv`, written like `"delimiter=tab, header, skip=2, B:date, ragged"`. They follow the
+/// sheet name, as in `:load_csv "parts.tsv" "parts" "delimiter=tab"`, or an empty name to name the sheet after the
+/// file, as in `:load_csv "parts.csv" "" "header"`:
+/// - `delimiter=<c>` and `quote=<c>` take a character or one of `comma`, `tab`, `semicolon`, `pipe` and `space`.
+///   `quote=none` reads quotes as they are.
+/// - `header` keeps the first row as text labels, `header=skip` leaves it out.
+/// - `skip=<n>` leaves out the first `n` rows, before the header.
+/// - `ragged` allows rows of different widths, the sheet is as wide as the widest.
+/// - `decimal` reads fractional numbers as exact decimals.
+/// - `encoding=utf8` or `encoding=latin1`.
+/// - `<column>:<type>` reads every value of the column as `text`, `int`, `float`, `decimal`, `bool` or `date`.
+///
+/// **Returns:**
+/// - `Ok(CsvOptions)` with the options not given left at their default.
+/// - `Err(String)` if an option is unknown or has an invalid value.
+pub fn parse_csv_options(spec: &str) -> Result<CsvOptions, String>
+{
+    let mut options = CsvOptions::default();
+    for item in spec.split(',').map(str::trim).filter(|item| !item.is_empty())
+    {
+        let invalid = || format!("Invalid csv option \"{}\"", item);
+        if let Some((col, kind)) = item.split_once(':')
+        {
+            let col = name_to_col(col.trim()).ok_or_else(invalid)?;
+            let kind = match kind.trim() {
+                "text" => ColumnType::Text,
+                "int" => ColumnType::Int,
+                "float" => ColumnType::Float,
+                "decimal" => ColumnType::Decimal,
+                "bool" => ColumnType::Bool,
+                "date" => ColumnType::Date,
+                _ => return Err(invalid()),
+            };
+            options.types.retain(|(c, _)| *c != col);
+            options.types.push((col, kind));
+            continue;
+        }
+        match item.split_once('=').map(|(key, value)| (key.trim(), value.trim()))
+        {
+            Some(("delimiter", value)) => options.delimiter = option_char(value).ok_or_else(invalid)?,
+            Some(("quote", "none")) => options.quote = None,
+            Some(("quote", value)) => options.quote = Some(option_char(value).ok_or_else(invalid)?),
+            Some(("header", "skip")) => (options.header, options.skip_header) = (true, true),
+            Some(("skip", value)) => options.skip_rows = value.parse().map_err(|_| invalid())?,
+            Some(("encoding", "utf8" | "utf-8")) => options.encoding = CsvEncoding::Utf8,
+            Some(("encoding", "latin1" | "iso-8859-1")) => options.encoding = CsvEncoding::Latin1,
+            None if item == "header" => options.header = true,
+            None if item == "ragged" => options.ragged = true,
+            None if item == "decimal" => options.decimals = true,
+            _ => return Err(invalid()),
+        }
+    }
+    if options.quote == Some(options.delimiter) {
+        return Err("The quote and the delimiter of a csv must differ".to_string());
+    }
+    Ok(options)
+}
+
+/// Reads one field of a csv as the formula, expression and value of a cell. Without a type the value is read
+/// as the first of an integer, a decimal (when asked for), a float, a boolean and a date that it is, or else as
+/// text.
+///
+/// **Returns:**
+/// - `Some((formula, expression, value))`.
+/// - `None` if the field is not of the type `kind`.
+fn read_field(raw_val: &str, kind: Option<ColumnType>, decimals: bool) -> Option<(String, Expr, ValueType)>
+{
+    let int = || raw_val.trim().parse::<i64>().ok().map(|val| (val.to_string(), Expr::Integer(val), ValueType::IntegerValue(val)));
+    let decimal = || Decimal::parse(raw_val.trim()).map(|val| (val.to_literal(), Expr::Decimal(val), ValueType::DecimalValue(val)));
+    let float = || raw_val.trim().parse::<f64>().ok().map(|val| (raw_val.trim().to_string(), Expr::Float(val), ValueType::FloatValue(val)));
+    let bool = || raw_val.trim().parse::<bool>().ok().map(|val| (bool_literal(val), Expr::Bool(val), ValueType::BoolValue(val)));
+    let date = || Date::parse(raw_val.trim()).map(|date| (date.to_literal(), Expr::Date(date), ValueType::DateValue(date)));
+    let text = || (string_literal(raw_val), Expr::String(raw_val.to_string()), ValueType::String(raw_val.to_string()));
+    match kind
+    {
+        Some(ColumnType::Text) => Some(text()),
+        Some(ColumnType::Int) => int(),
+        Some(ColumnType::Float) => float(),
+        Some(ColumnType::Decimal) => decimal(),
+        Some(ColumnType::Bool) => bool(),
+        Some(ColumnType::Date) => date(),
+        None => int()
+            .or_else(|| decimal().filter(|_| decimals))
+            .or_else(float)
+            .or_else(bool)
+            .or_else(date)
+            .or_else(|| Some(text())),
+    }
+}
+
+/// Imports a CSV file into a `Sheet`, read as `options` say.
+///
+/// **Arguments:**
+/// - `csv_name`: The name of the CSV file.
+/// - `sheet_idx`: The index of the sheet.
+/// - `options`: How the file is read, see `parse_csv_options`.
+///
+/// **Returns:**
+/// - `Ok(Sheet)` if the import is successful.
+/// - `Err(String)` if the file cannot be read, has no rows, has rows of different widths without `ragged`, or has
+///   a value that is not of the type forced on its column.
+pub fn import_csv(csv_name: &str, sheet_idx: u32, options: &CsvOptions) -> Result<Sheet, String>
+{
+    let bytes = std::fs::read(csv_name).map_err(|e| format!("Cannot read \"{}\": {}", csv_name, e))?;
+    let text = match options.encoding {
+        CsvEncoding::Utf8 => String::from_utf8(bytes).map_err(|_| "The csv is not valid UTF-8, try encoding=latin1".to_string())?,
+        CsvEncoding::Latin1 => bytes.iter().map(|b| *b as char).collect(),
+    };
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .quoting(options.quote.is_some())
+        .quote(options.quote.unwrap_or(b'"'))
+        .from_reader(text.as_bytes());
+    // Rows are numbered as in the file, counting the skipped ones.
+    let mut csv_data: Vec<(usize, Vec<String>)> = vec![];
+    for (line, result) in rdr.records().enumerate().skip(options.skip_rows)
+    {
+        let record = result.map_err(|e| format!("Row {} of the csv cannot be read: {}", line + 1, e))?;
+        csv_data.push((line + 1, record.iter().map(|s| s.to_string()).collect()));
+    }
+    if options.skip_header && !csv_data.is_empty() {
+        csv_data.remove(0);
+    }
+    let header = options.header && !options.skip_header;
+
+    let Some((first_line, first)) = csv_data.first() else {
+        return Err("The csv has no rows to import".to_string());
+    };
+    if !options.ragged
+        && let Some((line, record)) = csv_data.iter().find(|(_, record)| record.len() != first.len())
+    {
+        return Err(format!("Row {} of the csv has {} fields but row {} has {}, use the ragged option to allow this",
+            line, record.len(), first_line, first.len()));
+    }
+    let columns = csv_data.iter().map(|(_, record)| record.len()).max().unwrap_or(0);
+    if columns == 0 {
+        return Err("The csv has no rows to import".to_string());
+    }
+    if let Some((col, _)) = options.types.iter().find(|(col, _)| *col as usize >= columns) {
+        return Err(format!("Column {} of the csv does not exist", col_to_name(*col)));
+    }
+
+    let sheet: Sheet = Sheet::new(sheet_idx, columns as u32, csv_data.len() as u32);
+    for (row, (line, record)) in csv_data.iter().enumerate()
+    {
+        for (col, raw_val) in record.iter().enumerate()
+        {
+            if raw_val.is_empty()
+            {
+                continue;
+            }
+            let kind = if header && row == 0 {
+                Some(ColumnType::Text)
+            } else {
+                options.types.iter().find(|(c, _)| *c as usize == col).map(|(_, kind)| *kind)
+            };
+            let (formula, expr, value) = read_field(raw_val, kind, options.decimals).ok_or_else(|| {
+                format!("Value \"{}\" in column {} of row {} of the csv is not of the type of its column", raw_val, col_to_name(col as u32), line)
+            })?;
+            let mut cell = cell_operations::Cell::new(Addr::new(sheet_idx, row as u32, col as u32));
+            cell.cell_func = Some(cell_operations::CellFunc::new(expr));
+            cell.value = value;
+            cell.formula = formula;
+            sheet.data[col].borrow_mut().insert(cell);
+        }
+    }
+    Ok(sheet)
+}
+/// Exports a `Sheet` to a CSV file.
+///
+/// **Arguments:**
+/// - `sheet`: The sheet to export.
+/// - `name`: The name of the output file.
+///
+/// **Returns:**
+/// - `Ok(())` if the export is successful.
+/// - `Err(String)` if an error occurs.
+pub fn export_csv(sheet: &Sheet, name: &str) -> Result<(), String> 
+{
+    if let Ok(file) = File::create(String::from(name) + ".csv")
+    {
+        let mut writer = BufWriter::new(file);
+        let mut csv_data : Vec<Vec<String>> = vec![];
+        for col in &sheet.data
+        {
+            // Rows inside the column that have no cell read as 0, like in the sheet itself.
+            let col = col.borrow();
+            let curr_rows = col.len();
+            csv_data.push((0..sheet.rows as usize).map(|row| {
+                if row < curr_rows { col.val_at(row).to_string() } else { "<EMPTY>".to_string() }
+            }).collect());
+        }
+        for row in 0..csv_data[0].len()
+        {
+            for col in 0..csv_data.len()
+            {
+                // if csv_data[col][row] == "<EMPTY>"
+                // {
+                //     if let Ok(()) = write!(writer, "{}", "")
+                //     {}
+                //     else 
+                //     {
+                //         return Err("Error in writing csv".to_string());
+                //     }
+                // }
+                // if let Ok(()) = write!(writer, "{}", csv_data[col][row])
+                // {}
+                // else 
+                // {
+                //     return Err("Error in writing csv".to_string());
+                // }
+                // if row != csv_data[0].len()-1
+                // {
+                //     if let Ok(()) = write!(writer, ",")
+                //     {}
+                //     else 
+                //     {
+                //         return Err("Error in writing csv".to_string());
+                //     }
+                // }
+                if csv_data[col][row] != "<EMPTY>"
+                {
+
+                    if let Ok(()) = write!(writer, "{}", csv_data[col][row])
+                    {}
+                    else 
+                    {
+                        return Err("Error in writing csv".to_string());
+                    }
+                }
+                if col != csv_data.len()-1
+                {
+                    if let Ok(()) = write!(writer, ",")
+                    {}
+                    else 
+                    {
+                        return Err("Error in writing csv".to_string());
+                    }
+                }
+
+            }
+            if let Ok(()) = writeln!(writer)
+            {
+            }
+            else 
+            {
+                return Err("Error in writing csv".to_string());
+            }
+        }
+
+        Ok(())
+    }
+    else 
+    {
+        Err("Error in creating csv".to_string())
+    }
+}
@@ -0,0 +1,2266 @@
+// use std::fmt::Error;
+// use std::ptr::with_exposed_provenance;
+// use std::cmp;
+use std::thread;
+use std::time::Duration;
+use crate::ast::{Addr, InfixFunction, Expr, MonoFunction, ParentType, RangeFunction, BinaryFunction, TernaryFunction, LookupFunction};
+use crate::cell_operations::{compare_values,Sheet,Cell,CellFunc,ValueType,CellError,ErrorKind,Date,Decimal};
+#[allow(unused_imports)]
+use std::rc::{Rc, Weak};
+#[allow(unused_imports)]
+use std::cell::RefCell;
+use std::collections::HashSet;
+// use crate::cell_operations::CellFunc;
+/// Type of value a registry function accepts for an argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgType
+{
+    Number, //Integer, float or decimal
+    Integer,
+    String,
+    Bool,
+    Date,
+    Any,
+}
+
+impl ArgType
+{
+    fn accepts(&self, value: &ValueType) -> bool
+    {
+        matches!((self, value),
+            (ArgType::Any, _)
+            | (ArgType::Number, ValueType::IntegerValue(_) | ValueType::FloatValue(_) | ValueType::DecimalValue(_))
+            | (ArgType::Integer, ValueType::IntegerValue(_))
+            | (ArgType::String, ValueType::String(_))
+            | (ArgType::Bool, ValueType::BoolValue(_))
+            | (ArgType::Date, ValueType::DateValue(_)))
+    }
+
+    fn name(&self) -> &'static str
+    {
+        match self
+        {
+            ArgType::Number => "a number",
+            ArgType::Integer => "an integer",
+            ArgType::String => "a string",
+            ArgType::Bool => "a boolean",
+            ArgType::Date => "a date",
+            ArgType::Any => "a value",
+        }
+    }
+}
+
+/// A function that can be called by name in a formula as `NAME(arg, arg, ...)`.
+///
+/// Arguments are evaluated before the call, so an error in any argument is the result of the call.
+/// To add a function, write it and add an entry to `FUNCTIONS`; the lexer and grammar do not change.
+pub struct FunctionSpec
+{
+    pub name: &'static str,
+    /// Types of the arguments. If `variadic` is true the last type is repeated for any extra arguments.
+    pub params: &'static [ArgType],
+    /// Number of arguments that must be given. The rest of `params` are optional.
+    pub required: usize,
+    pub variadic: bool,
+    pub func: fn(&[ValueType]) -> Result<ValueType, CellError>,
+}
+
+impl FunctionSpec
+{
+    /// Checks the number of arguments of a call.
+    pub fn check_arity(&self, count: usize) -> Result<(), String>
+    {
+        if count < self.required
+        {
+            return Err(format!("{} needs at least {} argument(s)", self.name, self.required));
+        }
+        if !self.variadic && count > self.params.len()
+        {
+            return Err(format!("{} takes at most {} argument(s)", self.name, self.params.len()));
+        }
+        Ok(())
+    }
+
+    /// Checks the number and types of evaluated arguments.
+    fn check_args(&self, args: &[ValueType]) -> Result<(), CellError>
+    {
+        self.check_arity(args.len()).map_err(|e| CellError::new(ErrorKind::TypeMismatch, e))?;
+        for (i, arg) in args.iter().enumerate()
+        {
+            let expected = self.params[i.min(self.params.len() - 1)];
+            if !expected.accepts(arg)
+            {
+                return Err(CellError::new(ErrorKind::TypeMismatch, format!("{} expects {} as argument {}", self.name, expected.name(), i + 1)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the registry entry of a function name.
+pub fn find_function(name: &str) -> Option<&'static FunctionSpec>
+{
+    FUNCTIONS.iter().find(|spec| spec.name == name)
+}
+
+/// Checks that a function exists in the registry and takes `count` arguments. Used by the parser.
+pub fn check_call(name: &str, count: usize) -> Result<(), String>
+{
+    find_function(name).ok_or(format!("Unknown function {}", name))?.check_arity(count)
+}
+
+fn as_float(value: &ValueType) -> f64
+{
+    match value
+    {
+        ValueType::IntegerValue(n) => *n as f64,
+        ValueType::FloatValue(n) => *n,
+        ValueType::DecimalValue(n) => n.to_f64(),
+        _ => 0.0
+    }
+}
+
+fn fn_abs(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    match &args[0]
+    {
+        ValueType::IntegerValue(n) => n.checked_abs().map(ValueType::IntegerValue).ok_or(CellError::new(ErrorKind::Num, "Integer overflow in ABS")),
+        ValueType::DecimalValue(n) => Ok(ValueType::DecimalValue(if *n < Decimal::from_int(0) { -*n } else { *n })),
+        other => Ok(ValueType::FloatValue(as_float(other).abs()))
+    }
+}
+
+fn fn_sqrt(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let n = as_float(&args[0]);
+    if n < 0.0
+    {
+        return Err(CellError::new(ErrorKind::Num, "Square root of a negative number"));
+    }
+    Ok(ValueType::FloatValue(n.sqrt()))
+}
+
+fn fn_len(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let ValueType::String(s) = &args[0] else { unreachable!() };
+    Ok(ValueType::IntegerValue(s.chars().count() as i64))
+}
+
+fn fn_upper(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let ValueType::String(s) = &args[0] else { unreachable!() };
+    Ok(ValueType::String(s.to_uppercase()))
+}
+
+fn fn_lower(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let ValueType::String(s) = &args[0] else { unreachable!() };
+    Ok(ValueType::String(s.to_lowercase()))
+}
+
+fn fn_trim(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let ValueType::String(s) = &args[0] else { unreachable!() };
+    Ok(ValueType::String(s.trim().to_string()))
+}
+
+fn fn_concat(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::String(args.iter().map(|arg| arg.to_string()).collect()))
+}
+
+fn fn_isnumber(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::BoolValue(ArgType::Number.accepts(&args[0])))
+}
+
+fn fn_istext(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::BoolValue(ArgType::String.accepts(&args[0])))
+}
+
+fn fn_choose(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let ValueType::IntegerValue(i) = args[0] else { unreachable!() };
+    if i < 1 || i as usize >= args.len()
+    {
+        return Err(CellError::new(ErrorKind::BadRef, "CHOOSE index is out of range"));
+    }
+    Ok(args[i as usize].clone())
+}
+
+/// Turns the result of checked integer arithmetic into a value, or an overflow error naming the operation.
+fn checked(result: Option<i64>, operation: &str) -> Result<ValueType, CellError>
+{
+    result.map(ValueType::IntegerValue).ok_or_else(|| CellError::new(ErrorKind::Num, format!("Integer overflow in {}", operation)))
+}
+
+/// Operands of an arithmetic or comparison operator, sorted out for decimals.
+enum Operands
+{
+    /// Decimals combine exactly with decimals and integers, so such a pair is given as two decimals.
+    Decimal(Decimal, Decimal),
+    /// Any other pair, where a decimal has been turned into a float so it is handled like one.
+    Other(ValueType, ValueType),
+}
+
+fn decimal_operands(left: ValueType, right: ValueType) -> Operands
+{
+    match (&left, &right)
+    {
+        (ValueType::DecimalValue(n), ValueType::DecimalValue(m)) => Operands::Decimal(*n, *m),
+        (ValueType::DecimalValue(n), ValueType::IntegerValue(m)) => Operands::Decimal(*n, Decimal::from_int(*m)),
+        (ValueType::IntegerValue(n), ValueType::DecimalValue(m)) => Operands::Decimal(Decimal::from_int(*n), *m),
+        _ => Operands::Other(decimal_to_float(left), decimal_to_float(right)),
+    }
+}
+
+fn decimal_to_float(value: ValueType) -> ValueType
+{
+    match value
+    {
+        ValueType::DecimalValue(d) => ValueType::FloatValue(d.to_f64()),
+        other => other
+    }
+}
+
+/// Turns the result of checked decimal arithmetic into a value, or an overflow error naming the operation.
+fn checked_decimal(result: Option<Decimal>, operation: &str) -> Result<ValueType, CellError>
+{
+    result.map(ValueType::DecimalValue).ok_or_else(|| CellError::new(ErrorKind::Num, format!("Decimal overflow in {}", operation)))
+}
+
+fn decimal_div(n: &Decimal, m: &Decimal) -> Result<ValueType, CellError>
+{
+    if *m == Decimal::from_int(0)
+    {
+        return Err(CellError::new(ErrorKind::DivByZero, "Division by zero"));
+    }
+    checked_decimal(n.checked_div(m), "division")
+}
+
+/// Moves a date by a number of days, as in `A1+7`.
+fn shift_date(date: &Date, days: f64) -> Result<ValueType, CellError>
+{
+    date.add_days(days).map(ValueType::DateValue).ok_or(CellError::new(ErrorKind::Num, "Date out of range"))
+}
+
+/// Returns the days from `start` to `end`. The result is an integer unless one of them has a time of day.
+fn days_between(start: &Date, end: &Date) -> ValueType
+{
+    if start.secs == 0 && end.secs == 0
+    {
+        ValueType::IntegerValue(end.days as i64 - start.days as i64)
+    }
+    else
+    {
+        ValueType::FloatValue(end.days_since(start))
+    }
+}
+
+/// Returns the number of whole months from `start` to `end`, negative if `end` comes first.
+fn months_between(start: &Date, end: &Date) -> i32
+{
+    if end < start
+    {
+        return -months_between(end, start);
+    }
+    let (y1, m1, d1) = start.ymd();
+    let (y2, m2, d2) = end.ymd();
+    let months = (y2 - y1) * 12 + m2 as i32 - m1 as i32;
+    if (d2, end.secs) < (d1, start.secs) { months - 1 } else { months }
+}
+
+fn date_arg(args: &[ValueType], i: usize) -> Date
+{
+    let ValueType::DateValue(d) = args[i] else { unreachable!() };
+    d
+}
+
+fn fn_today(_args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::DateValue(Date::today()))
+}
+
+fn fn_date(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let [ValueType::IntegerValue(year), ValueType::IntegerValue(month), ValueType::IntegerValue(day)] = args else { unreachable!() };
+    let date = match (i32::try_from(*year), u32::try_from(*month), u32::try_from(*day)) {
+        (Ok(year), Ok(month), Ok(day)) => Date::from_ymd(year, month, day),
+        _ => None,
+    };
+    date.map(ValueType::DateValue).ok_or(CellError::new(ErrorKind::Num, format!("{}-{}-{} is not a date", year, month, day)))
+}
+
+fn fn_year(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::IntegerValue(date_arg(args, 0).ymd().0 as i64))
+}
+
+fn fn_month(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::IntegerValue(date_arg(args, 0).ymd().1 as i64))
+}
+
+fn fn_day(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    Ok(ValueType::IntegerValue(date_arg(args, 0).ymd().2 as i64))
+}
+
+/// `DATEDIFF(start, end, unit)` counts whole days ("D", the default), months ("M") or years ("Y") from start to end.
+fn fn_datediff(args: &[ValueType]) -> Result<ValueType, CellError>
+{
+    let (start, end) = (date_arg(args, 0), date_arg(args, 1));
+    let unit = match args.get(2) {
+        Some(ValueType::String(unit)) => unit.to_uppercase(),
+        _ => String::from("D"),
+    };
+    match unit.as_str()
+    {
+        "D" => Ok(ValueType::IntegerValue(end.days_since(&start).trunc() as i64)),
+        "M" => Ok(ValueType::IntegerValue(months_between(&start, &end) as i64)),
+        "Y" => Ok(ValueType::IntegerValue(months_between(&start, &end) as i64 / 12)),
+        _ => Err(CellError::new(ErrorKind::Num, format!("Unknown DATEDIFF unit \"{}\"", unit))),
+    }
+}
+
+/// All the functions that can be called with the `NAME(arg, ...)` syntax.
+pub static FUNCTIONS: &[FunctionSpec] = &[
+    FunctionSpec{name: "ABS", params: &[ArgType::Number], required: 1, variadic: false, func: fn_abs},
+    FunctionSpec{name: "SQRT", params: &[ArgType::Number], required: 1, variadic: false, func: fn_sqrt},
+    FunctionSpec{name: "LEN", params: &[ArgType::String], required: 1, variadic: false, func: fn_len},
+    FunctionSpec{name: "UPPER", params: &[ArgType::String], required: 1, variadic: false, func: fn_upper},
+    FunctionSpec{name: "LOWER", params: &[ArgType::String], required: 1, variadic: false, func: fn_lower},
+    FunctionSpec{name: "TRIM", params: &[ArgType::String], required: 1, variadic: false, func: fn_trim},
+    FunctionSpec{name: "CONCAT", params: &[ArgType::Any], required: 1, variadic: true, func: fn_concat},
+    FunctionSpec{name: "ISNUMBER", params: &[ArgType::Any], required: 1, variadic: false, func: fn_isnumber},
+    FunctionSpec{name: "ISTEXT", params: &[ArgType::Any], required: 1, variadic: false, func: fn_istext},
+    FunctionSpec{name: "CHOOSE", params: &[ArgType::Integer, ArgType::Any], required: 2, variadic: true, func: fn_choose},
+    FunctionSpec{name: "TODAY", params: &[], required: 0, variadic: false, func: fn_today},
+    FunctionSpec{name: "DATE", params: &[ArgType::Integer, ArgType::Integer, ArgType::Integer], required: 3, variadic: false, func: fn_date},
+    FunctionSpec{name: "YEAR", params: &[ArgType::Date], required: 1, variadic: false, func: fn_year},
+    FunctionSpec{name: "MONTH", params: &[ArgType::Date], required: 1, variadic: false, func: fn_month},
+    FunctionSpec{name: "DAY", params: &[ArgType::Date], required: 1, variadic: false, func: fn_day},
+    FunctionSpec{name: "DATEDIFF", params: &[ArgType::Date, ArgType::Date, ArgType::String], required: 2, variadic: false, func: fn_datediff},
+];
+
+/// Decides which cells of its range an aggregate takes, given the address of each cell. An error in a cell that is
+/// left out does not make the aggregate an error.
+type CellCondition<'a> = dyn Fn(&Addr) -> Result<bool, CellError> + 'a;
+
+/// Evaluates the condition of a conditional aggregate for one cell of its range, which is the `_` of the condition.
+fn condition_holds(cond: &Expr, sheets: &[Rc<RefCell<Sheet>>], addr: &Addr) -> Result<bool, CellError>
+{
+    match eval(cond, sheets, &Some(addr.clone()))?
+    {
+        ValueType::BoolValue(b) => Ok(b),
+        _ => Err(CellError::new(ErrorKind::TypeMismatch, "Condition should be boolean"))
+    }
+}
+
+/// Aggregates the cells of a range for which `cond` holds.
+fn range_func_eval(sheets: &[Rc<RefCell<Sheet>>], op: &RangeFunction, range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError>
+{
+    match op
+    {
+        RangeFunction::Min => min_eval(sheets, range, cond),
+        RangeFunction::Max => max_eval(sheets, range, cond),
+        RangeFunction::Sum => sum_eval(sheets, range, cond),
+        RangeFunction::Avg => avg_eval(sheets, range, cond),
+        RangeFunction::Stdev => stdev_eval(sheets, range, cond),
+        RangeFunction::Count => count_eval(sheets, range, cond),
+    }
+}
+
+fn min_eval(sheets: &[Rc<RefCell<Sheet>>], range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError> 
+{
+    
+    // let data = (Rc::clone(&sheets[range.0.sheet as usize])).borrow();
+    let cell1: (u32, u32) = (range.0.row, range.0.col);
+    let cell2: (u32, u32) = (range.1.row, range.1.col);
+    let mut mini = f64::MAX;
+    let mut mini_int = i64::MAX; //Kept apart so that integers past 2^53 are compared exactly
+    let mut mini_dec: Option<Decimal> = None; //Least of the integers and decimals, used if the range has decimals but no floats
+    let mut isdecimal = false;
+    let mut isfloat = false;
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 
+        {
+            for row in cell1.0..=cell2.0 
+            {
+            
+                // let temp1: std::cell::Ref<'_, Column> = (*data).data[col as usize].borrow();
+                // let temp2: Rc<RefCell<Cell>> = Rc::clone(&temp1[row as usize]);
+                // let temp: std::cell::Ref<'_, Cell> = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(value) = temp.value 
+                    {
+                        mini_int = mini_int.min(value);
+                        mini_dec = Some(mini_dec.map_or(Decimal::from_int(value), |m| m.min(Decimal::from_int(value))));
+                        if (value as f64) < mini 
+                        {
+                            mini = value as f64;
+                        }
+                    }
+                    else if let ValueType::FloatValue(value) = temp.value 
+                    {
+                        isfloat = true;
+                        if value < mini 
+                        {
+                            mini = value;
+                        }
+                    }
+                    else if let ValueType::DecimalValue(value) = temp.value
+                    {
+                        isdecimal = true;
+                        mini_dec = Some(mini_dec.map_or(value, |m| m.min(value)));
+                        mini = mini.min(value.to_f64());
+                    }
+                    else 
+                    {
+                        return Err(CellError::new(ErrorKind::TypeMismatch, format!("cell at ({}, {}) does not have numeral Type, but used in MIN function", col, row)));
+                    }     
+                }       
+            }
+        }
+    }
+    if isfloat
+    {
+        Ok(ValueType::FloatValue(mini))
+    }
+    else if let (true, Some(mini_dec)) = (isdecimal, mini_dec)
+    {
+        Ok(ValueType::DecimalValue(mini_dec))
+    }
+    else 
+    {
+        Ok(ValueType::IntegerValue(mini_int))
+    }
+}
+
+fn max_eval(sheets: &[Rc<RefCell<Sheet>>], range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError> 
+{
+    
+    // let data = Rc::clone(&sheets[range.0.sheet as usize]).borrow();
+    let cell1: (u32, u32) = (range.0.row, range.0.col);
+    let cell2: (u32, u32) = (range.1.row, range.1.col);
+    let mut maxi = f64::MIN;
+    let mut maxi_int = i64::MIN;
+    let mut maxi_dec: Option<Decimal> = None;
+    let mut isdecimal = false;
+    let mut isfloat = false;
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 
+        {
+            for row in cell1.0..=cell2.0 
+            {
+            
+                // let temp1: std::cell::Ref<'_, Column> = data[col as usize].borrow();
+                // let temp2: Rc<RefCell<Cell>> = Rc::clone(&temp1[row as usize]);
+                // let temp: std::cell::Ref<'_, Cell> = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(value) = temp.value 
+                    {
+                        maxi_int = maxi_int.max(value);
+                        maxi_dec = Some(maxi_dec.map_or(Decimal::from_int(value), |m| m.max(Decimal::from_int(value))));
+                        if (value as f64) > maxi 
+                        {
+                            maxi = value as f64;
+                        }
+                    }
+                    else if let ValueType::FloatValue(value) = temp.value 
+                    {
+                        isfloat = true;
+                        if value > maxi 
+                        {
+                            maxi = value;
+                        }
+                    }
+                    else if let ValueType::DecimalValue(value) = temp.value
+                    {
+                        isdecimal = true;
+                        maxi_dec = Some(maxi_dec.map_or(value, |m| m.max(value)));
+                        maxi = maxi.max(value.to_f64());
+                    }
+                    else 
+                    {
+                        return Err(CellError::new(ErrorKind::TypeMismatch, format!("cell at ({}, {}) does not have numeral Type, but used in MAX function", col, row)));
+                    }            
+                }           
+            }
+        }
+    }
+    if isfloat 
+    {
+        Ok(ValueType::FloatValue(maxi))
+    }
+    else if let (true, Some(maxi_dec)) = (isdecimal, maxi_dec)
+    {
+        Ok(ValueType::DecimalValue(maxi_dec))
+    }
+    else 
+    {
+        Ok(ValueType::IntegerValue(maxi_int))
+    }
+}
+
+fn sum_eval(sheets: &[Rc<RefCell<Sheet>>], range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError> 
+{
+    
+    // let data = Rc::clone(&sheets[range.0.sheet as usize]).borrow();
+    let cell1: (u32, u32) = (range.0.row, range.0.col);
+    let cell2: (u32, u32) = (range.1.row, range.1.col);
+    let mut summ = 0 as f64;
+    let mut summ_int: i64 = 0; //Integers are added exactly, and only join the float sum if the range has floats
+    let mut summ_dec: Option<Decimal> = None;
+    let mut isfloat = false;
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 
+        {
+            for row in cell1.0..=cell2.0 
+            {
+            
+                // let temp1: std::cell::Ref<'_, Column> = data[col as usize].borrow();
+                // let temp2: Rc<RefCell<Cell>> = Rc::clone(&temp1[row as usize]);
+                // let temp: std::cell::Ref<'_, Cell> = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(value) = temp.value 
+                    {
+                        summ_int = summ_int.checked_add(value).ok_or(CellError::new(ErrorKind::Num, "Integer overflow in SUM"))?;
+                    }
+                    else if let ValueType::FloatValue(value) = temp.value 
+                    {
+                        isfloat = true;
+                        summ += value;
+                    }
+                    else if let ValueType::DecimalValue(value) = temp.value
+                    {
+                        let total = summ_dec.unwrap_or(Decimal::from_int(0)).checked_add(&value);
+                        summ_dec = Some(total.ok_or(CellError::new(ErrorKind::Num, "Decimal overflow in SUM"))?);
+                    }
+                    else 
+                    {
+                        return Err(CellError::new(ErrorKind::TypeMismatch, format!("cell at ({}, {}) does not have numeral Type, but used in SUM function", col, row)));
+                    }        
+                }        
+            }
+        }
+    }
+    if isfloat 
+    {
+        Ok(ValueType::FloatValue(summ + summ_int as f64 + summ_dec.map_or(0.0, |d| d.to_f64())))
+    }
+    else if let Some(summ_dec) = summ_dec
+    {
+        checked_decimal(summ_dec.checked_add(&Decimal::from_int(summ_int)), "SUM")
+    }
+    else 
+    {
+        Ok(ValueType::IntegerValue(summ_int))
+    }
+}
+
+fn avg_eval(sheets: &[Rc<RefCell<Sheet>>], range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError> 
+{
+    
+    // let data = Rc::clone(&sheets[range.0.sheet as usize]).borrow();
+    let cell1: (u32, u32) = (range.0.row, range.0.col);
+    let cell2: (u32, u32) = (range.1.row, range.1.col);
+    let mut summ = 0 as f64;
+    let mut summ_dec = Some(Decimal::from_int(0)); //Exact sum of the integers and decimals, None if it overflowed
+    let mut isdecimal = false;
+    let mut isfloat = false;
+    let mut count = 0;
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 
+        {
+            for row in cell1.0..=cell2.0 
+            {
+            
+                // let temp1: std::cell::Ref<'_, Column> = data[col as usize].borrow();
+                // let temp2: Rc<RefCell<Cell>> = Rc::clone(&temp1[row as usize]);
+                // let temp: std::cell::Ref<'_, Cell> = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(value) = temp.value 
+                    {
+                        summ += value as f64;
+                        summ_dec = summ_dec.and_then(|s| s.checked_add(&Decimal::from_int(value)));
+                        count += 1;
+                    }
+                    else if let ValueType::FloatValue(value) = temp.value 
+                    {
+                        isfloat = true;
+                        summ += value;
+                        count += 1; 
+                    }
+                    else if let ValueType::DecimalValue(value) = temp.value
+                    {
+                        isdecimal = true;
+                        summ += value.to_f64();
+                        summ_dec = summ_dec.and_then(|s| s.checked_add(&value));
+                        count += 1;
+                    }
+                    else 
+                    {
+                        return Err(CellError::new(ErrorKind::TypeMismatch, format!("cell at ({}, {}) does not have numeral Type, but used in AVG function", col, row)));
+                    }        
+                }
+                   
+            }
+        }
+    }
+    if count == 0 
+    {
+        Err(CellError::new(ErrorKind::DivByZero, "No valid cells in range"))
+    } 
+    else if isdecimal && !isfloat
+    {
+        checked_decimal(summ_dec.and_then(|s| s.checked_div(&Decimal::from_int(count))), "AVG")
+    }
+    else 
+    {
+        Ok(ValueType::FloatValue(summ / (count as f64)))
+    }
+}
+
+fn stdev_eval(sheets: &[Rc<RefCell<Sheet>>], range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError> 
+{
+    // let data = Rc::clone(&sheets[range.0.sheet as usize]).borrow();
+    let cell1: (u32, u32) = (range.0.row, range.0.col);
+    let cell2: (u32, u32) = (range.1.row, range.1.col);
+    let mut summ = 0 as f64;
+    let mut count = 0;
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 
+        {
+            for row in cell1.0..=cell2.0 
+            {
+            
+                // let temp1 = data[col as usize].borrow();
+                // let temp2 = Rc::clone(&temp1[row as usize]);
+                // let temp = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(value) = temp.value 
+                    {
+                        summ += value as f64;
+                        count += 1;
+                    }
+                    else if let ValueType::FloatValue(value) = temp.value 
+                    {
+                        summ += value;
+                        count += 1; 
+                    }
+                    else if let ValueType::DecimalValue(value) = temp.value
+                    {
+                        summ += value.to_f64();
+                        count += 1;
+                    }
+                    else 
+                    {
+                        return Err(CellError::new(ErrorKind::TypeMismatch, format!("cell at ({}, {}) does not have numeral Type, but used in STDEV function", col, row)));
+                    }        
+                }       
+            }
+        }
+    }
+
+    if count == 0 {
+        return Err(CellError::new(ErrorKind::DivByZero, "No valid cells in range"));
+    }
+
+    let mean = summ / (count as f64);
+    let mut sum_squared_diff = 0.0;
+
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 {
+            for row in cell1.0..=cell2.0 {
+                // let temp1: std::cell::Ref<'_, Column> = data[col as usize].borrow();
+                // let temp2: Rc<RefCell<Cell>> = Rc::clone(&temp1[row as usize]);
+                // let temp: std::cell::Ref<'_, Cell> = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(value) = temp.value 
+                    {
+                        let diff = value as f64 - mean;
+                        sum_squared_diff += diff * diff;
+                    }
+                    else if let ValueType::FloatValue(value) = temp.value 
+                    {
+                        let diff = value - mean;
+                        sum_squared_diff += diff * diff;
+                    }
+                    else if let ValueType::DecimalValue(value) = temp.value
+                    {
+                        let diff = value.to_f64() - mean;
+                        sum_squared_diff += diff * diff;
+                    }
+                    else 
+                    {
+                        return Err(CellError::new(ErrorKind::TypeMismatch, format!("cell at ({}, {}) does not have numeral Type, but used in STDEV function", col, row)));
+                    }
+                }
+            }
+        }
+    }
+
+    let stdev = (sum_squared_diff / (count as f64)).sqrt();
+    Ok(ValueType::FloatValue(stdev))
+}
+
+fn count_eval(sheets: &[Rc<RefCell<Sheet>>], range: (Addr, Addr), cond: &CellCondition) -> Result<ValueType, CellError> 
+{
+    // let data = Rc::clone(&sheets[range.0.sheet as usize]).borrow();
+    let cell1: (u32, u32) = (range.0.row, range.0.col);
+    let cell2: (u32, u32) = (range.1.row, range.1.col);
+    let mut count = 0;
+    for sheet in range_sheets(sheets, &range.0, &range.1)
+    {
+        for col in cell1.1..=cell2.1 
+        {
+            for row in cell1.0..=cell2.0 
+            {
+            
+                // let temp1: std::cell::Ref<'_, Column> = data[col as usize].borrow();
+                // let temp2: Rc<RefCell<Cell>> = Rc::clone(&temp1[row as usize]);
+                // let temp: std::cell::Ref<'_, Cell> = temp2.borrow();
+                let cell_rc = cell_at(sheets, &Addr::new(sheet, row, col))?;
+
+                let temp = cell_rc.borrow();
+                let cond_bool = cond(&Addr::new(sheet, row, col))?;
+                if let ValueType::Error(e) = &temp.value && cond_bool
+                {
+                    return Err(e.clone());
+                }
+                if cond_bool
+                {
+                    if let ValueType::IntegerValue(_) = temp.value 
+                    {
+                        count += 1;
+                    }
+                    else if let ValueType::FloatValue(_) | ValueType::DecimalValue(_) = temp.value 
+                    {
+                        count += 1;
+                    }
+                }      
+            }
+        }
+    }
+    Ok(ValueType::IntegerValue(count))
+}
+
+/// Returns the value of the cell at an address, or its error.
+fn cell_value(sheets: &[Rc<RefCell<Sheet>>], addr: &Addr) -> Result<ValueType, CellError>
+{
+    let cell_rc = cell_at(sheets, addr)?;
+    let cell = cell_rc.borrow();
+    match &cell.value
+    {
+        ValueType::Error(e) => Err(e.clone()),
+        value => Ok(value.clone())
+    }
+}
+
+/// Returns true if a lookup key matches a value. Numbers are compared by value and strings must match exactly.
+pub fn lookup_equal(key: &ValueType, value: &ValueType) -> bool
+{
+    match (key, value)
+    {
+        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => n == m,
+        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) | (ValueType::FloatValue(m), ValueType::IntegerValue(n)) => (*n as f64) == *m,
+        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => n == m,
+        (ValueType::DecimalValue(n), ValueType::DecimalValue(m)) => n == m,
+        (ValueType::DecimalValue(n), ValueType::IntegerValue(m)) | (ValueType::IntegerValue(m), ValueType::DecimalValue(n)) => *n == Decimal::from_int(*m),
+        (ValueType::DecimalValue(n), ValueType::FloatValue(m)) | (ValueType::FloatValue(m), ValueType::DecimalValue(n)) => n.to_f64() == *m,
+        (ValueType::String(n), ValueType::String(m)) => n == m,
+        (ValueType::BoolValue(n), ValueType::BoolValue(m)) => n == m,
+        _ => false
+    }
+}
+
+/// Returns the address of the first cell of each key in the column `col` of a range, ordered like `sort_range`
+/// orders values. Keys are told apart like `VLOOKUP` matches them, so text differing in case gives two keys. Empty
+/// cells and errors are not keys.
+pub fn distinct_keys(sheets: &[Rc<RefCell<Sheet>>], start: &Addr, end: &Addr, col: u32) -> Vec<(Addr, ValueType)>
+{
+    let sheet = sheets[start.sheet as usize].borrow();
+    let Some(column) = sheet.data.get(col as usize) else { return vec![] };
+    let column = column.borrow();
+    let mut keys: Vec<(Addr, ValueType)> = (start.row..=end.row)
+        .filter_map(|row| column.get(row as usize))
+        .map(|cell_rc| cell_rc.borrow())
+        .filter(|cell| cell.cell_func.is_some() && !cell.is_error())
+        .map(|cell| (cell.addr.clone(), cell.value.clone()))
+        .collect();
+    keys.sort_by(|(_, a), (_, b)| compare_values(a, b));
+    // Checking each value against the last key kept is not enough: text differing only in case sorts as equal but
+    // gives two keys, so "West", "west", "West" can follow each other. The value is checked against the keys kept
+    // since the last one sorting differently instead, which are usually just one.
+    let mut distinct: Vec<(Addr, ValueType)> = Vec::new();
+    let mut run_start = 0;
+    for (addr, value) in keys
+    {
+        if distinct.last().is_some_and(|(_, last)| compare_values(last, &value) != std::cmp::Ordering::Equal)
+        {
+            run_start = distinct.len();
+        }
+        if !distinct[run_start..].iter().any(|(_, seen)| lookup_equal(seen, &value))
+        {
+            distinct.push((addr, value));
+        }
+    }
+    distinct
+}
+
+/// Converts a one based position given to a lookup function into an offset inside a range of `len` cells.
+fn lookup_position(value: ValueType, len: u32) -> Result<u32, CellError>
+{
+    let pos = match value
+    {
+        ValueType::IntegerValue(n) => n,
+        _ => return Err(CellError::new(ErrorKind::TypeMismatch, "Position in a lookup should be an integer"))
+    };
+    // Compared as i64, so that no position is cut down to fit in a u32.
+    if pos < 1 || pos > len as i64
+    {
+        return Err(CellError::new(ErrorKind::BadRef, "Position is outside the lookup range"));
+    }
+    Ok(pos as u32 - 1)
+}
+
+fn lookup_eval(sheets: &[Rc<RefCell<Sheet>>], op: &LookupFunction, range: (&Addr, &Addr), args: &[Expr], caller_cell: &Option<Addr>) -> Result<ValueType, CellError>
+{
+    let (start, end) = range;
+    if start.sheet != end.sheet
+    {
+        return Err(CellError::new(ErrorKind::BadRef, "Lookup ranges cannot span several sheets"));
+    }
+    let rows = end.row - start.row + 1;
+    let cols = end.col - start.col + 1;
+    let at = |row: u32, col: u32| Addr::new(start.sheet, start.row + row, start.col + col);
+    match op
+    {
+        LookupFunction::VLookup =>
+        {
+            let key = eval(&args[0], sheets, caller_cell)?;
+            let col = lookup_position(eval(&args[1], sheets, caller_cell)?, cols)?;
+            for row in 0..rows
+            {
+                // A cell with an error in the key column cannot match, so it is skipped.
+                if let Ok(value) = cell_value(sheets, &at(row, 0)) && lookup_equal(&key, &value)
+                {
+                    return cell_value(sheets, &at(row, col));
+                }
+            }
+            Err(CellError::new(ErrorKind::NotFound, "Key not found in the first column of the range"))
+        }
+        LookupFunction::HLookup =>
+        {
+            let key = eval(&args[0], sheets, caller_cell)?;
+            let row = lookup_position(eval(&args[1], sheets, caller_cell)?, rows)?;
+            for col in 0..cols
+            {
+                if let Ok(value) = cell_value(sheets, &at(0, col)) && lookup_equal(&key, &value)
+                {
+                    return cell_value(sheets, &at(row, col));
+                }
+            }
+            Err(CellError::new(ErrorKind::NotFound, "Key not found in the first row of the range"))
+        }
+        LookupFunction::Index =>
+        {
+            let row = lookup_position(eval(&args[0], sheets, caller_cell)?, rows)?;
+            let col = lookup_position(eval(&args[1], sheets, caller_cell)?, cols)?;
+            cell_value(sheets, &at(row, col))
+        }
+        LookupFunction::Match =>
+        {
+            let key = eval(&args[0], sheets, caller_cell)?;
+            if rows != 1 && cols != 1
+            {
+                return Err(CellError::new(ErrorKind::TypeMismatch, "MATCH needs a range of a single row or column"));
+            }
+            for i in 0..rows * cols
+            {
+                let addr = if cols == 1 { at(i, 0) } else { at(0, i) };
+                if let Ok(value) = cell_value(sheets, &addr) && lookup_equal(&key, &value)
+                {
+                    return Ok(ValueType::IntegerValue(i as i64 + 1));
+                }
+            }
+            Err(CellError::new(ErrorKind::NotFound, "Key not found in the range"))
+        }
+        LookupFunction::Key =>
+        {
+            let col = lookup_position(eval(&args[0], sheets, caller_cell)?, cols)?;
+            let n = match eval(&args[1], sheets, caller_cell)?
+            {
+                ValueType::IntegerValue(n) if n >= 1 => n as usize,
+                _ => return Err(CellError::new(ErrorKind::TypeMismatch, "The key number should be a positive integer"))
+            };
+            match distinct_keys(sheets, start, end, start.col + col).into_iter().nth(n - 1)
+            {
+                Some((_, key)) => Ok(key),
+                None => Err(CellError::new(ErrorKind::NotFound, "The column has fewer keys"))
+            }
+        }
+        LookupFunction::GroupBy(func) =>
+        {
+            // The column to aggregate, then pairs of a key column and the key its cells must equal.
+            let col = lookup_position(eval(&args[0], sheets, caller_cell)?, cols)?;
+            let keys = args[1..].chunks(2)
+                .map(|pair| Ok((lookup_position(eval(&pair[0], sheets, caller_cell)?, cols)?, eval(&pair[1], sheets, caller_cell)?)))
+                .collect::<Result<Vec<(u32, ValueType)>, CellError>>()?;
+            let matches = |addr: &Addr| Ok(keys.iter().all(|(key_col, key)| {
+                cell_value(sheets, &at(addr.row - start.row, *key_col)).is_ok_and(|value| lookup_equal(key, &value))
+            }));
+            range_func_eval(sheets, func, (at(0, col), at(rows - 1, col)), &matches)
+        }
+    }
+}
+
+fn sleep(seconds: f64)
+{
+    thread::sleep(Duration::from_secs_f64(seconds));
+}
+
+fn remove_old_dependencies(cell: &Addr,sheets: &mut [Rc<RefCell<Sheet>>], dependencies: Vec<ParentType>) -> Result<(),String>       // DEPENDENCIES OF OLD_FUNC
+{
+    for i in dependencies
+    {
+        match i 
+        {
+            ParentType::Single(addr) if is_removed(sheets, addr.sheet) => {},
+            ParentType::Range(start, end) if start.sheet == end.sheet && is_removed(sheets, start.sheet) => {},
+            ParentType::Single(addr) => 
+            {
+                // if let Addr { sheet, row, col } = addr 
+                // {
+                //     let temp1 = sheets[current_sheet as usize].borrow().clone();
+                //     let temp2 = temp1.as_ref();
+                //     let c = temp2.data[col as usize].borrow();
+                //     let cell_rc = &c[row as usize];
+                //     let mut parent_cell = cell_rc.borrow_mut();
+                //     let addr_of_cell = cell.upgrade().unwrap().borrow().addr.clone();
+
+                //     parent_cell.children.remove(&(cell.upgrade().unwrap().borrow().addr));       
+
+                
+                // } 
+                let Addr { sheet:sheet_num, row, col, .. } = addr;
+                let sheet_ref = &(*sheets)[sheet_num as usize];
+                let sheet = sheet_ref.borrow();
+
+                let column_ref = &sheet.data[col as usize];
+                let column = column_ref.borrow();
+
+                let Some(cell_rc) = column.get(row as usize).cloned() else { continue };
+                // drop(column);
+                let mut parent_cell = cell_rc.borrow_mut();
+                // let temp1 = (*sheets)[sheet_num as usize].borrow();
+                // let sheet = Rc::clone(&temp1);
+                // let temp2 = sheet.data[col as usize].borrow();
+                // let parent_cell = Rc::clone(&temp2[row as usize]);
+                // let mut parent_cell = parent_cell.borrow_mut();
+
+                parent_cell.children.remove(cell);
+                drop(parent_cell);  // is this needed? // Yes, to release the borrow before the next iteration
+            },
+            ParentType::Range(start, end) => 
+            {
+                let Addr{row:r1, col:c1, ..} = start;
+                let Addr{row:r2, col:c2, ..} = end;
+                for s in range_sheets(sheets, &start, &end)
+                {
+                    for i in c1..=c2 
+                    {
+                        for j in r1..=r2 
+                        {
+                            let sheet_ref = &(*sheets)[s as usize];
+                            let sheet = sheet_ref.borrow();
+
+                            // A sheet in the middle of a 3-D range may be smaller than the range.
+                            let Some(column_ref) = sheet.data.get(i as usize) else { continue };
+                            // let column = column_ref.borrow_mut();
+                            let column = column_ref.borrow();
+
+                            let Some(cell_rc) = column.get(j as usize).cloned() else { continue };
+                            drop(column);
+                            let mut parent_cell = cell_rc.borrow_mut();
+                            parent_cell.children.remove(cell);
+                            drop(parent_cell);  // is this needed? // Yes, to release the borrow before the next iteration
+                        }
+                    }
+                }
+            },
+        }
+    }
+    Ok(())
+} 
+
+
+/// Rules a frontend can put in front of the usual ones, eg. integer division for the cli whose values are all
+/// integers. Gives `None` for an expression it leaves to the usual rules.
+pub type EvalOverride = fn(&Expr, &[Rc<RefCell<Sheet>>]) -> Option<Result<ValueType, CellError>>;
+
+thread_local! {
+    static EVAL_OVERRIDE: std::cell::Cell<Option<EvalOverride>> = const { std::cell::Cell::new(None) };
+}
+
+/// Sets the rules that are tried before the usual ones for every expression this thread evaluates, or removes them.
+pub fn set_eval_override(rules: Option<EvalOverride>)
+{
+    EVAL_OVERRIDE.with(|o| o.set(rules));
+}
+
+/// Returns true if the sheet number belongs to a removed sheet, which has no cells.
+fn is_removed(sheets: &[Rc<RefCell<Sheet>>], sheet_num: u32) -> bool
+{
+    sheets[sheet_num as usize].borrow().sheet_idx != sheet_num
+}
+
+/// Returns the sheets a range covers. A range between two sheets, like `jan.A1:mar.A1`, covers every sheet from the
+/// first to the last in the order they were added, leaving out removed ones. A range on one sheet always covers it,
+/// so that reading a range of a removed sheet gives an error.
+pub fn range_sheets(sheets: &[Rc<RefCell<Sheet>>], start: &Addr, end: &Addr) -> Vec<u32>
+{
+    if start.sheet == end.sheet
+    {
+        return vec![start.sheet];
+    }
+    (start.sheet..=end.sheet).filter(|&sheet| !is_removed(sheets, sheet)).collect()
+}
+
+/// Creates the cells of a range in every sheet it covers, so that they can hold children. Removed sheets and
+/// sheets too small for the range are left alone.
+pub fn extend_range_sheets(sheets: &[Rc<RefCell<Sheet>>], start: &Addr, end: &Addr)
+{
+    for sheet_num in range_sheets(sheets, start, end)
+    {
+        if is_removed(sheets, sheet_num)
+        {
+            continue;
+        }
+        let sheet = sheets[sheet_num as usize].borrow();
+        if end.col < sheet.columns && end.row < sheet.rows
+        {
+            sheet.extend_range(start, end);
+        }
+    }
+}
+
+/// Returns the cell at an address, or an error value if the address points into a removed sheet or outside its sheet.
+fn cell_at(sheets: &[Rc<RefCell<Sheet>>], addr: &Addr) -> Result<Rc<RefCell<Cell>>, CellError>
+{
+    if is_removed(sheets, addr.sheet)
+    {
+        return Err(CellError::new(ErrorKind::DeletedSheet, "Reference to a removed sheet"));
+    }
+    let sheet = sheets[addr.sheet as usize].borrow();
+    let column = sheet.data.get(addr.col as usize).ok_or(CellError::new(ErrorKind::BadRef, "Reference out of the sheet"))?;
+    if addr.row >= sheet.rows
+    {
+        return Err(CellError::new(ErrorKind::BadRef, "Reference out of the sheet"));
+    }
+    // Cells that were never written to are not stored, and read as an empty cell.
+    let cell_rc = column.borrow().get(addr.row as usize).cloned();
+    Ok(cell_rc.unwrap_or_else(|| Rc::new(RefCell::new(Cell::new(addr.clone())))))
+}
+
+fn eval(expr: &Expr, sheets: &[Rc<RefCell<Sheet>>], caller_cell: &Option<Addr>) -> Result<ValueType, CellError> 
+{
+    if let Some(result) = EVAL_OVERRIDE.with(|o| o.get()).and_then(|rules| rules(expr, sheets))
+    {
+        return result;
+    }
+    match expr 
+    {
+        Expr::Integer(n) => Ok(ValueType::IntegerValue(*n)),
+        Expr::Float(n) => Ok(ValueType::FloatValue(*n)),
+        Expr::Date(d) => Ok(ValueType::DateValue(*d)),
+        Expr::Decimal(d) => Ok(ValueType::DecimalValue(*d)),
+        Expr::String(s) => Ok(ValueType::String(s.clone())),
+        Expr::Bool(b) => Ok(ValueType::BoolValue(*b)),
+
+        Expr::Cell(addr) =>
+        {
+            let cell_rc = cell_at(sheets, addr)?;
+            let parent_cell = cell_rc.borrow();
+            match &parent_cell.value
+            {
+                ValueType::Error(e) => Err(e.clone()),
+                value => Ok(value.clone())
+            }
+        }
+        Expr::MonoOp(fun, exp) =>
+        {
+            match fun 
+            {
+                MonoFunction::Sleep =>
+                {
+                    let sleep_val = eval(exp, sheets, caller_cell)?;
+                    match sleep_val {
+                        ValueType::IntegerValue(sec) => 
+                            {
+                                if sec < 0
+                                {
+                                    return Err(CellError::new(ErrorKind::Num, "Negative sleep time"));
+                                }
+                                sleep(sec as f64)
+                            },
+                        ValueType::FloatValue(sec) => 
+                        {
+                            if sec < 0.0
+                            {
+                                return Err(CellError::new(ErrorKind::Num, "Negative sleep time"));
+                            }
+                            sleep(sec)
+                        },
+                        _ => return Err(CellError::new(ErrorKind::TypeMismatch, "Invalid argument for sleep")),
+                    }
+                    Ok(sleep_val)
+                }
+                MonoFunction::Not =>
+                {
+                    let val = eval(exp, sheets, caller_cell)?;
+                    match val 
+                    {
+                        ValueType::BoolValue(b) => Ok(ValueType::BoolValue(!b)),
+                        _ => Err(CellError::new(ErrorKind::TypeMismatch, "Not operator can only be used on boolean values"))
+                    }
+                },
+                MonoFunction::IsError =>
+                {
+                    Ok(ValueType::BoolValue(eval(exp, sheets, caller_cell).is_err()))
+                },
+            }
+        }
+        Expr::Name(_, exp) => eval(exp, sheets, caller_cell),
+        Expr::RangeOp{op,start, end, cond, ..} =>
+        {
+            let holds = |addr: &Addr| condition_holds(cond, sheets, addr);
+            range_func_eval(sheets, op, (start.clone(), end.clone()), &holds)
+        }
+        Expr:: InfixOp(exp1,func , exp2 ) =>
+        {
+            match func 
+            {
+                InfixFunction:: Mul =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return checked_decimal(n.checked_mul(&m), "multiplication"),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left,right) 
+                    {
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n*m))
+                        }
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue((n as f64)*m))
+                        }
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n*(m as f64)))
+                        }
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            checked(n.checked_mul(m), "multiplication")
+                        }
+                        (_,_) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "String used in Multiplication"))
+                        }
+                    }
+                },
+                InfixFunction::Add =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return checked_decimal(n.checked_add(&m), "addition"),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) {
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n + m))
+                        }
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n as f64 + m))
+                        }
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n + m as f64))
+                        }
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            checked(n.checked_add(m), "addition")
+                        }
+                        (ValueType::DateValue(d), ValueType::IntegerValue(n)) | (ValueType::IntegerValue(n), ValueType::DateValue(d)) =>
+                        {
+                            shift_date(&d, n as f64)
+                        }
+                        (ValueType::DateValue(d), ValueType::FloatValue(n)) | (ValueType::FloatValue(n), ValueType::DateValue(d)) =>
+                        {
+                            shift_date(&d, n)
+                        }
+                        (ValueType::DateValue(_), ValueType::DateValue(_)) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "Two dates cannot be added"))
+                        }
+                        (_, _) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "String used in Addition"))
+                        }
+                    }
+                },
+                InfixFunction::Sub =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return checked_decimal(n.checked_sub(&m), "subtraction"),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) {
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n - m))
+                        }
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n as f64 - m))
+                        }
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            Ok(ValueType::FloatValue(n - m as f64))
+                        }
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            checked(n.checked_sub(m), "subtraction")
+                        }
+                        (ValueType::DateValue(d), ValueType::IntegerValue(n)) =>
+                        {
+                            shift_date(&d, -(n as f64))
+                        }
+                        (ValueType::DateValue(d), ValueType::FloatValue(n)) =>
+                        {
+                            shift_date(&d, -n)
+                        }
+                        (ValueType::DateValue(d1), ValueType::DateValue(d2)) =>
+                        {
+                            Ok(days_between(&d2, &d1))
+                        }
+                        (_, _) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "String used in Subtraction"))
+                        }
+                    }
+                },
+                InfixFunction::Div =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return decimal_div(&n, &m),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => 
+                        {
+                            if m == 0.0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else {
+                                Ok(ValueType::FloatValue(n / m))
+                            }
+                        },
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => 
+                        {
+                            if m == 0.0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else {
+                                Ok(ValueType::FloatValue(n as f64 / m))
+                            }
+                        },
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            if m == 0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else {
+                                Ok(ValueType::FloatValue(n / m as f64))
+                            }
+                        },
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            if m == 0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else {
+                                Ok(ValueType::FloatValue(n as f64 / m as f64))
+                            }
+                        },
+                        (_, _) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "String used in Division"))
+                        }
+                    }
+                },
+                InfixFunction::Mod =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    match (left, right) 
+                    {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            if m == 0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            // i64::MIN % -1 overflows in checked_rem, but every integer is a multiple of -1.
+                            else if m == -1
+                            {
+                                Ok(ValueType::IntegerValue(0))
+                            }
+                            else {
+                                checked(n.checked_rem(m), "modulus")
+                            }
+                        },
+                        (_, _) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "Modulus can only be used if both the operands are integers"))
+                        }
+                    }
+                },
+                InfixFunction::Pow => 
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match (left, right)
+                    {
+                        // Whole powers of a decimal stay exact while they fit.
+                        (ValueType::DecimalValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            return match u32::try_from(m).ok().and_then(|m| n.checked_pow(m))
+                            {
+                                Some(p) => Ok(ValueType::DecimalValue(p)),
+                                None => Ok(ValueType::FloatValue(n.to_f64().powf(m as f64))),
+                            };
+                        }
+                        (left, right) => (decimal_to_float(left), decimal_to_float(right)),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            // Negative powers, and powers too large for an integer, give a float.
+                            match u32::try_from(m).ok().and_then(|m| n.checked_pow(m))
+                            {
+                                Some(p) => Ok(ValueType::IntegerValue(p)),
+                                None => Ok(ValueType::FloatValue((n as f64).powf(m as f64))),
+                            }
+                        },
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => 
+                        {
+                            Ok(ValueType::FloatValue(n.powf(m)))
+                        },
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => 
+                        {
+                            Ok(ValueType::FloatValue((n as f64).powf(m)))
+                        },
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            Ok(ValueType::FloatValue(n.powf(m as f64)))
+                        },
+                        (_, _) =>
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "Power can only be used if the operands are integers or floats"))
+                        }
+                    }
+                },
+                InfixFunction::FloorDiv => 
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return decimal_div(&n, &m).map(|q| match q { ValueType::DecimalValue(q) => ValueType::DecimalValue(q.floor()), q => q }),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                
+                    match (left, right) {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            if m == 0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else 
+                            {
+                                let Some(div) = n.checked_div(m) else {
+                                    return Err(CellError::new(ErrorKind::Num, "Integer overflow in floor division"));
+                                };
+                                let rem = n % m;
+                                if rem != 0 && (rem < 0) != (m < 0) 
+                                {
+                                    Ok(ValueType::IntegerValue(div - 1))
+                                } 
+                                else 
+                                {
+                                    Ok(ValueType::IntegerValue(div))
+                                }
+                            }
+                        },
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => 
+                        {
+                            if m == 0.0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else 
+                            {
+                                Ok(ValueType::FloatValue((n / m).floor()))
+                            }
+                        },
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => 
+                        {
+                            if m == 0.0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else 
+                            {
+                                Ok(ValueType::FloatValue((n as f64 / m).floor()))
+                            }
+                        },
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            if m == 0 
+                            {
+                                Err(CellError::new(ErrorKind::DivByZero, "Division by zero"))
+                            } 
+                            else 
+                            {
+                                Ok(ValueType::FloatValue((n / m as f64).floor()))
+                            }
+                        },
+                        (_,_) => 
+                        {
+                            Err(CellError::new(ErrorKind::TypeMismatch, "Floor Division can only be used if both operands are integers or floats"))
+                        }
+                    }
+                },      
+                InfixFunction::And =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    match (left, right) 
+                    {
+                        (ValueType::BoolValue(n), ValueType::BoolValue(m)) => Ok(ValueType::BoolValue(n && m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "AND can only be used if both the operands are boolean"))
+                    }
+                },
+                InfixFunction::Or =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    match (left, right) 
+                    {
+                        (ValueType::BoolValue(n), ValueType::BoolValue(m)) => Ok(ValueType::BoolValue(n || m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "OR can only be used if both the operands are boolean"))
+                    }
+                },                
+                InfixFunction::Eq =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return Ok(ValueType::BoolValue(n == m)),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::BoolValue(n), ValueType::BoolValue(m)) => Ok(ValueType::BoolValue(n == m)),
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n == m)),
+                        (ValueType::DateValue(n), ValueType::DateValue(m)) => Ok(ValueType::BoolValue(n == m)),
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(n == m)),
+                        (ValueType::String(n), ValueType::String(m)) => Ok(ValueType::BoolValue(n == m)),
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(m == (n as f64))),
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n == (m as f64))),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Equality operator cannot be used with strings"))
+                    }
+                },
+                InfixFunction::Neq =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return Ok(ValueType::BoolValue(n != m)),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::BoolValue(n), ValueType::BoolValue(m)) => Ok(ValueType::BoolValue(n != m)),
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n != m)),
+                        (ValueType::DateValue(n), ValueType::DateValue(m)) => Ok(ValueType::BoolValue(n != m)),
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(n != m)),
+                        (ValueType::String(n), ValueType::String(m)) => Ok(ValueType::BoolValue(n != m)),
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(m != (n as f64))),
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n != (m as f64))),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Not equal to operator cannot be used with strings"))
+                    }
+                },
+                InfixFunction::Lt =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return Ok(ValueType::BoolValue(n < m)),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n < m)),
+                        (ValueType::DateValue(n), ValueType::DateValue(m)) => Ok(ValueType::BoolValue(n < m)),
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(n < m)),
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n < (m as f64))),
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue((n as f64) < m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Less than operator cannot be used with strings"))
+                    }
+                },
+                InfixFunction::Gt =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return Ok(ValueType::BoolValue(n > m)),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n > m)),
+                        (ValueType::DateValue(n), ValueType::DateValue(m)) => Ok(ValueType::BoolValue(n > m)),
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(n > m)),
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n > (m as f64))),
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue((n as f64) > m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Greater than operator cannot be used with strings"))
+                    }
+                },
+                InfixFunction::LtEq =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return Ok(ValueType::BoolValue(n <= m)),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n <= m)),
+                        (ValueType::DateValue(n), ValueType::DateValue(m)) => Ok(ValueType::BoolValue(n <= m)),
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(n <= m)),
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n <= (m as f64))),
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue((n as f64) <= m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Less than or equal to operator cannot be used with strings"))
+                    }
+                },
+                InfixFunction::GtEq =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    let (left, right) = match decimal_operands(left, right)
+                    {
+                        Operands::Decimal(n, m) => return Ok(ValueType::BoolValue(n >= m)),
+                        Operands::Other(left, right) => (left, right),
+                    };
+                    match (left, right) 
+                    {
+                        (ValueType::IntegerValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n >= m)),
+                        (ValueType::DateValue(n), ValueType::DateValue(m)) => Ok(ValueType::BoolValue(n >= m)),
+                        (ValueType::FloatValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue(n >= m)),
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => Ok(ValueType::BoolValue(n >= (m as f64))),
+                        (ValueType::IntegerValue(n), ValueType::FloatValue(m)) => Ok(ValueType::BoolValue((n as f64) >= m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Greater than or equal to operator cannot be used with strings"))
+                    }
+                },
+                InfixFunction::Concat =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    match (left, right) 
+                    {
+                        (ValueType::String(n), ValueType::String(m)) => Ok(ValueType::String(n + &m)),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "Concatenation can only be used if both the operands are strings"))
+                    }
+                },
+                
+            }
+        }
+        Expr::TernaryOp(fun, cond, true_exp, false_exp) =>
+        {
+            match fun 
+            {
+                TernaryFunction::IfThenElse => 
+                {
+                    let cond = eval(cond, sheets, caller_cell)?;
+                    match cond 
+                    {
+                        ValueType::BoolValue(b) => 
+                        {
+                            if b 
+                            {
+                                eval(true_exp, sheets, caller_cell)
+                            } 
+                            else 
+                            {
+                                eval(false_exp, sheets, caller_cell)
+                            }
+                        },
+                        _ => Err(CellError::new(ErrorKind::TypeMismatch, "Condition should be boolean"))
+                    }
+                }
+            }
+        }
+        Expr::BinOp(fun, exp1, exp2) =>
+        {
+            match fun 
+            {
+                BinaryFunction::Round =>
+                {
+                    let val = eval(exp1, sheets, caller_cell)?;
+                    let places = eval(exp2, sheets, caller_cell)?;
+                    match (val, places) 
+                    {
+                        (ValueType::FloatValue(n), ValueType::IntegerValue(m)) => 
+                        {
+                            if m < 0 
+                            {
+                                return Err(CellError::new(ErrorKind::Num, "Negative decimal places"));
+                            }
+                            let factor = 10f64.powi(m.min(i32::MAX as i64) as i32);
+                            Ok(ValueType::FloatValue((n * factor).round() / factor))
+                        },
+                        (ValueType::DecimalValue(n), ValueType::IntegerValue(m)) =>
+                        {
+                            if m < 0
+                            {
+                                return Err(CellError::new(ErrorKind::Num, "Negative decimal places"));
+                            }
+                            Ok(ValueType::DecimalValue(n.round(m.min(u32::MAX as i64) as u32)))
+                        },
+                        _ => Err(CellError::new(ErrorKind::TypeMismatch, "Round function takes a float or a decimal, and an integer"))
+                    }
+                }
+                BinaryFunction::IsSubstr =>
+                {
+                    let left = eval(exp1, sheets, caller_cell)?;
+                    let right = eval(exp2, sheets, caller_cell)?;
+                    match (left, right) 
+                    {
+                        (ValueType::String(n), ValueType::String(m)) => Ok(ValueType::BoolValue(m.contains(&n))),
+                        (_, _) => Err(CellError::new(ErrorKind::TypeMismatch, "IsSubstring can only be used if both the operands are strings"))
+                    }
+                }
+                BinaryFunction::IfError =>
+                {
+                    match eval(exp1, sheets, caller_cell)
+                    {
+                        Ok(value) => Ok(value),
+                        Err(_) => eval(exp2, sheets, caller_cell)
+                    }
+                }
+            }
+        }
+    
+        Expr::Call(name, args) =>
+        {
+            let spec = find_function(name).ok_or(CellError::new(ErrorKind::TypeMismatch, format!("Unknown function {}", name)))?;
+            let values = args.iter().map(|arg| eval(arg, sheets, caller_cell)).collect::<Result<Vec<ValueType>, CellError>>()?;
+            spec.check_args(&values)?;
+            (spec.func)(&values)
+        }
+        Expr::LookupOp{op, start, end, args, ..} => lookup_eval(sheets, op, (start, end), args, caller_cell),
+        Expr::RefError => Err(CellError::new(ErrorKind::BadRef, "Reference to a deleted cell")),
+
+        Expr::Wildcard =>
+        {
+            match caller_cell 
+            {
+                Some(address) =>
+                {
+                    let cell_rc = cell_at(sheets, address)?;
+                    let parent_cell = cell_rc.borrow();
+                    match &parent_cell.value
+                    {
+                        ValueType::Error(e) => Err(e.clone()),
+                        value => Ok(value.clone())
+                    }
+                }
+                None =>
+                {
+                    Err(CellError::new(ErrorKind::TypeMismatch, "Cannot evaluate wildcard in this context"))
+                }
+            }
+        }
+
+    }
+}
+
+thread_local! {
+    static EVALUATED_CELLS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Returns how many formulas this thread has evaluated since the last `reset_evaluated_cells`, used to measure how
+/// much work a recalculation does.
+pub fn evaluated_cells() -> usize
+{
+    EVALUATED_CELLS.with(|count| count.get())
+}
+
+/// Sets the counter returned by `evaluated_cells` back to zero.
+pub fn reset_evaluated_cells()
+{
+    EVALUATED_CELLS.with(|count| count.set(0));
+}
+
+// this would be a recursive function just like eval of an ast
+fn calculate(cell_rc:Rc<RefCell<Cell>>, sheets: &[Rc<RefCell<Sheet>>]) -> Result<(),String>
+{
+    let temp: Rc<RefCell<Cell>> = Rc::clone(&cell_rc);
+    let mut cell: std::cell::RefMut<'_, Cell> = temp.borrow_mut();
+    let cell_func: &Option<CellFunc> = &cell.cell_func;
+    match cell_func
+    {
+        Some(func) =>
+        {   
+            EVALUATED_CELLS.with(|count| count.set(count.get() + 1));
+            let expr = &func.expression;
+            let temp = eval(expr, sheets, &(Option::None));
+            let res = match temp
+            {
+                Err(err) =>
+                {
+                    cell.value = ValueType::Error(err.clone());
+                    drop(cell);
+                    Err(err.to_string())
+                }
+                Ok(value) =>
+                {
+                    cell.value = value;
+                    drop(cell);
+                    Ok(())
+                }
+            };
+            apply_format_rules(sheets, &cell_rc);
+            res
+            // cell.value = temp;
+        }
+        None => 
+        {
+            let r1= cell.addr.row;
+            let c1= cell.addr.col;
+            drop(cell);
+            Err(format!("No function associated to the cell at ({}, {})",r1, c1))
+        }
+    }  
+}
+
+/// Checks which conditional format rules of its sheet hold for a cell, with the value of the cell as the `_` of
+/// their conditions, and keeps their options in the cell. A condition that is not true, or that evaluates to an
+/// error, does not hold. Called whenever the cell is calculated, so the rules follow the value of the cell.
+pub fn apply_format_rules(sheets: &[Rc<RefCell<Sheet>>], cell_rc: &Rc<RefCell<Cell>>)
+{
+    let addr = cell_rc.borrow().addr.clone();
+    let sheet = sheets[addr.sheet as usize].borrow();
+    if sheet.rules.is_empty() && cell_rc.borrow().rule_options.is_empty()
+    {
+        return;
+    }
+    let caller_cell = Some(addr.clone());
+    let options = sheet.rules.iter()
+        .filter(|rule| rule.covers(&addr) && matches!(eval(&rule.cond, sheets, &caller_cell), Ok(ValueType::BoolValue(true))))
+        .flat_map(|rule| rule.options.iter().cloned())
+        .collect();
+    drop(sheet);
+    cell_rc.borrow_mut().rule_options = options;
+}
+
+/// Checks the conditional format rules whose conditions refer to one of the `updated` cells again, on every cell
+/// they cover. The cells of a rule are not children of the cells its condition refers to, since a format never
+/// changes a value (and a rule may read the cells it covers), so the rules are found here instead.
+fn refresh_rules_reading(sheets: &[Rc<RefCell<Sheet>>], updated: &[Addr])
+{
+    let reads = |dep: &ParentType, addr: &Addr| match dep {
+        ParentType::Single(a) => a.sheet == addr.sheet && a.row == addr.row && a.col == addr.col,
+        ParentType::Range(a1, a2) => (a1.sheet..=a2.sheet).contains(&addr.sheet)
+            && (a1.row..=a2.row).contains(&addr.row) && (a1.col..=a2.col).contains(&addr.col),
+    };
+    for sheet_ref in sheets
+    {
+        let cells: Vec<Rc<RefCell<Cell>>> = {
+            let sheet = sheet_ref.borrow();
+            let mut cells = vec![];
+            for rule in &sheet.rules
+            {
+                if !rule.cond.get_dependency_list().iter().any(|dep| updated.iter().any(|addr| reads(dep, addr)))
+                {
+                    continue;
+                }
+                for col in rule.start.col..=rule.end.col
+                {
+                    let column = sheet.data[col as usize].borrow();
+                    cells.extend((rule.start.row..=rule.end.row).filter_map(|row| column.get(row as usize).cloned()));
+                }
+            }
+            cells
+        };
+        for cell_rc in cells
+        {
+            apply_format_rules(sheets, &cell_rc);
+        }
+    }
+}
+
+/// Lists the rows of a sheet shown by its filter, in order, or returns `None` if the sheet has no filter.
+/// Only the cells that exist are checked one by one: the missing ones all read as empty, so the filter holds
+/// for all of them or for none.
+pub fn filtered_rows(sheets: &[Rc<RefCell<Sheet>>], sheet_num: u32) -> Option<Vec<u32>>
+{
+    let sheet = sheets[sheet_num as usize].borrow();
+    let filter = sheet.filter.as_ref()?;
+    let Addr{row: first, col, ..} = filter.start;
+    let holds = |row: u32| matches!(eval(&filter.cond, sheets, &Some(Addr::new(sheet_num, row, col))), Ok(ValueType::BoolValue(true)));
+    let mut rows: Vec<u32> = (0..first.min(sheet.rows)).collect();
+    let mut empty_holds: Option<bool> = None;
+    let mut next = first;
+    let mut add_empty = |rows: &mut Vec<u32>, from: u32, to: u32| {
+        if from < to && *empty_holds.get_or_insert_with(|| holds(from)) {
+            rows.extend(from..to);
+        }
+    };
+    if let Some(column) = sheet.data.get(col as usize)
+    {
+        let existing: Vec<u32> = column.borrow().iter().map(|cell_rc| cell_rc.borrow().addr.row).filter(|row| (first..sheet.rows).contains(row)).collect();
+        for row in existing
+        {
+            add_empty(&mut rows, next, row);
+            if holds(row) {
+                rows.push(row);
+            }
+            next = row + 1;
+        }
+    }
+    add_empty(&mut rows, next, sheet.rows);
+    Some(rows)
+}
+
+fn update_parent_avls(cell:&Addr, sheets: &mut [Rc<RefCell<Sheet>>], dependencies: Vec<ParentType>) -> Result<(),String>
+{
+    for i in dependencies
+    {
+        match i 
+        {
+            ParentType::Single(addr) if is_removed(sheets, addr.sheet) => {},
+            ParentType::Range(start, end) if start.sheet == end.sheet && is_removed(sheets, start.sheet) => {},
+            ParentType::Single(addr) => 
+            { 
+                // let sheet = *(*sheets)[addr.sheet as usize].borrow();
+
+                
+                // let parent_cell = *sheet.data[addr.col as usize].borrow()[addr.row as usize].clone();
+                // let mut parent_cell = *parent_cell.borrow_mut();
+                let sheet_ref = &(*sheets)[addr.sheet as usize];
+                let sheet = sheet_ref.borrow();
+
+                let column_ref = &sheet.data[addr.col as usize];
+                let mut column = column_ref.borrow_mut();
+
+                // A parent that was never written to gets a cell now, so it can hold its children.
+                let cell_rc = Rc::clone(column.get_or_create(addr.row as usize));
+                drop(column);
+                let mut parent_cell = cell_rc.borrow_mut();
+                parent_cell.children.insert((cell).clone());
+                drop(parent_cell); 
+            },
+            ParentType::Range(start, end) => 
+            {
+                let Addr{row:r1, col:c1, ..} = start;
+                let Addr{row:r2, col:c2, ..} = end;
+                for s in range_sheets(sheets, &start, &end)
+                {
+                    for i in c1..=c2 
+                    {
+                        for j in r1..=r2 
+                        {
+                            let sheet_ref = &(*sheets)[s as usize];
+                            let sheet = sheet_ref.borrow();
+
+                            // A sheet in the middle of a 3-D range may be smaller than the range, the cells
+                            // outside it read as errors and cannot hold children.
+                            let Some(column_ref) = sheet.data.get(i as usize) else { continue };
+                            if j >= sheet.rows
+                            {
+                                continue;
+                            }
+                            let mut column = column_ref.borrow_mut();
+
+                            let cell_rc = Rc::clone(column.get_or_create(j as usize));
+                            drop(column);
+                            let mut parent_cell = cell_rc.borrow_mut();
+                            parent_cell.children.insert((cell).clone());
+                            drop(parent_cell);
+                        }
+                    }
+                }
+            },
+        }
+        
+    }
+    Ok(())
+}
+
+
+fn children_of(sheets: &[Rc<RefCell<Sheet>>], addr: &Addr) -> std::vec::IntoIter<Addr>
+{
+    let sheet = sheets[addr.sheet as usize].borrow();
+    let column = sheet.data[addr.col as usize].borrow();
+    let children: Vec<Addr> = column[addr.row as usize].borrow().children.iter().cloned().collect();
+    children.into_iter()
+}
+
+/// Orders a cell and everything that depends on it so that every cell comes after the cells it refers to, once
+/// the result is reversed. The depth first search keeps its own stack of cells instead of recursing, so chains of
+/// millions of cells do not overflow the call stack.
+///
+/// **Returns:**
+/// - `Ok(Vec<Addr>)` with the cells in reverse topological order.
+/// - `Err(String)` if a cycle is found.
+fn topological_sort(sheets: &[Rc<RefCell<Sheet>>], addr:&Addr) -> Result<Vec<Addr>,String> 
+{
+    let mut visited: HashSet<Addr> = HashSet::new();
+    let mut on_path: HashSet<Addr> = HashSet::new();
+    let mut stack: Vec<Addr> = Vec::new();
+    // Each frame is a cell on the current path with the children not visited yet.
+    let mut frames: Vec<(Addr, std::vec::IntoIter<Addr>)> = vec![(addr.clone(), children_of(sheets, addr))];
+    on_path.insert(addr.clone());
+    while let Some((_, children)) = frames.last_mut()
+    {
+        match children.next()
+        {
+            Some(i) if on_path.contains(&i) =>
+            {
+                return Err(format!("Cyclic dependency detected at cell ({}, {})", i.row+1, i.col+1));
+            }
+            Some(i) if visited.contains(&i) => {}
+            Some(i) =>
+            {
+                let grandchildren = children_of(sheets, &i);
+                on_path.insert(i.clone());
+                frames.push((i, grandchildren));
+            }
+            None =>
+            {
+                let (current_cell, _) = frames.pop().unwrap();
+                on_path.remove(&current_cell);
+                visited.insert(current_cell.clone());
+                stack.push(current_cell);
+            }
+        }
+    }
+    Ok(stack)
+}
+
+
+/// Recalculates a cell and every cell that depends on it, in topological order.
+///
+/// A dependent is only recalculated if one of the cells it refers to got a different value, so the walk stops at
+/// cells whose value did not change. The whole graph below the cell is still sorted, so cycles are always found.
+/// Conditional format rules whose conditions read a cell that got a different value are then checked again.
+///
+/// **Returns:**
+/// - `Ok(())` if the last recalculated cell has no error.
+/// - `Err(String)` with the error of the last recalculated cell, or if a cycle is found.
+pub fn update_children(sheets: &[Rc<RefCell<Sheet>>], cell: &Addr) -> Result<(), String> 
+{
+    let ret = topological_sort(sheets, cell)?;
+    let mut changed: HashSet<Addr> = HashSet::from([cell.clone()]);
+    let mut updated: Vec<Addr> = vec![];
+    let mut error: Result<(), String> = Ok(());
+    for i in ret.iter().rev()
+    {
+        if !changed.contains(i)
+        {
+            continue;
+        }
+        let sheet_ref = &(*sheets)[i.sheet as usize];
+        let sheet = sheet_ref.borrow();
+
+        let column_ref = &sheet.data[i.col as usize];
+        let column = column_ref.borrow();
+
+        let cell_rc = Rc::clone(& column[i.row as usize]);
+        drop(column);
+        let curr_cell = cell_rc.borrow();
+        let checker = curr_cell.cell_func.is_some();
+        let old_value = curr_cell.value.clone();
+        drop(curr_cell);
+        if checker
+        {
+            let result = calculate(Rc::clone(&cell_rc), sheets);
+            // The first error is kept, as the cells after it are calculated from it.
+            if error.is_ok()
+            {
+                error = result;
+            }
+        }
+        let curr_cell = cell_rc.borrow();
+        if !checker || curr_cell.value != old_value
+        {
+            changed.extend(curr_cell.children.iter().cloned());
+            updated.push(i.clone());
+        }
+    }
+    refresh_rules_reading(sheets, &updated);
+    error
+}
+
+pub fn evaluate(sheets: &mut [Rc<RefCell<Sheet>>], cell: &Addr, old_func: &Option<CellFunc>) -> Result<(), String>   /////// OWNERSHIP NAHI LENI THI!!!!!!!!
+{
+    // println!("{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+    let cell_rc = {
+        let sheet_ref = &(*sheets)[cell.sheet as usize];
+        let sheet = sheet_ref.borrow();
+        let column_ref = &sheet.data[cell.col as usize];
+        let column = column_ref.borrow();
+        Rc::clone(&column[cell.row as usize])
+        // drop(column);
+    };
+    // println!("{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+    let curr_cell = cell_rc.borrow();
+    // let roww = curr_cell.addr.row.clone();
+    // let coll = curr_cell.addr.col.clone();
+    // println!("2{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+    let cell_funcc = curr_cell.cell_func.clone();
+    drop(curr_cell);
+    // println!("1{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+
+    let old_dependencies =  match old_func {
+        Some(x) => x.expression.get_dependency_list(),
+        None => vec![]
+    };                                                                  ////// ISKO THODA DEKH LENA
+    // let dependencies = curr_cell.cell_func.unwrap().clone().expression.get_dependency_list();       ////// ISKO THODA DEKH LENA
+    // println!("{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+    let dependencies = match &cell_funcc {
+        Some(func) => func.expression.get_dependency_list(),
+        None => {
+            vec![]
+        }
+    };
+    // println!("{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+    
+    remove_old_dependencies(cell, sheets, old_dependencies)?;
+    // println!("{}", Rc::clone(& (&sheets[0].borrow().data[cell.col as usize].borrow_mut()[cell.row as usize])).try_borrow_mut().is_ok());
+
+    update_parent_avls(cell, sheets, dependencies)?;
+    let temp = update_children(sheets, cell);
+    if let Err(strr) = temp 
+    {
+        // kya sleep me negative hai to restore karni hai values? //Yes
+        if strr.contains("Cyclic dependency detected") || strr.contains("Negative sleep time") //NOTE: Isko theek karna hai
+        {
+            // we need to restore the values 
+            // let func = curr_cell.cell_func.clone();
+            let mut curr_cell = cell_rc.borrow_mut();
+            curr_cell.cell_func = old_func.clone();
+            // A cell that had no formula is not calculated again, so it is emptied here.
+            if old_func.is_none()
+            {
+                curr_cell.value = ValueType::IntegerValue(0);
+            }
+            drop(curr_cell);            ////////////////////////////////////////////////////
+            evaluate(sheets,cell, &cell_funcc)?;
+        }
+        return Err(strr);
+    }    
+    Ok(())
+}
+/// Clears the dependency graph of all sheets and evaluates every formula again, which builds it back.
+/// Used after changes that move cells to other addresses, like inserting or removing rows.
+///
+/// **Arguments:**
+/// - `sheets`: A mutable reference to the list of sheets.
+///
+/// Errors of single formulas (eg. division by zero) stay in their cells and are not returned.
+pub fn reevaluate_all(sheets: &mut [Rc<RefCell<Sheet>>])
+{
+    let mut formula_cells: Vec<(Addr, CellFunc)> = vec![];
+    for sheet_ref in sheets.iter()
+    {
+        let sheet = sheet_ref.borrow();
+        for column_ref in &sheet.data
+        {
+            for cell_rc in column_ref.borrow().iter()
+            {
+                let mut cell = cell_rc.borrow_mut();
+                cell.children.clear();
+                if let Some(func) = &cell.cell_func
+                {
+                    formula_cells.push((cell.addr.clone(), func.clone()));
+                }
+            }
+        }
+    }
+    evaluate_formulas(sheets, formula_cells);
+}
+
+/// Builds the dependency graph to and from the sheets numbered `nums` again and evaluates their formulas, and
+/// those of the sheets referring to them. Used when copies of these sheets are put back in the store (eg. by undo),
+/// so that the sheets not related to them are left as they are, unlike `reevaluate_all`.
+///
+/// **Arguments:**
+/// - `sheets`: A mutable reference to the list of sheets.
+/// - `nums`: The numbers of the sheets put back.
+///
+/// Errors of single formulas (eg. division by zero) stay in their cells and are not returned.
+pub fn reevaluate_sheets(sheets: &mut [Rc<RefCell<Sheet>>], nums: &[usize])
+{
+    let points_into = |func: &CellFunc| func.expression.get_dependency_list().iter().any(|dep| match dep {
+        ParentType::Single(a) => nums.contains(&(a.sheet as usize)),
+        // A range between sheets reads the sheets in between too.
+        ParentType::Range(a1, a2) => nums.iter().any(|num| (a1.sheet as usize..=a2.sheet as usize).contains(num)),
+    });
+    let mut formula_cells: Vec<(Addr, CellFunc)> = vec![];
+    for (num, sheet_ref) in sheets.iter().enumerate()
+    {
+        // The children kept in the copies put back are stale, and so are the links to their cells from the
+        // other sheets. They are built again when the formulas are evaluated.
+        let put_back = nums.contains(&num);
+        let mut referring = put_back;
+        let mut sheet_cells = vec![];
+        let sheet = sheet_ref.borrow();
+        for column_ref in &sheet.data
+        {
+            for cell_rc in column_ref.borrow().iter()
+            {
+                let mut cell = cell_rc.borrow_mut();
+                if put_back
+                {
+                    cell.children.clear();
+                }
+                else
+                {
+                    cell.children.retain(|child| !nums.contains(&(child.sheet as usize)));
+                }
+                if let Some(func) = &cell.cell_func
+                {
+                    referring = referring || points_into(func);
+                    sheet_cells.push((cell.addr.clone(), func.clone()));
+                }
+            }
+        }
+        if referring
+        {
+            formula_cells.extend(sheet_cells);
+        }
+    }
+    evaluate_formulas(sheets, formula_cells);
+}
+
+/// Evaluates every formula of `formula_cells`, which adds them to the dependency graph, after making sure the cells
+/// they depend on exist.
+fn evaluate_formulas(sheets: &mut [Rc<RefCell<Sheet>>], formula_cells: Vec<(Addr, CellFunc)>)
+{
+    for (addr, func) in formula_cells
+    {
+        for dep in func.expression.get_dependency_list()
+        {
+            let (start, end) = match dep {
+                ParentType::Single(a) => (a.clone(), a),
+                ParentType::Range(a1, a2) => (a1, a2),
+            };
+            extend_range_sheets(sheets, &start, &end);
+        }
+        let _ = evaluate(sheets, &addr, &Some(func));
+    }
+}
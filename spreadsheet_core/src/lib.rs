@@ -0,0 +1,251 @@
+pub mod ast;
+pub mod tokensexpr;
+pub mod cell_operations;
+pub mod evaluate_operations;
+pub mod sheet_operations;
+pub mod csv_operations;
+pub mod workbook_operations;
+use lalrpop_util::lalrpop_mod;
+use lalrpop_util::ParseError;
+use logos::Logos;
+use crate::ast::{Command, Expr, NameDef, ParentType};
+use crate::cell_operations::Sheet;
+use crate::tokensexpr::{Token, LexicalError};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+lalrpop_mod!(#[allow(clippy::type_complexity)] pub grammarexpr); // include the generated parser
+
+/// A struct for storing all the sheets created and managing sheet operations.
+pub struct SheetStorage {
+    pub map: Vec<(String, usize)>,
+    pub data: Vec<Rc<RefCell<Sheet>>>,   //NOTE: This should be made int Option<Rc<...>>
+    pub names: BTreeMap<String, NameDef>, //Names defined with `name=...`, changed through `define_name` and `remove_name`
+}
+
+impl Default for SheetStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SheetStorage {
+
+    /// # Details
+    /// Function to create a new SheetStorage
+    ///
+    /// **Returns:**
+    /// - new `SheetStorage` instance
+    pub fn new() -> Self {
+        SheetStorage{
+            map: vec![],
+            data: vec![],
+            names: BTreeMap::new(),
+        }
+    }
+
+    /// # Details
+    /// Method to list names of all the sheets
+    ///
+    /// **Returns:**
+    /// - `Vec<String>` containing the names of all the active sheets.
+    pub fn list_names(&self) -> Vec<String> {
+        self.map.iter().map(|x| x.0.clone()).collect::<Vec<String>>()
+    }
+
+    /// # Details
+    /// Method to get global sheet index of a sheet number.
+    /// 
+    /// **Arguments:**
+    /// - `num`: The sheet number.
+    /// 
+    /// **Returns:**
+    /// - `Some(usize)` containing global index if num is valid, else `None`.
+    pub fn list_index_from_num(&self, num: usize) -> Option<usize> {
+        for i in 0..self.map.len() {
+            if self.map[i].1 == num {
+                return Some(i)
+            }
+        };
+        None
+    }
+
+    /// # Details
+    /// Method to get sheet number for a sheet name.
+    /// 
+    /// **Arguments:**
+    /// - `name`: The sheet name.
+    ///
+    /// **Returns:**
+    /// - `Some(usize)` containing num if name exists, else `None`.
+    pub fn num_from_name(&self, name: &str) -> Option<usize> {
+        for (curr_name, num) in &self.map {
+            if curr_name == name {
+                return Some(*num);
+            }
+        };
+        None
+    }
+
+    /// # Details
+    /// Method to get sheet name for a sheet number.
+    /// 
+    /// **Arguments:**
+    /// - `num`: The sheet number.
+    /// 
+    /// **Returns:**
+    /// - `Some(&String)` containing the name if num is valid, else `None`.
+    pub fn name_from_num(&self, num: usize) -> Option<&String> {
+        for (curr_name, number) in &self.map {
+            if number == &num {
+                return Some(curr_name);
+            }
+        };
+        None
+    }
+
+    /// # Details
+    /// Method to create a new sheet in the store
+    /// 
+    /// **Arguments:**
+    /// - `name`: Name for the new sheet.
+    /// - `cols`: Number of initial columns
+    /// - `rows`: Number of initial rows
+    /// 
+    /// **Returns:**
+    /// - `Some(usize)` containing the num of the new sheet if name does not conflict. If name is repeated then `None`.
+    pub fn new_sheet(&mut self, name: &str, cols: usize, rows: usize) -> Option<usize> {
+        for (curr_name, _num) in &self.map {
+            if curr_name == name {
+                return None;
+            }
+        };
+        let new_num = self.data.len();
+        let new_sheet_ref = RefCell::new(Sheet::new(new_num as u32, cols as u32, rows as u32));
+        self.data.push(Rc::new(new_sheet_ref));
+        self.map.push((String::from(name), new_num));
+        Some(new_num)
+    }
+
+    /// # Details
+    /// Method to add an existing sheet object to the store.
+    /// 
+    /// **Arguments:**
+    /// - `name`: Name of the new sheet.
+    /// - `sheet`: The sheet object to add.
+    /// 
+    /// **Returns:**
+    /// - `Some(usize)` containing the num of the added sheet if name does not conflict. If name is repeated then `None`.
+    pub fn add_sheet(&mut self, name: &str, sheet: Sheet) -> Option<usize> { //Assumes that sheet_idx would be same as data.len()
+        for (curr_name, _num) in &self.map {
+            if curr_name == name {
+                return None;
+            }
+        };
+        let new_num = self.data.len();
+        let new_sheet_ref = RefCell::new(sheet);
+        self.data.push(Rc::new(new_sheet_ref));
+        self.map.push((String::from(name), new_num));
+        Some(new_num)
+    }
+
+    /// # Details
+    /// Method to remove a sheet from the store.
+    /// 
+    /// **Arguments:**
+    /// - `name`: Name of the sheet to remove.
+    /// 
+    /// **Returns:**
+    /// - `Some(usize)` containing the num of the removed sheet if a sheet by that name exists. If a sheet by the name does not exist then `None`.
+    pub fn remove_sheet(&mut self, name: &str) -> Option<usize> {
+        
+        for i in 0..self.map.len() {
+            if self.map[i].0 == name {
+                let removed_num = self.map[i].1;
+                self.data[removed_num] = Rc::new(RefCell::new(Sheet::new(999, 0, 0))); //NOTE: This very bad bad fix later
+                self.map.remove(i);
+                return Some(removed_num)
+            }
+        };
+        None
+    }
+
+    /// # Details
+    /// Method to put back a sheet taken out by `remove_sheet`, eg. to undo the removal.
+    ///
+    /// **Arguments:**
+    /// - `name`: Name of the sheet.
+    /// - `num`: The num the sheet had.
+    /// - `position`: Index of the sheet in the list of sheet names.
+    /// - `sheet`: The removed sheet.
+    ///
+    /// **Returns:**
+    /// - `Some(usize)` containing the num if the name and num are free, else `None`.
+    pub fn restore_sheet(&mut self, name: &str, num: usize, position: usize, sheet: Rc<RefCell<Sheet>>) -> Option<usize> {
+        if num >= self.data.len() || self.num_from_name(name).is_some() || self.name_from_num(num).is_some() {
+            return None;
+        }
+        self.data[num] = sheet;
+        self.map.insert(position.min(self.map.len()), (String::from(name), num));
+        Some(num)
+    }
+
+    pub fn rename_sheet(&mut self, name: &str, name_new: &str) -> Option<usize> {
+
+        for i in 0..self.map.len() {
+            if self.map[i].0 == name {
+                let renamed_num = self.map[i].1;
+                if self.num_from_name(name_new).is_none() {
+                    self.map[i] = (String::from(name_new), renamed_num);
+                    return Some(renamed_num)
+                } else { return None }
+            }
+        };
+        None
+    }
+}
+
+
+/// Parses an assignment such as `A1=B1+2` against the sheets in `sheetstore`.
+///
+/// **Arguments:**
+/// - `input`: The assignment text.
+/// - `curr_sheet`: Sheet number that local addresses (eg. `A1`) refer to.
+/// - `sheetstore`: The store used to resolve global addresses (eg. `sheet1.A1`).
+///
+/// **Returns:**
+/// - `Ok((Command, Vec<ParentType>))` containing the `Command::AssignCmd` and the addresses it depends on.
+/// - `Err(ParseError)` if the input is not a valid assignment.
+pub fn parse_assign(input: &str, curr_sheet: u32, sheetstore: &SheetStorage) -> Result<(Command, Vec<ParentType>), ParseError<usize, Token, LexicalError>> {
+    let lexer = Token::lexer(input).spanned()
+        .map(|(token_result, span)| {
+            let token = token_result?; // Propagate LexicalError
+            Ok((span.start, token, span.end)) // (usize, Token, usize)
+        });
+    let parser = grammarexpr::AssignParser::new();
+    parser.parse(curr_sheet, sheetstore, lexer)
+}
+
+/// Error given by the formula parser, eg. by `parse_condition`.
+pub type ExprParseError = ParseError<usize, Token, LexicalError>;
+
+/// Parses a condition such as `_>100` or `_>B1*2`, like the ones of conditional formats.
+///
+/// **Arguments:**
+/// - `input`: The condition text.
+/// - `curr_sheet`: Sheet number that local addresses (eg. `A1`) refer to.
+/// - `sheetstore`: The store used to resolve global addresses and names.
+///
+/// **Returns:**
+/// - `Ok((Box<Expr>, Vec<ParentType>))` containing the condition and the addresses it refers to.
+/// - `Err(ParseError)` if the input is not a valid expression.
+pub fn parse_condition(input: &str, curr_sheet: u32, sheetstore: &SheetStorage) -> Result<(Box<Expr>, Vec<ParentType>), ExprParseError> {
+    let lexer = Token::lexer(input).spanned()
+        .map(|(token_result, span)| {
+            let token = token_result?; // Propagate LexicalError
+            Ok((span.start, token, span.end)) // (usize, Token, usize)
+        });
+    let parser = grammarexpr::ConditionParser::new();
+    parser.parse(curr_sheet, sheetstore, lexer)
+}
@@ -0,0 +1,1622 @@
+use crate::ast::{bool_literal, col_to_name, name_to_col, string_literal, Addr, Command, Expr, LookupFunction, NameDef, ParentType, RangeFunction};
+use crate::cell_operations::{self, compare_values, Cell, CellFunc, FormatRule, RowFilter, Sheet, ValueType};
+use crate::evaluate_operations::{apply_format_rules, distinct_keys, evaluate, extend_range_sheets, range_sheets, reevaluate_all, update_children};
+use crate::{parse_assign, SheetStorage};
+use regex::{NoExpand, Regex};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+/// Copies the value of one cell to another.
+///
+/// **Arguments:**
+/// - `addr1`: The address of the source cell.
+/// - `addr2`: The address of the destination cell.
+/// - `sheets`: A reference to the list of sheets.
+pub fn copy_cell_value(addr1:Addr, addr2:Addr, sheets: &[Rc<RefCell<Sheet>>])
+{
+    let sheet_ref = &sheets[addr1.sheet as usize];
+    let sheet = sheet_ref.borrow();
+    let column_ref = &sheet.data[addr1.col as usize];
+    let column = column_ref.borrow();
+    let cell_rc = Rc::clone(&column[addr1.row as usize]);
+    // drop(column);
+    let cell = cell_rc.borrow();
+    let value = cell.value.clone();
+    
+    let sheet_ref2 = &sheets[addr2.sheet as usize];
+    let sheet2 = sheet_ref2.borrow();
+    let column_ref2 = &sheet2.data[addr2.col as usize];
+    let column2 = column_ref2.borrow();
+    let cell_rc2 = Rc::clone(&column2[addr2.row as usize]);
+    // drop(column);
+    let mut cell2 = cell_rc2.borrow_mut();
+    
+    
+    match value.clone() 
+    {
+        ValueType::IntegerValue(val) => {
+            cell2.cell_func = Some(cell_operations::CellFunc::new(Expr::Integer(val)));
+            cell2.formula = val.to_string();
+        }
+        ValueType::FloatValue(val) => {
+            cell2.cell_func = Some(cell_operations::CellFunc::new(Expr::Float(val)));
+            cell2.formula = format!("{:?}", val);
+        }
+        ValueType::BoolValue(val) => {
+            cell2.cell_func = Some(cell_operations::CellFunc::new(Expr::Bool(val)));
+            cell2.formula = bool_literal(val);
+        }
+        ValueType::String(val) => {
+            cell2.cell_func = Some(cell_operations::CellFunc::new(Expr::String(val.clone())));
+            cell2.formula = string_literal(&val);
+        }
+        ValueType::DateValue(val) => {
+            cell2.cell_func = Some(cell_operations::CellFunc::new(Expr::Date(val)));
+            cell2.formula = val.to_literal();
+        }
+        ValueType::DecimalValue(val) => {
+            cell2.cell_func = Some(cell_operations::CellFunc::new(Expr::Decimal(val)));
+            cell2.formula = val.to_literal();
+        }
+        ValueType::Error(e) => {
+            // An error has no literal, so the copy is a plain error value without a formula.
+            cell2.cell_func = None;
+            cell2.formula = e.kind.code().to_string();
+        }
+    }
+    cell2.value = value;
+}
+
+/// Checks that the source range of a copy is in order and that the destination range ends inside the `u32` rows
+/// and columns.
+fn check_copy_range(addr1: &Addr, addr2: &Addr, addr3: &Addr) -> Result<(), String>
+{
+    let (Some(row_diff), Some(col_diff)) = (addr2.row.checked_sub(addr1.row), addr2.col.checked_sub(addr1.col)) else {
+        return Err("Range to copy is out of range, its start comes after its end".to_string());
+    };
+    if addr3.row.checked_add(row_diff).is_none() || addr3.col.checked_add(col_diff).is_none() {
+        return Err("Range to copy to is out of range".to_string());
+    }
+    Ok(())
+}
+
+/// Copies the values of a range of cells to another range.
+///
+/// **Arguments:**
+/// - `addr1`: The top-left address of the source range.
+/// - `addr2`: The bottom-right address of the source range.
+/// - `addr3`: The top-left address of the destination range.
+/// - `sheets`: A reference to the list of sheets.
+///
+/// **Returns:**
+/// - `Ok(())` if the operation is successful.
+/// - `Err(String)` if the source range is reversed or the destination range does not fit.
+pub fn copy_range_value(addr1:Addr, addr2:Addr, addr3: Addr, sheets: &[Rc<RefCell<Sheet>>]) -> Result<(), String>
+{
+    check_copy_range(&addr1, &addr2, &addr3)?;
+    let mut m = 0;
+    for (n,i) in (addr1.row..=addr2.row).enumerate()
+    {
+        for j in addr1.col..=addr2.col
+        {
+            copy_cell_value(Addr::new(addr1.sheet, i, j), Addr::new(addr3.sheet, addr3.row + n as u32, addr3.col + m), sheets);
+            m += 1;
+        }
+
+        m = 0;
+    }
+    Ok(())
+}
+/// Copies the formula of one cell to another.
+///
+/// Relative references in the formula are moved by the distance between the two cells, while the parts of a
+/// reference anchored with `$` are kept. A reference that would be moved out of its sheet becomes `#REF!`.
+///
+/// **Arguments:**
+/// - `addr1`: The address of the source cell.
+/// - `addr2`: The address of the destination cell.
+/// - `sheetstore`: The store holding the sheets.
+///
+/// **Returns:**
+/// - `Ok(())` if the operation is successful.
+/// - `Err(String)` if an error occurs.
+pub fn copy_cell_function(addr1:Addr, addr2:Addr, sheetstore: &mut SheetStorage) -> Result<(),String>
+{
+    for addr in [&addr1, &addr2]
+    {
+        let sheet = sheetstore.data[addr.sheet as usize].borrow();
+        if addr.col >= sheet.columns || addr.row >= sheet.rows
+        {
+            return Err("Cell out of the sheet".to_string());
+        }
+        sheet.extend_to(addr.col as usize, addr.row as usize);
+    }
+    let cell_rc = Rc::clone(&sheetstore.data[addr1.sheet as usize].borrow().data[addr1.col as usize].borrow()[addr1.row as usize]);
+    let cell = cell_rc.borrow();
+    let func = cell.cell_func.clone();
+    let formula = cell.formula.clone();
+    drop(cell);
+
+    let new_func = func.map(|func| {
+        let exp = update_cell_func(func.expression, addr1.sheet, addr2.sheet);
+        let row_off = addr2.row as i64 - addr1.row as i64;
+        let col_off = addr2.col as i64 - addr1.col as i64;
+        translate_cell_func(exp, row_off, col_off, &sheetstore.data)
+    });
+    if let Some(func) = &new_func
+    {
+        // The moved references may point to cells that were never created.
+        for dep in func.get_dependency_list()
+        {
+            let (a1, a2) = match dep {
+                ParentType::Single(a) => (a.clone(), a),
+                ParentType::Range(a1, a2) => (a1, a2),
+            };
+            extend_range_sheets(&sheetstore.data, &a1, &a2);
+        }
+    }
+
+    let cell_rc2 = Rc::clone(&sheetstore.data[addr2.sheet as usize].borrow().data[addr2.col as usize].borrow()[addr2.row as usize]);
+    let mut cell2 = cell_rc2.borrow_mut();
+    let old_func = cell2.cell_func.clone();
+    cell2.formula = match &new_func {
+        Some(exp) => exp.to_formula(addr2.sheet, sheetstore),
+        None => formula,
+    };
+    cell2.cell_func = new_func.map(CellFunc::new);
+    drop(cell2);
+    evaluate(&mut sheetstore.data, &addr2, &old_func)
+
+}
+/// Copies the formulas of a range of cells to another range, moving relative references like `copy_cell_function`.
+///
+/// **Arguments:**
+/// - `addr1`: The top-left address of the source range.
+/// - `addr2`: The bottom-right address of the source range.
+/// - `addr3`: The top-left address of the destination range.
+/// - `sheetstore`: The store holding the sheets.
+///
+/// **Returns:**
+/// - `Ok(())` if the operation is successful.
+/// - `Err(String)` if the ranges do not fit, as for `copy_range_value`, or an error occurs.
+pub fn copy_range_function(addr1:Addr, addr2:Addr, addr3: Addr, sheetstore: &mut SheetStorage) -> Result<(),String>
+{
+    check_copy_range(&addr1, &addr2, &addr3)?;
+    let mut m = 0;
+    for (n,i) in (addr1.row..=addr2.row).enumerate()
+    {
+        for j in addr1.col..=addr2.col
+        {
+            copy_cell_function(Addr::new(addr1.sheet, i, j), Addr::new(addr3.sheet, addr3.row + n as u32, addr3.col + m), sheetstore)?;
+            m += 1;
+        }
+        m=0;
+    }
+    Ok(())
+    
+}
+
+fn is_removed_sheet(sheets: &[Rc<RefCell<Sheet>>], sheet_num: u32) -> bool
+{
+    sheets[sheet_num as usize].borrow().sheet_idx != sheet_num
+}
+
+/// Moves the relative references of an expression by an offset, used when a formula is copied.
+/// References that end up outside their sheet become `Expr::RefError`.
+fn translate_cell_func(exp: Expr, row_off: i64, col_off: i64, sheets: &[Rc<RefCell<Sheet>>]) -> Expr
+{
+    let translate = |addr: &Addr| -> Option<Addr> {
+        let new_addr = addr.translate(row_off, col_off)?;
+        if is_removed_sheet(sheets, new_addr.sheet) {
+            return Some(new_addr);
+        }
+        let sheet = sheets[new_addr.sheet as usize].borrow();
+        (new_addr.row < sheet.rows && new_addr.col < sheet.columns).then_some(new_addr)
+    };
+    match exp
+    {
+        Expr::Cell(addr) =>
+        {
+            match translate(&addr) {
+                Some(new_addr) => Expr::Cell(new_addr),
+                None => Expr::RefError,
+            }
+        }
+        Expr::RangeOp{op, start, end, cond, name} =>
+        {
+            let new_cond = translate_cell_func(*cond, row_off, col_off, sheets);
+            match (translate(&start), translate(&end)) {
+                (Some(new_start), Some(new_end)) => Expr::RangeOp{op, start: new_start, end: new_end, cond: Box::new(new_cond), name},
+                _ => Expr::RefError,
+            }
+        }
+        Expr::LookupOp{op, start, end, args, name} =>
+        {
+            let args = args.into_iter().map(|e| translate_cell_func(e, row_off, col_off, sheets)).collect();
+            match (translate(&start), translate(&end)) {
+                (Some(new_start), Some(new_end)) => Expr::LookupOp{op, start: new_start, end: new_end, args, name},
+                _ => Expr::RefError,
+            }
+        }
+        Expr::Call(name, args) => Expr::Call(name, args.into_iter().map(|e| translate_cell_func(e, row_off, col_off, sheets)).collect()),
+        Expr::MonoOp(a, b) => Expr::MonoOp(a, Box::new(translate_cell_func(*b, row_off, col_off, sheets))),
+        Expr::BinOp(a, b, c) =>
+        {
+            let expr1 = translate_cell_func(*b, row_off, col_off, sheets);
+            let expr2 = translate_cell_func(*c, row_off, col_off, sheets);
+            Expr::BinOp(a, Box::new(expr1), Box::new(expr2))
+        },
+        Expr::InfixOp(a, b, c) =>
+        {
+            let expr1 = translate_cell_func(*a, row_off, col_off, sheets);
+            let expr2 = translate_cell_func(*c, row_off, col_off, sheets);
+            Expr::InfixOp(Box::new(expr1), b, Box::new(expr2))
+        },
+        Expr::TernaryOp(a, b, c, d) =>
+        {
+            let expr1 = translate_cell_func(*b, row_off, col_off, sheets);
+            let expr2 = translate_cell_func(*c, row_off, col_off, sheets);
+            let expr3 = translate_cell_func(*d, row_off, col_off, sheets);
+            Expr::TernaryOp(a, Box::new(expr1), Box::new(expr2), Box::new(expr3))
+        },
+        other => other,
+    }
+}
+
+/// Fills a range with the formula of its first cell when that formula refers to other cells, the way dragging a
+/// formula works. Returns `None` when the first cell holds a constant, so the caller fills a progression instead.
+fn fill_formula(start_addr: &Addr, end_addr: &Addr, sheetstore: &mut SheetStorage) -> Option<Result<(), String>>
+{
+    let has_refs = {
+        let sheet = sheetstore.data[start_addr.sheet as usize].borrow();
+        let column = sheet.data.get(start_addr.col as usize)?.borrow();
+        let cell = column.get(start_addr.row as usize)?.borrow();
+        cell.cell_func.as_ref().is_some_and(|f| !f.expression.get_dependency_list().is_empty())
+    };
+    if !has_refs {
+        return None;
+    }
+    for row in start_addr.row..=end_addr.row
+    {
+        for col in start_addr.col..=end_addr.col
+        {
+            if row == start_addr.row && col == start_addr.col { continue }
+            if let Err(e) = copy_cell_function(start_addr.clone(), Addr::new(start_addr.sheet, row, col), sheetstore) {
+                return Some(Err(e));
+            }
+        }
+    }
+    Some(Ok(()))
+}
+
+/// Returns a term of an integer geometric progression, as a float once it no longer fits in an integer.
+fn progression_value(val: f64) -> (ValueType, Expr, String)
+{
+    if val.abs() < i64::MAX as f64
+    {
+        (ValueType::IntegerValue(val as i64), Expr::Integer(val as i64), (val as i64).to_string())
+    }
+    else
+    {
+        (ValueType::FloatValue(val), Expr::Float(val), format!("{:?}", val))
+    }
+}
+
+/// Autofills a range of cells in an arithmetic progression (AP).
+/// If the first cell holds a formula with references, that formula is copied to the rest of the range instead.
+///
+/// **Arguments:**
+/// - `start_addr`: The starting address of the range.
+/// - `end_addr`: The ending address of the range.
+/// - `sheetstore`: The store holding the sheets.
+///
+/// **Returns:**
+/// - `Ok(())` if the operation is successful.
+/// - `Err(String)` if an error occurs.
+pub fn autofill_ap(start_addr: Addr, end_addr: Addr, sheetstore: &mut SheetStorage) -> Result<(), String> {
+    if let Some(res) = fill_formula(&start_addr, &end_addr, sheetstore) {
+        return res;
+    }
+    sheetstore.data[start_addr.sheet as usize].borrow().extend_range(&start_addr, &end_addr);
+    let sheet_ref: &Rc<RefCell<Sheet>> = &sheetstore.data[start_addr.sheet as usize];
+    let sheet: std::cell::Ref<'_, Sheet> = sheet_ref.borrow();
+    let column_ref: &RefCell<cell_operations::Column> = &sheet.data[start_addr.col as usize];
+    let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+    let cell_rc: Rc<RefCell<Cell>> = Rc::clone(&column[start_addr.row as usize]);
+    let cell1: std::cell::Ref<'_, Cell> = cell_rc.borrow();
+
+    if start_addr.col == end_addr.col {
+        // Autofill in the same column
+        let cell2_rc = Rc::clone(&column[(start_addr.row + 1) as usize]);
+        let cell2 = cell2_rc.borrow();
+        match (cell1.value.clone(), cell2.value.clone()) {
+            (ValueType::IntegerValue(val1), ValueType::IntegerValue(val2)) => {
+                let common_diff = val2.checked_sub(val1).ok_or_else(|| "Integer overflow in AP autofill".to_string())?;
+                for row in start_addr.row + 2..=end_addr.row {
+                    let cell_rc = Rc::clone(&column[row as usize]);
+                    let mut cell = cell_rc.borrow_mut();
+                    let val = common_diff.checked_mul((row - start_addr.row) as i64)
+                        .and_then(|diff| val1.checked_add(diff))
+                        .ok_or_else(|| "Integer overflow in AP autofill".to_string())?;
+                    cell.value = ValueType::IntegerValue(val);
+                    cell.cell_func = Some(CellFunc::new(Expr::Integer(val)));
+                    cell.formula = val.to_string();
+                }
+            }
+            (ValueType::FloatValue(val1), ValueType::FloatValue(val2)) => {
+                let common_diff = val2 - val1;
+                for row in start_addr.row + 2..=end_addr.row {
+                    let cell_rc = Rc::clone(&column[row as usize]);
+                    let mut cell = cell_rc.borrow_mut();
+                    let val = val1 + common_diff * (row - start_addr.row) as f64;
+                    cell.value = ValueType::FloatValue(val);
+                    cell.cell_func = Some(CellFunc::new(Expr::Float(val)));
+                    cell.formula = format!("{:?}", val);
+                }
+            }
+            (_, _) => {
+                return Err("AP autofill cannot be used with Booleans or Strings".to_string());
+            }
+        }
+    } else if start_addr.row == end_addr.row {
+        // Autofill in the same row
+        let column_ref: &RefCell<cell_operations::Column> = &sheet.data[(start_addr.col + 1) as usize];
+        let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+        let cell_rc: Rc<RefCell<Cell>> = Rc::clone(&column[start_addr.row as usize]);
+        let cell2: std::cell::Ref<'_, Cell> = cell_rc.borrow();
+        match (cell1.value.clone(), cell2.value.clone()) {
+            (ValueType::IntegerValue(val1), ValueType::IntegerValue(val2)) => {
+                let common_diff = val2.checked_sub(val1).ok_or_else(|| "Integer overflow in AP autofill".to_string())?;
+                for col in start_addr.col + 2..=end_addr.col {
+                    let column_ref: &RefCell<cell_operations::Column> = &sheet.data[col as usize];
+                    let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+                    let cell_rc = Rc::clone(&column[start_addr.row as usize]);
+                    let mut cell3 = cell_rc.borrow_mut();
+                    let val = common_diff.checked_mul((col - start_addr.col) as i64)
+                        .and_then(|diff| val1.checked_add(diff))
+                        .ok_or_else(|| "Integer overflow in AP autofill".to_string())?;
+                    cell3.value = ValueType::IntegerValue(val);
+                    cell3.cell_func = Some(CellFunc::new(Expr::Integer(val)));
+                    cell3.formula = val.to_string();
+                }
+            }
+            (ValueType::FloatValue(val1), ValueType::FloatValue(val2)) => {
+                let common_diff = val2 - val1;
+                for col in start_addr.col + 2..=end_addr.col {
+                    let column_ref: &RefCell<cell_operations::Column> = &sheet.data[col as usize];
+                    let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+                    let cell_rc = Rc::clone(&column[start_addr.row as usize]);
+                    let mut cell3 = cell_rc.borrow_mut();
+                    let val = val1 + common_diff * (col - start_addr.col) as f64;
+                    cell3.value = ValueType::FloatValue(val);
+                    cell3.cell_func = Some(CellFunc::new(Expr::Float(val)));
+                    cell3.formula = format!("{:?}", val);
+                }
+            }
+            (_, _) => {
+                return Err("AP autofill cannot be used with Booleans or Strings".to_string());
+            }
+        }
+    } else {
+        return Err("Cannot autofill sequence in 2-D range".to_string());
+    }
+    Ok(())
+}
+
+/// Autofills a range of cells in a geometric progression (GP).
+/// If the first cell holds a formula with references, that formula is copied to the rest of the range instead.
+///
+/// **Arguments:**
+/// - `start_addr`: The starting address of the range.
+/// - `end_addr`: The ending address of the range.
+/// - `sheetstore`: The store holding the sheets.
+///
+/// **Returns:**
+/// - `Ok(())` if the operation is successful.
+/// - `Err(String)` if an error occurs.
+pub fn autofill_gp(start_addr: Addr, end_addr: Addr, sheetstore: &mut SheetStorage) -> Result<(), String> {
+    if let Some(res) = fill_formula(&start_addr, &end_addr, sheetstore) {
+        return res;
+    }
+    sheetstore.data[start_addr.sheet as usize].borrow().extend_range(&start_addr, &end_addr);
+    let sheet_ref: &Rc<RefCell<Sheet>> = &sheetstore.data[start_addr.sheet as usize];
+    let sheet: std::cell::Ref<'_, Sheet> = sheet_ref.borrow();
+    let column_ref: &RefCell<cell_operations::Column> = &sheet.data[start_addr.col as usize];
+    let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+    let cell_rc: Rc<RefCell<Cell>> = Rc::clone(&column[start_addr.row as usize]);
+    let cell1: std::cell::Ref<'_, Cell> = cell_rc.borrow();
+
+    if let ValueType::IntegerValue(0) = cell1.value {
+        return Err("GP cannot start with Integral Value 0".to_string());
+    }
+    if let ValueType::FloatValue(0.0) = cell1.value {
+        return Err("GP cannot start with Float Value 0".to_string());
+    }
+
+    if start_addr.col == end_addr.col {
+        // Autofill in the same column
+        let cell2_rc = Rc::clone(&column[(start_addr.row + 1) as usize]);
+        let cell2 = cell2_rc.borrow();
+        match (cell1.value.clone(), cell2.value.clone()) {
+            (ValueType::IntegerValue(val1), ValueType::IntegerValue(val2)) => {
+                let common_ratio = val2 as f64 / val1 as f64;
+                for row in start_addr.row + 2..=end_addr.row {
+                    let cell_rc = Rc::clone(&column[row as usize]);
+                    let mut cell = cell_rc.borrow_mut();
+                    let val = val1 as f64 * common_ratio.powf((row - start_addr.row) as f64);
+                    let (value, expr, formula) = progression_value(val);
+                    cell.value = value;
+                    cell.cell_func = Some(CellFunc::new(expr));
+                    cell.formula = formula;
+                }
+            }
+            (ValueType::FloatValue(val1), ValueType::FloatValue(val2)) => {
+                let common_ratio = val2 / val1;
+                for row in start_addr.row + 2..=end_addr.row {
+                    let cell_rc = Rc::clone(&column[row as usize]);
+                    let mut cell = cell_rc.borrow_mut();
+                    let val = val1 * common_ratio.powf((row - start_addr.row) as f64);
+                    cell.value = ValueType::FloatValue(val);
+                    cell.cell_func = Some(CellFunc::new(Expr::Float(val)));
+                    cell.formula = format!("{:?}", val);
+                }
+            }
+            (_, _) => {
+                return Err("GP autofill cannot be used with Booleans or Strings".to_string());
+            }
+        }
+    } else if start_addr.row == end_addr.row {
+        // Autofill in the same row
+        let column_ref: &RefCell<cell_operations::Column> = &sheet.data[(start_addr.col + 1) as usize];
+        let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+        let cell_rc: Rc<RefCell<Cell>> = Rc::clone(&column[start_addr.row as usize]);
+        let cell2: std::cell::Ref<'_, Cell> = cell_rc.borrow();
+        match (cell1.value.clone(), cell2.value.clone()) {
+            (ValueType::IntegerValue(val1), ValueType::IntegerValue(val2)) => {
+                let common_ratio = val2 as f64 / val1 as f64;
+                for col in start_addr.col + 2..=end_addr.col {
+                    let column_ref: &RefCell<cell_operations::Column> = &sheet.data[col as usize];
+                    let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+                    let cell_rc = Rc::clone(&column[start_addr.row as usize]);
+                    let mut cell3 = cell_rc.borrow_mut();
+                    let val = val1 as f64 * common_ratio.powf((col - start_addr.col) as f64);
+                    let (value, expr, formula) = progression_value(val);
+                    cell3.value = value;
+                    cell3.cell_func = Some(CellFunc::new(expr));
+                    cell3.formula = formula;
+                }
+            }
+            (ValueType::FloatValue(val1), ValueType::FloatValue(val2)) => {
+                let common_ratio = val2 / val1;
+                for col in start_addr.col + 2..=end_addr.col {
+                    let column_ref: &RefCell<cell_operations::Column> = &sheet.data[col as usize];
+                    let column: std::cell::Ref<'_, cell_operations::Column> = column_ref.borrow();
+                    let cell_rc = Rc::clone(&column[start_addr.row as usize]);
+                    let mut cell3 = cell_rc.borrow_mut();
+                    let val = val1 * common_ratio.powf((col - start_addr.col) as f64);
+                    cell3.value = ValueType::FloatValue(val);
+                    cell3.cell_func = Some(CellFunc::new(Expr::Float(val)));
+                    cell3.formula = format!("{:?}", val);
+                }
+            }
+            (_, _) => {
+                return Err("GP autofill cannot be used with Booleans or Strings".to_string());
+            }
+        }
+    } else {
+        return Err("Cannot autofill sequence in 2-D range".to_string());
+    }
+    Ok(())
+}
+/// Recalculates the cells that depend on a sheet which was just removed, so that they hold a deleted sheet error.
+/// Links from other sheets to cells of the removed sheet are dropped from the dependency graph.
+///
+/// **Arguments:**
+/// - `sheets`: A mutable reference to the list of sheets, where the removed sheet is already replaced.
+/// - `removed`: The sheet that was removed.
+pub fn invalidate_children(sheets: &mut [Rc<RefCell<Sheet>>], removed: &Sheet) 
+{
+    for sheet_ref in sheets.iter()
+    {
+        for column_ref in &sheet_ref.borrow().data
+        {
+            for cell_rc in column_ref.borrow().iter()
+            {
+                cell_rc.borrow_mut().children.retain(|child| child.sheet != removed.sheet_idx);
+            }
+        }
+    }
+    for column_ref in &removed.data
+    {
+        for cell_rc in column_ref.borrow().iter()
+        {
+            let children = std::mem::take(&mut cell_rc.borrow_mut().children);
+            for child_addr in children.iter().filter(|child| child.sheet != removed.sheet_idx)
+            {
+                // The error is stored in the child, so the returned message is not needed here.
+                let _ = update_children(sheets, child_addr);
+            }
+        }
+    }
+}
+
+/// Duplicates a sheet by creating a copy of it.
+///
+/// **Arguments:**
+/// - `sheets`: A mutable reference to the list of sheets.
+/// - `sheet_number`: The index of the sheet to duplicate.
+///
+/// **Returns:**
+/// - `Ok(Sheet)` if the duplication is successful.
+/// - `Err(String)` if the sheet does not exist or an error occurs.
+pub fn duplicate_sheet(sheets: &mut Vec<Rc<RefCell<Sheet>>>, sheet_number: usize) -> Result<Sheet,String>  // sheet_number and sheet_name correspond to the old sheet that has been copied
+{
+    if sheet_number >= sheets.len()
+    {
+        return Err("The sheet you are trying to copy does not exit".to_string());
+    }
+    let mut new_sheet = sheets[sheet_number].borrow().clone();
+    // new_sheet.sheet_name = sheet_name;
+    new_sheet.sheet_idx = sheets.len() as u32;
+    
+
+    for col in new_sheet.data.iter()
+    {
+        for row in col.borrow().iter()
+        {
+            let mut cell: std::cell::RefMut<'_, Cell> = row.borrow_mut();
+            cell.addr.sheet = sheet_number as u32;
+            let dep_list_ref: Option<&CellFunc> = cell.cell_func.as_ref();
+            if let Some(dep_listt) = dep_list_ref
+            {
+                let dep_list: Vec<ParentType> = dep_listt.expression.get_dependency_list();
+                for dep in dep_list.iter()
+                {
+                    match dep {
+                        ParentType::Single(a_1) => {
+                            let sheet_ref =&(*sheets)[a_1.sheet as usize];
+                            let sheet = sheet_ref.borrow();
+                            let column_ref = &sheet.data[a_1.col as usize];
+                            let column = column_ref.borrow();
+                            let cell_rc = Rc::clone(&column[a_1.row as usize]);
+                            // drop(column);
+                            let mut parent_cell = cell_rc.borrow_mut();
+                            parent_cell.children.insert(cell.addr.clone());
+                            if a_1.sheet == sheet_number as u32 
+                            {
+                                let old_addr = Addr::new(sheet_number as u32, cell.addr.row, cell.addr.col);
+                                parent_cell.children.remove(&old_addr);
+                            }
+                        }
+                        ParentType::Range(a_1, a_2) => {
+                            for i in a_1.col..=a_2.col
+                            {
+                                for j in a_1.row..=a_2.row
+                                {
+                                    let sheet_ref =&(*sheets)[a_1.sheet as usize];
+                                    let sheet = sheet_ref.borrow();
+                                    let column_ref = &sheet.data[i as usize];
+                                    let column = column_ref.borrow();
+                                    let cell_rc = Rc::clone(&column[j as usize]);
+                                    // drop(column);
+                                    let mut parent_cell = cell_rc.borrow_mut();
+                                    parent_cell.children.insert(cell.addr.clone());
+                                    if a_1.sheet == sheet_number as u32 
+                                    {
+                                        let old_addr = Addr::new(sheet_number as u32, j, i);
+                                        parent_cell.children.remove(&old_addr);
+                                    }
+                                }
+                            }
+                        },
+                    }
+                }
+
+                
+            }
+            else
+            {
+                continue;
+            }
+
+
+            // idhar hum bache change karenge
+            let mut toinsert = vec![];
+            for address in cell.children.iter()
+            {
+                if address.sheet == sheet_number as u32
+                {
+                    toinsert.push(address.clone());
+                }
+            }
+            
+            cell.children.clear();
+
+            for addr in toinsert.iter()
+            {
+                let new_addr = Addr::new(new_sheet.sheet_idx, addr.row, addr.col);
+                cell.children.insert(new_addr);
+            }
+            // aur idhar cell_func change karenge
+            if let Some(func) = cell.cell_func.clone()
+            { 
+                let temp = update_cell_func(func.expression,sheet_number as u32, new_sheet.sheet_idx);            
+                cell.cell_func = Some(CellFunc::new(temp));
+            }
+            
+        }
+    }
+    for rule in new_sheet.rules.iter_mut()
+    {
+        rule.start.sheet = new_sheet.sheet_idx;
+        rule.end.sheet = new_sheet.sheet_idx;
+        rule.cond = update_cell_func(rule.cond.clone(), sheet_number as u32, new_sheet.sheet_idx);
+    }
+    if let Some(filter) = new_sheet.filter.as_mut()
+    {
+        filter.start.sheet = new_sheet.sheet_idx;
+        filter.cond = update_cell_func(filter.cond.clone(), sheet_number as u32, new_sheet.sheet_idx);
+    }
+    // sheets.push(Rc::new(RefCell::new(new_sheet)));
+    Ok(new_sheet)
+
+}
+
+/// Updates the cell function to reflect changes in the sheet index, used when we duplicate a sheet.
+///
+/// **Arguments:**
+/// - `exp`: The expression to update.
+/// - `sheet_num`: The original sheet index.
+/// - `sheet_idx`: The new sheet index.
+///
+/// **Returns:**
+/// - The updated expression.
+pub fn update_cell_func(exp: Expr, sheet_num: u32, sheet_idx: u32) -> Expr
+{
+    match exp 
+    {
+        Expr::Bool(val) => Expr::Bool(val),
+        Expr::Float(val) => Expr::Float(val),
+        Expr::Date(val) => Expr::Date(val),
+        Expr::Decimal(val) => Expr::Decimal(val),
+        Expr::Integer(val) => Expr::Integer(val),
+        Expr::String(val) => Expr::String(val),
+        Expr::Wildcard => Expr::Wildcard,
+        Expr::RefError => Expr::RefError,
+        Expr::MonoOp(a,b) => 
+        {
+            let expr = update_cell_func(*b, sheet_num, sheet_idx);
+            let exprbox = Box::new(expr);
+            Expr::MonoOp(a,exprbox)
+        },
+        Expr::BinOp(a,b,c) =>
+        {
+            let expr1 = update_cell_func(*b, sheet_num, sheet_idx);
+            let expr2 = update_cell_func(*c, sheet_num, sheet_idx);
+            let exprbox1 = Box::new(expr1);
+            let exprbox2 = Box::new(expr2);
+            Expr::BinOp(a,exprbox1,exprbox2)
+        },
+        Expr::InfixOp(a,b,c) =>
+        {
+            let expr1 = update_cell_func(*a, sheet_num, sheet_idx);
+            let expr2 = update_cell_func(*c, sheet_num, sheet_idx);
+            let exprbox1 = Box::new(expr1);
+            let exprbox2 = Box::new(expr2);
+            Expr::InfixOp(exprbox1, b, exprbox2)
+        },
+        Expr::TernaryOp(a,b,c,d) =>
+        {
+            let expr1 = update_cell_func(*b, sheet_num, sheet_idx);
+            let expr2 = update_cell_func(*c, sheet_num, sheet_idx);
+            let expr3 = update_cell_func(*d, sheet_num, sheet_idx);
+            let exprbox1 = Box::new(expr1);
+            let exprbox2 = Box::new(expr2);
+            let exprbox3 = Box::new(expr3);
+            Expr::TernaryOp(a,exprbox1, exprbox2, exprbox3)
+        },
+        Expr::RangeOp{op, start, end, cond, name} =>
+        {
+            let new_cond = update_cell_func(*cond, sheet_num, sheet_idx);
+            // Names belong to the workbook, so a named range keeps pointing to the original sheet. A range between
+            // sheets keeps its sheets too.
+            if sheet_num == start.sheet && start.sheet == end.sheet && name.is_none()
+            {
+                let new_start = Addr{sheet: sheet_idx, ..start};
+                let new_end = Addr{sheet: sheet_idx, ..end};
+                Expr::RangeOp{op, start: new_start, end: new_end, cond: Box::new(new_cond), name}
+            }
+            else 
+            {
+               Expr::RangeOp { op, start, end, cond: Box::new(new_cond), name }
+            }
+        }
+        Expr::Name(name, exp) => Expr::Name(name, exp),
+        Expr::Call(name, args) => Expr::Call(name, args.into_iter().map(|e| update_cell_func(e, sheet_num, sheet_idx)).collect()),
+        Expr::LookupOp{op, start, end, args, name} =>
+        {
+            let args = args.into_iter().map(|e| update_cell_func(e, sheet_num, sheet_idx)).collect();
+            if sheet_num == start.sheet && name.is_none()
+            {
+                let new_start = Addr{sheet: sheet_idx, ..start};
+                let new_end = Addr{sheet: sheet_idx, ..end};
+                Expr::LookupOp{op, start: new_start, end: new_end, args, name}
+            }
+            else
+            {
+                Expr::LookupOp{op, start, end, args, name}
+            }
+        }
+        Expr::Cell(addr) => 
+        {
+            if addr.sheet == sheet_num
+            {
+                let new_addr = Addr{sheet: sheet_idx, ..addr};
+                Expr::Cell(new_addr)
+            }
+            else
+            {
+                Expr::Cell(addr)
+            }
+        }
+        
+    }
+}
+
+/// Whether a structural change works on rows or on columns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetAxis {
+    Row,
+    Col,
+}
+
+/// Describes one insertion or removal of rows or columns, used to move the addresses in formulas.
+struct Shift {
+    sheet: u32,
+    axis: SheetAxis,
+    at: u32,
+    count: u32,
+    insert: bool,
+}
+
+impl Shift {
+    /// Returns the new position of a row or column number, or `None` if it was removed.
+    fn index(&self, idx: u32) -> Option<u32> {
+        if idx < self.at {
+            Some(idx)
+        } else if self.insert {
+            Some(idx + self.count)
+        } else if idx >= self.at + self.count {
+            Some(idx - self.count)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the new bounds of a span of rows or columns, or `None` if the whole span was removed.
+    /// Inserting inside a span makes it larger and removing part of a span makes it smaller.
+    fn span(&self, start: u32, end: u32) -> Option<(u32, u32)> {
+        if self.insert {
+            return Some((self.index(start)?, self.index(end)?));
+        }
+        let new_start = self.index(start).unwrap_or(self.at);
+        let new_end = match self.index(end) {
+            Some(e) => e,
+            None if self.at == 0 => return None,
+            None => self.at - 1,
+        };
+        if new_start > new_end { None } else { Some((new_start, new_end)) }
+    }
+
+    fn addr(&self, addr: Addr) -> Option<Addr> {
+        if addr.sheet != self.sheet {
+            return Some(addr);
+        }
+        match self.axis {
+            SheetAxis::Row => Some(Addr{row: self.index(addr.row)?, ..addr}),
+            SheetAxis::Col => Some(Addr{col: self.index(addr.col)?, ..addr}),
+        }
+    }
+
+    fn range(&self, start: Addr, end: Addr) -> Option<(Addr, Addr)> {
+        // A range between sheets covers the same cells in each of them, so rows moving in one sheet leave it alone.
+        if start.sheet != self.sheet || end.sheet != self.sheet {
+            return Some((start, end));
+        }
+        match self.axis {
+            SheetAxis::Row => {
+                let (r1, r2) = self.span(start.row, end.row)?;
+                Some((Addr{row: r1, ..start}, Addr{row: r2, ..end}))
+            }
+            SheetAxis::Col => {
+                let (c1, c2) = self.span(start.col, end.col)?;
+                Some((Addr{col: c1, ..start}, Addr{col: c2, ..end}))
+            }
+        }
+    }
+
+    /// Moves the cells a name stands for. A name whose cells were removed becomes a `#REF!` constant.
+    fn name_def(&self, def: NameDef) -> NameDef {
+        match def {
+            NameDef::Cell(addr) => self.addr(addr).map_or(NameDef::Constant(Expr::RefError), NameDef::Cell),
+            NameDef::Range(start, end) => self.range(start, end).map_or(NameDef::Constant(Expr::RefError), |(s, e)| NameDef::Range(s, e)),
+            constant => constant,
+        }
+    }
+}
+
+/// Moves the addresses of an expression after a structural change. References to removed cells become `Expr::RefError`.
+/// `changed` is set to true if any address in the expression was moved or removed.
+fn shift_cell_func(exp: Expr, shift: &Shift, changed: &mut bool) -> Expr
+{
+    match exp
+    {
+        Expr::Cell(addr) =>
+        {
+            match shift.addr(addr.clone())
+            {
+                Some(new_addr) => {
+                    *changed |= new_addr != addr;
+                    Expr::Cell(new_addr)
+                }
+                None => {
+                    *changed = true;
+                    Expr::RefError
+                }
+            }
+        }
+        Expr::Name(name, exp) => Expr::Name(name, Box::new(shift_cell_func(*exp, shift, changed))),
+        Expr::RangeOp{op, start, end, cond, name} =>
+        {
+            let new_cond = shift_cell_func(*cond, shift, changed);
+            match shift.range(start.clone(), end.clone())
+            {
+                Some((new_start, new_end)) => {
+                    *changed |= new_start != start || new_end != end;
+                    Expr::RangeOp{op, start: new_start, end: new_end, cond: Box::new(new_cond), name}
+                }
+                None => {
+                    *changed = true;
+                    Expr::RefError
+                }
+            }
+        }
+        Expr::Call(name, args) => Expr::Call(name, args.into_iter().map(|e| shift_cell_func(e, shift, changed)).collect()),
+        Expr::LookupOp{op, start, end, args, name} =>
+        {
+            let args = args.into_iter().map(|e| shift_cell_func(e, shift, changed)).collect();
+            match shift.range(start.clone(), end.clone())
+            {
+                Some((new_start, new_end)) => {
+                    *changed |= new_start != start || new_end != end;
+                    Expr::LookupOp{op, start: new_start, end: new_end, args, name}
+                }
+                None => {
+                    *changed = true;
+                    Expr::RefError
+                }
+            }
+        }
+        Expr::MonoOp(a, b) => Expr::MonoOp(a, Box::new(shift_cell_func(*b, shift, changed))),
+        Expr::BinOp(a, b, c) =>
+        {
+            let expr1 = shift_cell_func(*b, shift, changed);
+            let expr2 = shift_cell_func(*c, shift, changed);
+            Expr::BinOp(a, Box::new(expr1), Box::new(expr2))
+        },
+        Expr::InfixOp(a, b, c) =>
+        {
+            let expr1 = shift_cell_func(*a, shift, changed);
+            let expr2 = shift_cell_func(*c, shift, changed);
+            Expr::InfixOp(Box::new(expr1), b, Box::new(expr2))
+        },
+        Expr::TernaryOp(a, b, c, d) =>
+        {
+            let expr1 = shift_cell_func(*b, shift, changed);
+            let expr2 = shift_cell_func(*c, shift, changed);
+            let expr3 = shift_cell_func(*d, shift, changed);
+            Expr::TernaryOp(a, Box::new(expr1), Box::new(expr2), Box::new(expr3))
+        },
+        other => other,
+    }
+}
+
+/// Inserts or removes rows or columns of a sheet.
+///
+/// Cells after the change are moved, and every formula in every sheet that points into the changed sheet is
+/// rewritten to follow the cells. Formulas that pointed to a removed cell get a `#REF!` reference and become error
+/// cells. The dependency graph is then rebuilt and all formulas are evaluated again.
+///
+/// **Arguments:**
+/// - `sheetstore`: The store holding the sheet.
+/// - `sheet_num`: The sheet number of the sheet to change.
+/// - `axis`: Whether rows or columns are changed.
+/// - `at`: Zero based index of the first row or column inserted or removed.
+/// - `count`: Number of rows or columns.
+/// - `insert`: `true` to insert, `false` to remove.
+///
+/// **Returns:**
+/// - `Ok(())` if the operation is successful.
+/// - `Err(String)` if the index or count is out of range, or the sheet would grow past `MAX_ROWS` or `MAX_COLS`.
+pub fn shift_sheet(sheetstore: &mut SheetStorage, sheet_num: u32, axis: SheetAxis, at: u32, count: u32, insert: bool) -> Result<(), String>
+{
+    let (name, limit) = match axis {
+        SheetAxis::Row => ("row", cell_operations::MAX_ROWS),
+        SheetAxis::Col => ("column", cell_operations::MAX_COLS),
+    };
+    if sheetstore.name_from_num(sheet_num as usize).is_none() {
+        return Err("Sheet not found".to_string());
+    }
+    if count == 0 {
+        return Err(format!("Number of {}s cannot be zero", name));
+    }
+    {
+        let mut sheet = sheetstore.data[sheet_num as usize].borrow_mut();
+        let size = match axis {
+            SheetAxis::Row => sheet.rows,
+            SheetAxis::Col => sheet.columns,
+        };
+        if insert {
+            if at > size {
+                return Err(format!("The {} is out of range", name));
+            }
+            if size.checked_add(count).is_none_or(|n| n > limit) {
+                return Err(format!("Too many {}s", name));
+            }
+        }
+        else {
+            if at.checked_add(count).is_none_or(|e| e > size) {
+                return Err(format!("The {}s to remove are out of range", name));
+            }
+            if count == size {
+                return Err(format!("Cannot remove every {} of a sheet", name));
+            }
+        }
+        match (axis, insert) {
+            (SheetAxis::Row, true) => sheet.insert_rows(at as usize, count as usize),
+            (SheetAxis::Row, false) => sheet.remove_rows(at as usize, count as usize),
+            (SheetAxis::Col, true) => sheet.insert_cols(at as usize, count as usize),
+            (SheetAxis::Col, false) => sheet.remove_cols(at as usize, count as usize),
+        }
+    }
+
+    let shift = Shift{sheet: sheet_num, axis, at, count, insert};
+    for (_, num) in &sheetstore.map
+    {
+        let sheet = sheetstore.data[*num].borrow();
+        for column_ref in &sheet.data
+        {
+            for cell_rc in column_ref.borrow().iter()
+            {
+                let mut cell = cell_rc.borrow_mut();
+                let Some(func) = cell.cell_func.clone() else { continue };
+                let mut changed = false;
+                let exp = shift_cell_func(func.expression, &shift, &mut changed);
+                if changed
+                {
+                    cell.formula = exp.to_formula(*num as u32, sheetstore);
+                    cell.cell_func = Some(CellFunc::new(exp));
+                }
+            }
+        }
+    }
+    let names = std::mem::take(&mut sheetstore.names);
+    sheetstore.names = names.into_iter().map(|(name, def)| (name, shift.name_def(def))).collect();
+    // Rules move with their cells, and a rule whose cells were all removed goes with them.
+    for (_, num) in &sheetstore.map
+    {
+        let mut sheet = sheetstore.data[*num].borrow_mut();
+        let rules = std::mem::take(&mut sheet.rules);
+        sheet.rules = rules.into_iter().filter_map(|rule| {
+            let (start, end) = shift.range(rule.start, rule.end)?;
+            let cond = shift_cell_func(rule.cond, &shift, &mut false);
+            Some(FormatRule{start, end, cond, options: rule.options})
+        }).collect();
+        // A filter starting on a removed row starts on the next one, and goes when its column is removed.
+        sheet.filter = sheet.filter.take().and_then(|filter| {
+            let start = match shift.axis {
+                SheetAxis::Row if filter.start.sheet == shift.sheet => Addr{row: shift.index(filter.start.row).unwrap_or(shift.at), ..filter.start},
+                _ => shift.addr(filter.start)?,
+            };
+            Some(RowFilter{start, cond: shift_cell_func(filter.cond, &shift, &mut false)})
+        });
+    }
+    reevaluate_all(&mut sheetstore.data);
+    Ok(())
+}
+
+/// Points the uses of `name` in an expression at a new definition. `found` is set to true if the name is used.
+///
+/// **Returns:**
+/// - `Ok(Expr)` with the updated expression.
+/// - `Err(String)` if the name is used in a way the new definition does not allow, eg. as a value when it is now a range.
+fn retarget_name(exp: Expr, name: &str, def: &NameDef, found: &mut bool) -> Result<Expr, String>
+{
+    let range = |found: &mut bool| -> Result<(Addr, Addr), String> {
+        *found = true;
+        match def {
+            NameDef::Cell(addr) => Ok((addr.clone(), addr.clone())),
+            NameDef::Range(start, end) => Ok((start.clone(), end.clone())),
+            NameDef::Constant(_) => Err(format!("Name \"{}\" is used as a range, so it cannot be a constant", name)),
+        }
+    };
+    Ok(match exp
+    {
+        Expr::Name(n, _) if n == name =>
+        {
+            *found = true;
+            match def {
+                NameDef::Cell(addr) => Expr::Name(n, Box::new(Expr::Cell(addr.clone()))),
+                NameDef::Constant(c) => Expr::Name(n, Box::new(c.clone())),
+                NameDef::Range(..) => return Err(format!("Name \"{}\" is used as a value, so it cannot be a range", name)),
+            }
+        }
+        Expr::RangeOp{op, start, end, cond, name: n} =>
+        {
+            let cond = Box::new(retarget_name(*cond, name, def, found)?);
+            let (start, end) = if n.as_deref() == Some(name) { range(found)? } else { (start, end) };
+            Expr::RangeOp{op, start, end, cond, name: n}
+        }
+        Expr::LookupOp{op, start, end, args, name: n} =>
+        {
+            let args = args.into_iter().map(|e| retarget_name(e, name, def, found)).collect::<Result<_, _>>()?;
+            let (start, end) = if n.as_deref() == Some(name) { range(found)? } else { (start, end) };
+            Expr::LookupOp{op, start, end, args, name: n}
+        }
+        Expr::Call(n, args) => Expr::Call(n, args.into_iter().map(|e| retarget_name(e, name, def, found)).collect::<Result<_, _>>()?),
+        Expr::MonoOp(a, b) => Expr::MonoOp(a, Box::new(retarget_name(*b, name, def, found)?)),
+        Expr::BinOp(a, b, c) => Expr::BinOp(a, Box::new(retarget_name(*b, name, def, found)?), Box::new(retarget_name(*c, name, def, found)?)),
+        Expr::InfixOp(a, b, c) => Expr::InfixOp(Box::new(retarget_name(*a, name, def, found)?), b, Box::new(retarget_name(*c, name, def, found)?)),
+        Expr::TernaryOp(a, b, c, d) =>
+        {
+            let expr1 = retarget_name(*b, name, def, found)?;
+            let expr2 = retarget_name(*c, name, def, found)?;
+            let expr3 = retarget_name(*d, name, def, found)?;
+            Expr::TernaryOp(a, Box::new(expr1), Box::new(expr2), Box::new(expr3))
+        },
+        other => other,
+    })
+}
+
+/// Finds the formulas that use `name` and works out what they become under a new definition.
+///
+/// **Returns:**
+/// - `Ok(Vec)` with the address and updated formula of every cell that uses the name.
+/// - `Err(String)` if one of them cannot use the new definition.
+fn name_users(sheetstore: &SheetStorage, name: &str, def: &NameDef) -> Result<Vec<(Addr, CellFunc)>, String>
+{
+    let mut users = vec![];
+    for (_, num) in &sheetstore.map
+    {
+        let sheet = sheetstore.data[*num].borrow();
+        for column_ref in &sheet.data
+        {
+            for cell_rc in column_ref.borrow().iter()
+            {
+                let cell = cell_rc.borrow();
+                let Some(func) = &cell.cell_func else { continue };
+                let mut found = false;
+                let exp = retarget_name(func.expression.clone(), name, def, &mut found)?;
+                if found
+                {
+                    users.push((cell.addr.clone(), CellFunc::new(exp)));
+                }
+            }
+        }
+    }
+    Ok(users)
+}
+
+/// Checks that the cells a name stands for are inside a live sheet, and creates them so they can hold children.
+fn check_name_def(sheetstore: &SheetStorage, def: &NameDef) -> Result<(), String>
+{
+    let (start, end) = match def {
+        NameDef::Cell(addr) => (addr, addr),
+        NameDef::Range(start, end) => (start, end),
+        NameDef::Constant(_) => return Ok(()),
+    };
+    if sheetstore.name_from_num(start.sheet as usize).is_none() || sheetstore.name_from_num(end.sheet as usize).is_none() {
+        return Err("Sheet not found".to_string());
+    }
+    if start.sheet > end.sheet || start.row > end.row || start.col > end.col {
+        return Err("Range start is after range end".to_string());
+    }
+    for sheet_num in range_sheets(&sheetstore.data, start, end) {
+        let sheet = sheetstore.data[sheet_num as usize].borrow();
+        if end.row >= sheet.rows || end.col >= sheet.columns {
+            return Err("Name address out of range".to_string());
+        }
+    }
+    extend_range_sheets(&sheetstore.data, start, end);
+    Ok(())
+}
+
+/// Defines a name, or changes what an existing name stands for. Every formula that uses the name is pointed at
+/// the new definition and evaluated again, so cells that depend on the name are recalculated.
+///
+/// **Arguments:**
+/// - `sheetstore`: The store holding the names.
+/// - `name`: The name, in lower case.
+/// - `def`: The cell, range or constant the name stands for.
+///
+/// **Returns:**
+/// - `Ok(Option<NameDef>)` with the previous definition, if there was one.
+/// - `Err(String)` if the definition is invalid, does not fit a formula that uses the name or makes a cycle.
+///   The name is left as it was.
+pub fn define_name(sheetstore: &mut SheetStorage, name: &str, def: NameDef) -> Result<Option<NameDef>, String>
+{
+    check_name_def(sheetstore, &def)?;
+    let users = name_users(sheetstore, name, &def)?;
+    let old_def = sheetstore.names.insert(name.to_string(), def);
+    for (addr, func) in users
+    {
+        let old_func = {
+            let sheet = sheetstore.data[addr.sheet as usize].borrow();
+            let cell_rc = Rc::clone(&sheet.data[addr.col as usize].borrow()[addr.row as usize]);
+            cell_rc.borrow_mut().cell_func.replace(func)
+        };
+        if let Err(e) = evaluate(&mut sheetstore.data, &addr, &old_func) && e.contains("Cyclic dependency")
+        {
+            // Cells changed so far go back to the old definition, which made no cycle.
+            match old_def {
+                Some(old) => { define_name(sheetstore, name, old)?; }
+                None => { sheetstore.names.remove(name); }
+            }
+            return Err(e);
+        }
+    }
+    Ok(old_def)
+}
+
+/// Removes a name that no formula uses.
+///
+/// **Returns:**
+/// - `Ok(NameDef)` with the definition of the removed name.
+/// - `Err(String)` if the name is not defined or a formula still uses it.
+pub fn remove_name(sheetstore: &mut SheetStorage, name: &str) -> Result<NameDef, String>
+{
+    let def = sheetstore.names.get(name).ok_or_else(|| format!("Name \"{}\" is not defined", name))?;
+    if let Some((addr, _)) = name_users(sheetstore, name, def)?.first() {
+        return Err(format!("Name \"{}\" is used by {}", name, addr.to_formula(u32::MAX, sheetstore)));
+    }
+    Ok(sheetstore.names.remove(name).unwrap())
+}
+
+/// Checks the conditional format rules of every cell from `start` to `end` again. Missing cells are created, so
+/// that empty cells are formatted as well.
+fn refresh_format_rules(sheets: &[Rc<RefCell<Sheet>>], start: &Addr, end: &Addr)
+{
+    let sheet = sheets[start.sheet as usize].borrow();
+    sheet.extend_range(start, end);
+    let mut cells = vec![];
+    for col in start.col..=end.col
+    {
+        let column = sheet.data[col as usize].borrow();
+        cells.extend((start.row..=end.row).map(|row| Rc::clone(&column[row as usize])));
+    }
+    drop(sheet);
+    for cell_rc in cells
+    {
+        apply_format_rules(sheets, &cell_rc);
+    }
+}
+
+/// Adds a conditional format rule to the sheet of its cells and applies it to them. The rule is checked again
+/// every time one of its cells is calculated.
+pub fn add_format_rule(sheets: &[Rc<RefCell<Sheet>>], rule: FormatRule)
+{
+    let (start, end) = (rule.start.clone(), rule.end.clone());
+    sheets[start.sheet as usize].borrow_mut().rules.push(rule);
+    refresh_format_rules(sheets, &start, &end);
+}
+
+/// Removes every conditional format rule whose cells overlap the range from `start` to `end`.
+///
+/// **Returns:**
+/// - The number of rules removed.
+pub fn clear_format_rules(sheets: &[Rc<RefCell<Sheet>>], start: &Addr, end: &Addr) -> usize
+{
+    let removed: Vec<FormatRule> = {
+        let mut sheet = sheets[start.sheet as usize].borrow_mut();
+        let (removed, kept) = std::mem::take(&mut sheet.rules).into_iter().partition(|rule| {
+            rule.start.row <= end.row && start.row <= rule.end.row && rule.start.col <= end.col && start.col <= rule.end.col
+        });
+        sheet.rules = kept;
+        removed
+    };
+    for rule in &removed
+    {
+        refresh_format_rules(sheets, &rule.start, &rule.end);
+    }
+    removed.len()
+}
+
+/// One key of a sort: a column of the sheet and whether it is sorted from the largest value down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub col: u32,
+    pub descending: bool,
+}
+
+/// Reads a list of sort keys separated by commas, like `B desc,A`. Each key is a column followed by `asc` or
+/// `desc`, which is `asc` when left out.
+///
+/// **Returns:**
+/// - `Ok(Vec<SortKey>)` with the keys in the order they were written, the first one sorting first.
+/// - `Err(String)` naming the first key that is not valid.
+pub fn parse_sort_keys(spec: &str) -> Result<Vec<SortKey>, String>
+{
+    let keys = spec.split(',').map(str::trim).filter(|key| !key.is_empty()).map(|key| {
+        let invalid = || format!("Invalid sort key \"{}\"", key);
+        let mut words = key.split_whitespace();
+        let col = words.next().and_then(|col| name_to_col(&col.to_ascii_uppercase())).ok_or_else(invalid)?;
+        let descending = match words.next() {
+            None | Some("asc") => false,
+            Some("desc") => true,
+            Some(_) => return Err(invalid()),
+        };
+        if words.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(SortKey{col, descending})
+    }).collect::<Result<Vec<SortKey>, String>>()?;
+    if keys.is_empty() {
+        return Err("No sort key given".to_string());
+    }
+    Ok(keys)
+}
+
+/// Orders two cells of a sort key. Empty cells go last whatever the direction.
+fn compare_sort_cells(a: &Cell, b: &Cell, descending: bool) -> Ordering
+{
+    let is_empty = |cell: &Cell| cell.cell_func.is_none() && !cell.is_error();
+    match (is_empty(a), is_empty(b)) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) if descending => compare_values(&a.value, &b.value).reverse(),
+        (false, false) => compare_values(&a.value, &b.value),
+    }
+}
+
+/// Sorts the rows of the range from `start` to `end` by the values in the key columns, the first key first.
+/// Rows that are equal on every key keep their order. Values of different types are ordered by `compare_values`.
+///
+/// Whole rows of the range move, with the formulas, values and formats of their cells. A moved formula has its
+/// relative references moved with it, like `copy_cell_function`, so a formula reading cells of its own row still
+/// reads them after the sort. The dependencies of every sheet are built again afterwards.
+///
+/// **Returns:**
+/// - `Ok(())` if the range was sorted.
+/// - `Err(String)` if a key column is outside the range.
+pub fn sort_range(sheetstore: &mut SheetStorage, start: &Addr, end: &Addr, keys: &[SortKey]) -> Result<(), String>
+{
+    if let Some(key) = keys.iter().find(|key| key.col < start.col || key.col > end.col) {
+        return Err(format!("Sort column {} is outside the range", col_to_name(key.col)));
+    }
+    let rows: Vec<Vec<Cell>> = {
+        let sheet = sheetstore.data[start.sheet as usize].borrow();
+        sheet.extend_range(start, end);
+        (start.row..=end.row).map(|row| {
+            (start.col..=end.col).map(|col| sheet.data[col as usize].borrow()[row as usize].borrow().clone()).collect()
+        }).collect()
+    };
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by(|&a, &b| {
+        keys.iter()
+            .map(|key| {
+                let idx = (key.col - start.col) as usize;
+                compare_sort_cells(&rows[a][idx], &rows[b][idx], key.descending)
+            })
+            .find(|ord| ord.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    if order.iter().enumerate().all(|(new_row, &old_row)| new_row == old_row) {
+        return Ok(());
+    }
+
+    for (new_row, &old_row) in order.iter().enumerate()
+    {
+        let row_off = new_row as i64 - old_row as i64;
+        for (idx, old) in rows[old_row].iter().enumerate()
+        {
+            let func = old.cell_func.clone().map(|func| translate_cell_func(func.expression, row_off, 0, &sheetstore.data));
+            let formula = match &func {
+                Some(exp) => exp.to_formula(start.sheet, sheetstore),
+                None => old.formula.clone(),
+            };
+            let sheet = sheetstore.data[start.sheet as usize].borrow();
+            let column = sheet.data[start.col as usize + idx].borrow();
+            let mut cell = column[start.row as usize + new_row].borrow_mut();
+            cell.cell_func = func.map(CellFunc::new);
+            cell.formula = formula;
+            cell.value = old.value.clone();
+            cell.format = old.format.clone();
+            cell.rule_options = old.rule_options.clone();
+        }
+    }
+    reevaluate_all(&mut sheetstore.data);
+    Ok(())
+}
+
+/// What a pivot summarises: the aggregate `func` of the `values` column for each key of the `rows` column, and
+/// for each key of the `cols` column when there is one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PivotSpec {
+    pub rows: u32,
+    pub cols: Option<u32>,
+    pub func: RangeFunction,
+    pub values: u32,
+}
+
+/// Reads a pivot like `rows=A,cols=B,sum=D`. `cols` can be left out, and the aggregate is one of `sum`, `avg`,
+/// `min`, `max`, `stdev` and `count`, naming the column it is taken of.
+///
+/// **Returns:**
+/// - `Ok(PivotSpec)` if the pivot has rows and an aggregate.
+/// - `Err(String)` naming the first option that is not valid, or telling what is missing.
+pub fn parse_pivot_spec(spec: &str) -> Result<PivotSpec, String>
+{
+    let (mut rows, mut cols, mut agg) = (None, None, None);
+    for opt in spec.split(',').map(str::trim).filter(|opt| !opt.is_empty())
+    {
+        let invalid = || format!("Invalid pivot option \"{}\"", opt);
+        let (key, col) = opt.split_once('=').ok_or_else(invalid)?;
+        let col = name_to_col(&col.trim().to_ascii_uppercase()).ok_or_else(invalid)?;
+        let func = match key.trim() {
+            "rows" => { rows = Some(col); continue; }
+            "cols" => { cols = Some(col); continue; }
+            "sum" => RangeFunction::Sum,
+            "avg" => RangeFunction::Avg,
+            "min" => RangeFunction::Min,
+            "max" => RangeFunction::Max,
+            "stdev" => RangeFunction::Stdev,
+            "count" => RangeFunction::Count,
+            _ => return Err(invalid()),
+        };
+        agg = Some((func, col));
+    }
+    match (rows, agg) {
+        (Some(rows), Some((func, values))) => Ok(PivotSpec{rows, cols, func, values}),
+        _ => Err("A pivot needs rows=<column> and an aggregate like sum=<column>".to_string()),
+    }
+}
+
+/// Writes a formula into a cell of the store and evaluates it. An error the formula evaluates to stays in the cell.
+fn write_formula(sheetstore: &mut SheetStorage, addr: &Addr, exp: Expr)
+{
+    let formula = exp.to_formula(addr.sheet, sheetstore);
+    {
+        let sheet = sheetstore.data[addr.sheet as usize].borrow();
+        let column = sheet.data[addr.col as usize].borrow();
+        let mut cell = column[addr.row as usize].borrow_mut();
+        cell.cell_func = Some(CellFunc::new(exp));
+        cell.formula = formula;
+    }
+    let _ = evaluate(&mut sheetstore.data, addr, &None);
+}
+
+/// Summarises the range from `start` to `end` into a new sheet called `name`. The keys of the `rows` column go
+/// down the first column of the new sheet and the keys of the `cols` column along its first row, each once, as
+/// `KEY` formulas. Every other cell is a `SUMBY` (or `AVGBY`, ...) formula reading the range and the keys beside it,
+/// so the summary follows later changes to the range, keys included. The sheet keeps the size it was made with, so
+/// a key beyond it needs a new pivot, and a key slot left without a key shows an error.
+///
+/// **Returns:**
+/// - `Ok(usize)` with the number of the new sheet.
+/// - `Err(String)` if a column of the pivot is outside the range or the name is taken.
+pub fn pivot(sheetstore: &mut SheetStorage, start: &Addr, end: &Addr, spec: &PivotSpec, name: &str) -> Result<usize, String>
+{
+    for col in [Some(spec.rows), spec.cols, Some(spec.values)].into_iter().flatten()
+    {
+        if col < start.col || col > end.col {
+            return Err(format!("Pivot column {} is outside the range", col_to_name(col)));
+        }
+    }
+    sheetstore.data[start.sheet as usize].borrow().extend_range(start, end);
+    let row_keys = distinct_keys(&sheetstore.data, start, end, spec.rows);
+    let col_keys = match spec.cols {
+        Some(col) => distinct_keys(&sheetstore.data, start, end, col),
+        None => vec![],
+    };
+    let label = format!("{} of {}", spec.func.name(), col_to_name(spec.values));
+    let num = sheetstore.new_sheet(name, col_keys.len().max(1) + 1, row_keys.len() + 1)
+        .ok_or_else(|| format!("Sheet name \"{}\" already exists.", name))?;
+    let num_u32 = num as u32;
+    {
+        let sheet = sheetstore.data[num].borrow();
+        sheet.extend_range(&Addr::new(num_u32, 0, 0), &Addr::new(num_u32, sheet.rows - 1, sheet.columns - 1));
+    }
+
+    let corner = if spec.cols.is_some() { label.clone() } else { col_to_name(spec.rows) };
+    write_formula(sheetstore, &Addr::new(num_u32, 0, 0), Expr::String(corner));
+    if spec.cols.is_none() {
+        write_formula(sheetstore, &Addr::new(num_u32, 0, 1), Expr::String(label));
+    }
+
+    // The range is read with absolute addresses and its columns are counted from one, like in VLOOKUP.
+    let source = |addr: &Addr| Addr{abs_row: true, abs_col: true, ..addr.clone()};
+    let position = |col: u32| Expr::Integer((col - start.col + 1) as i64);
+    let key = |col: u32, n: usize| Expr::LookupOp{op: LookupFunction::Key, start: source(start), end: source(end),
+        args: vec![position(col), Expr::Integer(n as i64 + 1)], name: None};
+    for i in 0..row_keys.len() {
+        write_formula(sheetstore, &Addr::new(num_u32, i as u32 + 1, 0), key(spec.rows, i));
+    }
+    if let Some(col) = spec.cols {
+        for j in 0..col_keys.len() {
+            write_formula(sheetstore, &Addr::new(num_u32, 0, j as u32 + 1), key(col, j));
+        }
+    }
+    for i in 0..row_keys.len() as u32
+    {
+        for j in 0..col_keys.len().max(1) as u32
+        {
+            let mut args = vec![position(spec.values), position(spec.rows), Expr::Cell(Addr{abs_col: true, ..Addr::new(num_u32, i + 1, 0)})];
+            if let Some(col) = spec.cols {
+                args.extend([position(col), Expr::Cell(Addr{abs_row: true, ..Addr::new(num_u32, 0, j + 1)})]);
+            }
+            let exp = Expr::LookupOp{op: LookupFunction::GroupBy(spec.func.clone()), start: source(start), end: source(end), args, name: None};
+            write_formula(sheetstore, &Addr::new(num_u32, i + 1, j + 1), exp);
+        }
+    }
+    Ok(num)
+}
+
+/// What `:find` and `:replace` look for. The text is matched as written, or read as a regular expression with
+/// `regex`. `formulas` searches the formulas of the cells instead of the values they show, and `all_sheets` every
+/// sheet instead of the current one.
+#[derive(Debug, Clone)]
+pub struct FindSpec
+{
+    pub text: String,
+    pub pattern: Regex,
+    pub regex: bool,
+    pub formulas: bool,
+    pub all_sheets: bool,
+}
+
+/// Parses the text to find and its options, written like `"formulas, regex, all"`. The options may be empty.
+///
+/// **Returns:**
+/// - `Ok(FindSpec)` with the compiled pattern.
+/// - `Err(String)` if the text is empty, an option is unknown or the regular expression is invalid.
+pub fn parse_find_spec(text: &str, options: &str) -> Result<FindSpec, String>
+{
+    if text.is_empty() {
+        return Err("Nothing to find".to_string());
+    }
+    let (mut regex, mut formulas, mut all_sheets) = (false, false, false);
+    for option in options.split(',').map(str::trim).filter(|option| !option.is_empty())
+    {
+        match option
+        {
+            "regex" => regex = true,
+            "formulas" => formulas = true,
+            "all" => all_sheets = true,
+            _ => return Err(format!("Invalid find option \"{}\"", option)),
+        }
+    }
+    let pattern = if regex { text.to_string() } else { regex::escape(text) };
+    let pattern = Regex::new(&pattern).map_err(|_| format!("Invalid regular expression \"{}\"", text))?;
+    Ok(FindSpec{text: text.to_string(), pattern, regex, formulas, all_sheets})
+}
+
+/// Returns the cells holding something that `spec` matches, in the order of the sheet tabs and row by row within
+/// a sheet. Values are matched as the cells show them, with their format. Empty cells never match.
+///
+/// **Arguments:**
+/// - `sheetstore`: The store holding the sheets.
+/// - `curr_sheet`: The sheet searched when `spec` does not search all of them.
+/// - `spec`: What to look for, see `parse_find_spec`.
+pub fn find_cells(sheetstore: &SheetStorage, curr_sheet: u32, spec: &FindSpec) -> Vec<Addr>
+{
+    let sheet_nums: Vec<usize> = if spec.all_sheets {
+        sheetstore.map.iter().map(|(_, num)| *num).collect()
+    } else {
+        vec![curr_sheet as usize]
+    };
+    let mut found = vec![];
+    for num in sheet_nums
+    {
+        let sheet = sheetstore.data[num].borrow();
+        let mut in_sheet: Vec<Addr> = vec![];
+        for column in &sheet.data
+        {
+            for cell_rc in column.borrow().iter()
+            {
+                let cell = cell_rc.borrow();
+                if cell.cell_func.is_none() {
+                    continue;
+                }
+                let text = if spec.formulas { cell.formula.clone() } else { cell.shown_format().render(&cell.value) };
+                if spec.pattern.is_match(&text) {
+                    in_sheet.push(cell.addr.clone());
+                }
+            }
+        }
+        in_sheet.sort_by_key(|addr| (addr.row, addr.col));
+        found.extend(in_sheet);
+    }
+    found
+}
+
+/// Replaces what `spec` matches in the formulas of the cells `cells`, usually found with `find_cells`, by `with`,
+/// and evaluates the rewritten cells again. With `regex`, `with` may use the groups of the match like `$1`.
+/// Every rewritten formula is parsed before any cell changes, so a replacement giving an invalid formula changes
+/// nothing. A rewritten formula that closes a cycle is not kept, but the other cells are still rewritten.
+///
+/// **Returns:**
+/// - `Ok(usize)` with the number of cells rewritten.
+/// - `Err(String)` if a rewritten formula is invalid or refers outside its sheets, or closes a cycle.
+pub fn replace_in_formulas(sheetstore: &mut SheetStorage, cells: &[Addr], spec: &FindSpec, with: &str) -> Result<usize, String>
+{
+    let mut rewritten = vec![];
+    for addr in cells
+    {
+        let formula = {
+            let sheet = sheetstore.data[addr.sheet as usize].borrow();
+            let column = sheet.data[addr.col as usize].borrow();
+            column[addr.row as usize].borrow().formula.clone()
+        };
+        let new_formula = if spec.regex {
+            spec.pattern.replace_all(&formula, with).into_owned()
+        } else {
+            spec.pattern.replace_all(&formula, NoExpand(with)).into_owned()
+        };
+        if new_formula == formula {
+            continue;
+        }
+        let name = format!("{}{}", col_to_name(addr.col), addr.row + 1);
+        let invalid = || format!("Replacing gives an invalid formula \"{}\" in {}", new_formula, name);
+        let Ok((Command::AssignCmd(_, expr), deps)) = parse_assign(&format!("{}={}", name, new_formula), addr.sheet, sheetstore) else {
+            return Err(invalid());
+        };
+        let ranges: Vec<(Addr, Addr)> = deps.into_iter().map(|dep| match dep {
+            ParentType::Single(a) => (a.clone(), a),
+            ParentType::Range(a1, a2) => (a1, a2),
+        }).collect();
+        for (a1, a2) in &ranges
+        {
+            if a1.sheet > a2.sheet || a1.row > a2.row || a1.col > a2.col {
+                return Err(invalid());
+            }
+            for sheet_num in range_sheets(&sheetstore.data, a1, a2) {
+                let sheet = sheetstore.data[sheet_num as usize].borrow();
+                if a2.row >= sheet.rows || a2.col >= sheet.columns {
+                    return Err(invalid());
+                }
+            }
+        }
+        rewritten.push((addr.clone(), *expr, new_formula, ranges));
+    }
+
+    let mut count = 0;
+    let mut cycle = None;
+    for (addr, expr, new_formula, ranges) in rewritten
+    {
+        for (a1, a2) in &ranges {
+            extend_range_sheets(&sheetstore.data, a1, a2);
+        }
+        let (old_func, old_formula) = {
+            let sheet = sheetstore.data[addr.sheet as usize].borrow();
+            let column = sheet.data[addr.col as usize].borrow();
+            let mut cell = column[addr.row as usize].borrow_mut();
+            (cell.cell_func.replace(CellFunc::new(expr)), std::mem::replace(&mut cell.formula, new_formula))
+        };
+        match evaluate(&mut sheetstore.data, &addr, &old_func)
+        {
+            // The old formula is back in the cell, and so must be its text.
+            Err(e) if e.contains("Cyclic dependency detected") || e.contains("Negative sleep time") => {
+                let sheet = sheetstore.data[addr.sheet as usize].borrow();
+                sheet.data[addr.col as usize].borrow()[addr.row as usize].borrow_mut().formula = old_formula;
+                cycle.get_or_insert(format!("Replacing in {}{}: {}", col_to_name(addr.col), addr.row + 1, e));
+            }
+            _ => count += 1,
+        }
+    }
+    match cycle {
+        Some(e) => Err(e),
+        None => Ok(count),
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;  // to implement the Display trait later
+use std::num::ParseIntError;
+use logos::Logos;
+use crate::ast::RangeFunction;
+use crate::cell_operations::{Date, Decimal};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum LexicalError {
+    InvalidInteger(ParseIntError),
+    InternalError(String),
+    SheetNotFoundErr(String),
+    FunctionErr(String),
+    NameErr(String),
+    AddressOutOfRange(String),
+    NumberTooLarge(String),
+    #[default]
+    InvalidToken,
+    
+}
+
+impl From<ParseIntError> for LexicalError {
+    fn from(err: ParseIntError) -> Self {
+        LexicalError::InvalidInteger(err)
+    }
+}
+
+
+
+/// Parses a cell like `B3` or `$B$3` into (column, row, absolute column, absolute row).
+/// Rows and columns can go up to the `u32` range, anything larger is an `AddressOutOfRange` error.
+fn parse_local_cell(s: &str) -> Result<(u32, u32, bool, bool), LexicalError> {
+    let out_of_range = || LexicalError::AddressOutOfRange(s.to_string());
+    let abs_col = s.starts_with('$');
+    let abs_row = s[1..].contains('$');
+    let (letters, digits) = s.chars()
+        .filter(|c| *c != '$')
+        .partition::<String, _>(|c| c.is_ascii_alphabetic());
+
+    let row = digits.parse::<u32>().map_err(|_| out_of_range())? - 1;
+    let mut col: u32 = 0;
+    for c in letters.chars() {
+        col = col.checked_mul(26)
+            .and_then(|col| col.checked_add((c as u8 - b'A' + 1) as u32))
+            .ok_or_else(out_of_range)?;
+    }
+    col -=1; //Zero based indexing
+    Ok((col, row, abs_col, abs_row))
+}
+
+fn group_function(s: &str) -> RangeFunction {
+    match &s[..s.len() - 2] {
+        "SUM" => RangeFunction::Sum,
+        "AVG" => RangeFunction::Avg,
+        "MIN" => RangeFunction::Min,
+        "MAX" => RangeFunction::Max,
+        "STDEV" => RangeFunction::Stdev,
+        _ => RangeFunction::Count,
+    }
+}
+
+fn parse_global_cell(s: &str) -> Result<(String, u32, u32, bool, bool), LexicalError> {
+
+    let (sheet, addr) = s.split_once(".").unwrap(); //NOTE: Source of panic, ensure it is correct.
+    let (col, row, abs_col, abs_row) = parse_local_cell(addr)?;
+    Ok((sheet.to_string(), col, row, abs_col, abs_row))
+}
+
+#[derive(Logos, Debug, Clone, PartialEq)]
+#[logos(skip r"[ \t\n\r\f]+", error = LexicalError)]  //removed skip r"[ \t\n\r\f]+"
+pub enum Token {
+
+    // #[regex("[ \t]+")]
+    // Ws,  //Ws stands for whitespace
+    #[regex(r"(0|[1-9][0-9]*)\.[0-9]+([eE][-+]?[0-9]+)?", |lex| lex.slice().parse().ok())]
+    #[regex("(0|[1-9][0-9]*)[eE][-+]?[0-9]+", |lex| lex.slice().parse().ok())]
+    Float(f64),
+
+    #[regex("0|[1-9][0-9]*", |lex| lex.slice().parse::<i64>().map_err(|_| LexicalError::NumberTooLarge(lex.slice().to_string())))]
+    Integer(i64),
+
+    #[regex(r"(0|[1-9][0-9]*)(\.[0-9]+)?d", |lex| {let s = lex.slice(); Decimal::parse(&s[..s.len() - 1]).ok_or(LexicalError::NumberTooLarge(s.to_string()))})]
+    Decimal(Decimal), //Written with a `d` after the digits, as in 12.50d
+
+    #[regex("[0-9]{4}-[0-9]{2}-[0-9]{2}(T[0-9]{2}:[0-9]{2}(:[0-9]{2})?)?", |lex| Date::parse(lex.slice()))]
+    Date(Date), //Written as 2024-03-15 or 2024-03-15T09:30. Being the longer match, it wins over reading 2024-03-15 as a subtraction
+
+
+    #[regex("True|False", |lex| Some(lex.slice() == "True"))]
+    Bool(bool),
+
+    #[regex("\"([^\"]|\"\")*\"", |lex| {let s = lex.slice(); s[1..s.len() - 1].replace("\"\"", "\"")})]
+    #[regex("\'[^\']*\'", |lex| {let s = lex.slice(); s[1..s.len() - 1].to_string()}) ]
+    Str(String), //A doubled quote inside double quotes stands for one quote, as in "say ""hi"""
+
+    #[token("_")]
+    Wildcard,
+    #[token("#REF!")]
+    RefError,
+  
+    #[regex("\\$?[A-Z]+\\$?[1-9][0-9]*", |lex| parse_local_cell(lex.slice()))]
+    LocalCell((u32, u32, bool, bool)),
+    #[regex("[a-z0-9_]+\\.\\$?[A-Z]+\\$?[1-9][0-9]*", |lex| parse_global_cell(lex.slice()))] //NOTE: Sheet names must be lower case in this implementation
+    GlobalCell((String, u32, u32, bool, bool)),
+
+    #[token("SUM")]
+    Sum,
+    #[token("AVG")]
+    Avg,
+    #[token("MIN")]
+    Min,
+    #[token("MAX")]
+    Max,
+    #[token("STDEV")]
+    Stdev,
+    #[token("SLEEP")]
+    Sleep,
+    #[token("NOT")]
+    Not,
+    #[token("ROUND")]
+    Round,
+    #[token("isSubstr")]
+    IsSubstr,
+    #[token("COUNT")]
+    Count,
+    #[token("IFELSE")]
+    IfElse,
+    #[token("ISERROR")]
+    IsError,
+    #[token("IFERROR")]
+    IfError,
+    #[token("VLOOKUP")]
+    VLookup,
+    #[token("HLOOKUP")]
+    HLookup,
+    #[token("INDEX")]
+    Index,
+    #[token("MATCH")]
+    Match,
+    #[token("KEY")]
+    Key,
+    #[regex("(SUM|AVG|MIN|MAX|STDEV|COUNT)BY", |lex| group_function(lex.slice()))]
+    GroupBy(RangeFunction), //SUMBY and the like, an aggregate over the rows of a range matching keys
+
+    #[regex("[A-Z][A-Z_]*", |lex| lex.slice().to_string())]
+    FuncName(String), //Any other upper case name, looked up in the function registry
+
+    #[regex("[a-z][a-z0-9_]*", |lex| lex.slice().to_string())]
+    Name(String), //A lower case name, defined with `name=...` and kept in the `SheetStorage`
+
+    #[token("==")]
+    OperatorEq,
+    #[token("!=")]
+    OperatorNotEq,
+    
+    #[token(">=")]
+    OperatorGtEq,
+    #[token(">")]
+    OperatorGt,
+    #[token("<=")]
+    OperatorLtEq,
+    #[token("<")]
+    OperatorLt,
+    #[token("&&")] //4
+    OperatorAnd,
+    #[token("||")] //4
+    OperatorOr,
+
+
+    #[token("(")]
+    LParen,
+    #[token(")")]
+    RParen,
+    #[token("=")]
+    Assign,
+  
+    #[token("**")]
+    OperatorPow,   //1
+    #[token("//")]
+    OperatorFloorDiv,  //2
+    #[token("%")]
+    OperatorMod,  //2
+
+    #[token("+")]
+    OperatorAdd, //3
+    #[token("-")]
+    OperatorSub, //3
+    #[token("*")]
+    OperatorMul, //2
+    #[token("/")]
+    OperatorDiv, //2
+
+    #[token("^")]
+    OperatorConcat,
+    
+
+    #[token(":")]
+    Colon,
+    #[token(",")]
+    Comma,
+}
+
+//Below is copy paste from lalrpop tutorial:
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      write!(f, "{:?}", self)
+    }
+  }
+
+impl fmt::Display for LexicalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,265 @@
+use crate::ast::{col_to_name, Addr, Command, ParentType};
+use crate::cell_operations::{format_options_to_string, parse_format_options, CellError, CellFunc, ErrorKind, FormatOption, FormatRule, ValueType};
+use crate::evaluate_operations::{evaluate, range_sheets, update_children};
+use crate::sheet_operations::{add_format_rule, define_name};
+use crate::tokensexpr::Token;
+use crate::{parse_assign, parse_condition, SheetStorage};
+use logos::Logos;
+use std::io::{Write, BufWriter, BufRead, BufReader};
+use std::rc::Rc;
+use std::fs::File;
+
+/// First line of every workbook file.
+const WORKBOOK_HEADER: &str = "SPREADSHEET_WORKBOOK";
+/// Version of the format written by `save_workbook`. Bump this when the layout of the file changes.
+pub const WORKBOOK_VERSION: u32 = 3;
+
+/// Escapes the backslashes and line breaks of a formula, which may hold them inside text, so that it fits on
+/// one line of the file.
+fn escape_formula(formula: &str) -> String
+{
+    formula.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+/// Reverses `escape_formula`. A backslash before any other character is kept as it is.
+fn unescape_formula(text: &str) -> String
+{
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next()
+    {
+        match (c, chars.clone().next()) {
+            ('\\', Some('\\')) => { out.push('\\'); chars.next(); }
+            ('\\', Some('n')) => { out.push('\n'); chars.next(); }
+            ('\\', Some('r')) => { out.push('\r'); chars.next(); }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Saves all the active sheets of a `SheetStorage` to a workbook file.
+///
+/// The file is plain text. The first line is the header and version, followed by one `SHEET` line per sheet
+/// (columns, rows and name, in tab order) followed by one `CELL` line per cell of the sheet that has a formula,
+/// one `FORMAT` line per cell that is not shown with the default format and one `RULE` line per conditional format
+/// rule (range, condition and options), then one `NAME` line per defined name.
+/// Formulas and conditions are written as text, so references to other sheets are stored by sheet name, with the
+/// backslashes and line breaks of their text escaped.
+///
+/// **Arguments:**
+/// - `sheetstore`: The store to save.
+/// - `path`: The path of the output file.
+///
+/// **Returns:**
+/// - `Ok(())` if the save is successful.
+/// - `Err(String)` if an error occurs.
+pub fn save_workbook(sheetstore: &SheetStorage, path: &str) -> Result<(), String>
+{
+    let file = File::create(path).map_err(|e| format!("Error in creating workbook file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let mut out = format!("{}\t{}\n", WORKBOOK_HEADER, WORKBOOK_VERSION);
+    for (name, num) in &sheetstore.map
+    {
+        let sheet = sheetstore.data[*num].borrow();
+        out.push_str(&format!("SHEET\t{}\t{}\t{}\n", sheet.columns, sheet.rows, name));
+        let mut formats = String::new();
+        for column in &sheet.data
+        {
+            for cell_rc in column.borrow().iter()
+            {
+                let cell = cell_rc.borrow();
+                if let Some(func) = &cell.cell_func
+                {
+                    let formula = escape_formula(&func.expression.to_formula(*num as u32, sheetstore));
+                    out.push_str(&format!("CELL\t{}{}\t{}\n", col_to_name(cell.addr.col), cell.addr.row + 1, formula));
+                }
+                if !cell.format.is_default()
+                {
+                    formats.push_str(&format!("FORMAT\t{}{}\t{}\n", col_to_name(cell.addr.col), cell.addr.row + 1, cell.format));
+                }
+            }
+        }
+        out.push_str(&formats);
+        for rule in &sheet.rules
+        {
+            out.push_str(&format!("RULE\t{}{}:{}{}\t{}\t{}\n", col_to_name(rule.start.col), rule.start.row + 1, col_to_name(rule.end.col),
+                rule.end.row + 1, escape_formula(&rule.cond.to_formula(*num as u32, sheetstore)), format_options_to_string(&rule.options)));
+        }
+    }
+    for (name, def) in &sheetstore.names
+    {
+        out.push_str(&format!("NAME\t{}\t{}\n", name, escape_formula(&def.to_formula(sheetstore))));
+    }
+    writer.write_all(out.as_bytes()).map_err(|e| format!("Error in writing workbook file: {}", e))?;
+    writer.flush().map_err(|e| format!("Error in writing workbook file: {}", e))
+}
+
+/// Loads a workbook file written by `save_workbook` into a new `SheetStorage`.
+///
+/// All sheets are created first, then the names are defined and every formula is parsed again and evaluated,
+/// which rebuilds the dependency graph. Cells whose formula evaluates to an error are kept as error cells.
+/// The formats and conditional format rules of the cells are put back last.
+/// Files of an older version are read as well.
+///
+/// **Arguments:**
+/// - `path`: The path of the workbook file.
+///
+/// **Returns:**
+/// - `Ok(SheetStorage)` containing the loaded sheets.
+/// - `Err(String)` if the file cannot be read or is not a valid workbook.
+pub fn load_workbook(path: &str) -> Result<SheetStorage, String>
+{
+    let file = File::open(path).map_err(|e| format!("Error in opening workbook file: {}", e))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header = match lines.next() {
+        Some(Ok(x)) => x,
+        _ => return Err("Workbook file is empty".to_string())
+    };
+    match header.split_once('\t') {
+        Some((WORKBOOK_HEADER, version)) => {
+            if !version.trim().parse::<u32>().is_ok_and(|v| (1..=WORKBOOK_VERSION).contains(&v)) {
+                return Err(format!("Unsupported workbook version \"{}\"", version.trim()));
+            }
+        }
+        _ => return Err("Not a workbook file".to_string())
+    }
+
+    let mut sheetstore = SheetStorage::new();
+    let mut cells: Vec<(u32, String)> = vec![];  // (sheet number, "A1=formula")
+    let mut names: Vec<String> = vec![];  // "name=definition"
+    let mut formats: Vec<(Addr, Vec<FormatOption>)> = vec![];
+    let mut rules: Vec<(u32, usize, Vec<String>)> = vec![];  // (sheet number, line number, [range, condition, options])
+    let mut curr_sheet: Option<u32> = None;
+    for (line_no, line_result) in lines.enumerate()
+    {
+        let line = line_result.map_err(|e| format!("Error in reading workbook file: {}", e))?;
+        let line_no = line_no + 2;
+        if line.is_empty() {
+            continue;
+        }
+        let (kind, rest) = line.split_once('\t').ok_or(format!("Invalid line {} in workbook", line_no))?;
+        match kind {
+            "SHEET" => {
+                let fields: Vec<&str> = rest.splitn(3, '\t').collect();
+                if fields.len() != 3 {
+                    return Err(format!("Invalid sheet at line {} in workbook", line_no));
+                }
+                let cols = fields[0].parse::<usize>().map_err(|_| format!("Invalid column count at line {} in workbook", line_no))?;
+                let rows = fields[1].parse::<usize>().map_err(|_| format!("Invalid row count at line {} in workbook", line_no))?;
+                let num = sheetstore.new_sheet(fields[2], cols, rows).ok_or(format!("Sheet name \"{}\" repeated in workbook", fields[2]))?;
+                curr_sheet = Some(num as u32);
+            }
+            "CELL" => {
+                let sheet_num = curr_sheet.ok_or(format!("Cell before any sheet at line {} in workbook", line_no))?;
+                let (addr, formula) = rest.split_once('\t').ok_or(format!("Invalid cell at line {} in workbook", line_no))?;
+                cells.push((sheet_num, format!("{}={}", addr, unescape_formula(formula))));
+            }
+            "FORMAT" => {
+                let sheet_num = curr_sheet.ok_or(format!("Format before any sheet at line {} in workbook", line_no))?;
+                let invalid = || format!("Invalid format at line {} in workbook", line_no);
+                let (addr, spec) = rest.split_once('\t').ok_or_else(invalid)?;
+                let addr = local_cell(&sheetstore, sheet_num, addr).ok_or_else(invalid)?;
+                formats.push((addr, parse_format_options(spec).map_err(|e| format!("{} at line {} in workbook", e, line_no))?));
+            }
+            "RULE" => {
+                let sheet_num = curr_sheet.ok_or(format!("Rule before any sheet at line {} in workbook", line_no))?;
+                let fields: Vec<String> = rest.splitn(3, '\t').map(str::to_string).collect();
+                if fields.len() != 3 {
+                    return Err(format!("Invalid rule at line {} in workbook", line_no));
+                }
+                rules.push((sheet_num, line_no, fields));
+            }
+            "NAME" => {
+                let (name, def) = rest.split_once('\t').ok_or(format!("Invalid name at line {} in workbook", line_no))?;
+                names.push(format!("{}={}", name, unescape_formula(def)));
+            }
+            _ => return Err(format!("Invalid line {} in workbook", line_no))
+        }
+    }
+    if sheetstore.map.is_empty() {
+        return Err("Workbook has no sheets".to_string());
+    }
+
+    for inp in names
+    {
+        let Ok((Command::DefineName(name, def), _)) = parse_assign(&inp, 0, &sheetstore) else {
+            return Err(format!("Invalid name \"{}\" in workbook", inp));
+        };
+        define_name(&mut sheetstore, &name, def).map_err(|e| format!("Invalid name \"{}\" in workbook: {}", inp, e))?;
+    }
+
+    for (sheet_num, inp) in cells
+    {
+        let (cmd, dep_vec) = parse_assign(&inp, sheet_num, &sheetstore).map_err(|e| format!("Invalid formula \"{}\" in workbook: {:?}", inp, e))?;
+        let Command::AssignCmd(addr, expr) = cmd else {
+            return Err(format!("Invalid formula \"{}\" in workbook", inp));
+        };
+        let mut to_create = vec![(addr.clone(), addr.clone())];
+        for dep in dep_vec {
+            match dep {
+                ParentType::Single(a) => to_create.push((a.clone(), a)),
+                ParentType::Range(a1, a2) => to_create.push((a1, a2)),
+            }
+        }
+        for (a1, a2) in to_create {
+            if a1.sheet > a2.sheet || a1.row > a2.row || a1.col > a2.col {
+                return Err(format!("Address out of range in \"{}\" in workbook", inp));
+            }
+            for sheet_num in range_sheets(&sheetstore.data, &a1, &a2) {
+                let sheet = sheetstore.data[sheet_num as usize].borrow();
+                if a2.row >= sheet.rows || a2.col >= sheet.columns {
+                    return Err(format!("Address out of range in \"{}\" in workbook", inp));
+                }
+                sheet.extend_range(&a1, &a2);
+            }
+        }
+        let old_func = {
+            let sheet = sheetstore.data[addr.sheet as usize].borrow();
+            let cell_rc = Rc::clone(&sheet.data[addr.col as usize].borrow()[addr.row as usize]);
+            let mut cell = cell_rc.borrow_mut();
+            let old_func = cell.cell_func.clone();
+            cell.cell_func = Some(CellFunc::new(*expr));
+            cell.formula = inp[inp.find('=').unwrap_or(0) + 1..].to_string();
+            old_func
+        };
+        // Errors like division by zero are stored in the cell itself, so they do not stop the load.
+        // A formula that closes a cycle is not kept, and its cell shows a cycle error instead.
+        if let Err(e) = evaluate(&mut sheetstore.data, &addr, &old_func) && e.contains("Cyclic dependency")
+        {
+            let sheet = sheetstore.data[addr.sheet as usize].borrow();
+            let cell_rc = Rc::clone(&sheet.data[addr.col as usize].borrow()[addr.row as usize]);
+            cell_rc.borrow_mut().value = ValueType::Error(CellError::new(ErrorKind::Cycle, e));
+            drop(sheet);
+            let _ = update_children(&sheetstore.data, &addr);
+        }
+    }
+
+    for (addr, options) in formats
+    {
+        sheetstore.data[addr.sheet as usize].borrow().format_range(&addr, &addr, Some(&options));
+    }
+    for (sheet_num, line_no, fields) in rules
+    {
+        let invalid = || format!("Invalid rule at line {} in workbook", line_no);
+        let (start, end) = fields[0].split_once(':').ok_or_else(invalid)?;
+        let start = local_cell(&sheetstore, sheet_num, start).ok_or_else(invalid)?;
+        let end = local_cell(&sheetstore, sheet_num, end).filter(|end| start.row <= end.row && start.col <= end.col).ok_or_else(invalid)?;
+        let (cond, _) = parse_condition(&unescape_formula(&fields[1]), sheet_num, &sheetstore).map_err(|_| invalid())?;
+        let options = parse_format_options(&fields[2]).map_err(|e| format!("{} at line {} in workbook", e, line_no))?;
+        add_format_rule(&sheetstore.data, FormatRule{start, end, cond: *cond, options});
+    }
+    Ok(sheetstore)
+}
+
+/// Reads a cell like `B3` of the sheet `sheet_num`, or returns `None` if it is not a cell inside that sheet.
+fn local_cell(sheetstore: &SheetStorage, sheet_num: u32, text: &str) -> Option<Addr>
+{
+    let mut lexer = Token::lexer(text);
+    let (Some(Ok(Token::LocalCell((col, row, ..)))), None) = (lexer.next(), lexer.next()) else {
+        return None;
+    };
+    let sheet = sheetstore.data[sheet_num as usize].borrow();
+    (row < sheet.rows && col < sheet.columns).then(|| Addr::new(sheet_num, row, col))
+}
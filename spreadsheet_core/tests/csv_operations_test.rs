@@ -0,0 +1,76 @@
+use spreadsheet_core::cell_operations::ValueType;
+use spreadsheet_core::csv_operations::{import_csv, parse_csv_options, CsvOptions};
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_import_reads_decimals_when_asked() {
+        let path = std::env::temp_dir().join("spreadsheet_core_decimal_import.csv");
+        let path = path.to_str().unwrap();
+        fs::write(path, "12.50,3\n0.1,x\n").unwrap();
+        let floats = import_csv(path, 0, &CsvOptions::default()).unwrap();
+        let decimals = import_csv(path, 0, &parse_csv_options("decimal").unwrap()).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert!(matches!(floats.val_at(0, 0), ValueType::FloatValue(x) if x == 12.5));
+        assert!(matches!(decimals.val_at(0, 0), ValueType::DecimalValue(_)));
+        assert_eq!(decimals.val_at(0, 0).to_string(), "12.50");
+        assert_eq!(decimals.data[0].borrow()[0].borrow().formula, "12.50d");
+        assert!(matches!(decimals.val_at(1, 0), ValueType::IntegerValue(3)));
+        assert!(matches!(decimals.val_at(1, 1), ValueType::String(_)));
+    }
+
+    #[test]
+    fn test_import_follows_the_options() {
+        let path = std::env::temp_dir().join("spreadsheet_core_options_import.csv");
+        let path = path.to_str().unwrap();
+        fs::write(path, "Report 2024\nregion\tyear\tamount\n\"east\"\t2024\t1.50\nwest\t2023\t007\n").unwrap();
+        let labelled = import_csv(path, 0, &parse_csv_options("delimiter=tab, skip=1, header, B:text, C:decimal").unwrap());
+        let plain = import_csv(path, 0, &parse_csv_options("delimiter=tab, skip=1, header=skip").unwrap());
+        let wrong_type = import_csv(path, 0, &parse_csv_options("delimiter=tab, skip=1, header=skip, B:date").unwrap());
+        let ragged = import_csv(path, 0, &parse_csv_options("delimiter=tab").unwrap());
+        let padded = import_csv(path, 0, &parse_csv_options("delimiter=tab, ragged").unwrap());
+        fs::write(path, b"caf\xe9;1\ntrue;\"say \"\"hi\"\"\"\n").unwrap();
+        let latin1 = import_csv(path, 0, &parse_csv_options("delimiter=semicolon, encoding=latin1").unwrap());
+        let not_utf8 = import_csv(path, 0, &parse_csv_options("delimiter=semicolon").unwrap());
+        fs::write(path, "").unwrap();
+        let empty = import_csv(path, 0, &CsvOptions::default());
+        fs::remove_file(path).unwrap();
+
+        // The header stays text, and forced types apply to the rows under it only.
+        let labelled = labelled.unwrap();
+        assert_eq!((labelled.rows, labelled.columns), (3, 3));
+        assert!(matches!(labelled.val_at(1, 0), ValueType::String(s) if s == "year"));
+        assert!(matches!(labelled.val_at(1, 1), ValueType::String(s) if s == "2024"));
+        assert_eq!(labelled.data[1].borrow()[1].borrow().formula, "\"2024\"");
+        assert!(matches!(labelled.val_at(2, 2), ValueType::DecimalValue(_)));
+        assert_eq!(labelled.val_at(2, 2).to_string(), "7");
+        let plain = plain.unwrap();
+        assert_eq!(plain.rows, 2);
+        assert!(matches!(plain.val_at(0, 0), ValueType::String(s) if s == "east"));
+        assert!(matches!(plain.val_at(1, 0), ValueType::IntegerValue(2024)));
+        assert!(matches!(plain.val_at(2, 1), ValueType::IntegerValue(7)));
+
+        assert_eq!(wrong_type.err().unwrap(), "Value \"2024\" in column B of row 3 of the csv is not of the type of its column");
+        assert!(ragged.is_err());
+        let padded = padded.unwrap();
+        assert_eq!((padded.rows, padded.columns), (4, 3));
+        assert!(matches!(padded.val_at(0, 0), ValueType::String(s) if s == "Report 2024"));
+        // Stored formulas are literals the formula parser reads back.
+        let latin1 = latin1.unwrap();
+        assert!(matches!(latin1.val_at(0, 0), ValueType::String(s) if s == "café"));
+        assert_eq!(latin1.data[0].borrow()[0].borrow().formula, "\"café\"");
+        assert_eq!(latin1.data[0].borrow()[1].borrow().formula, "True");
+        assert_eq!(latin1.data[1].borrow()[1].borrow().formula, "'say \"hi\"'");
+        assert!(not_utf8.is_err());
+        assert!(empty.is_err());
+
+        assert!(parse_csv_options("delimiter=tab, quote=none, A:int, encoding=utf8").is_ok());
+        assert!(parse_csv_options("delimiter=tabs").is_err());
+        assert!(parse_csv_options("B:money").is_err());
+        assert!(parse_csv_options("quote=;, delimiter=semicolon").is_err());
+        assert!(parse_csv_options("headers").is_err());
+    }
+}
@@ -0,0 +1,539 @@
+use spreadsheet_core::SheetStorage;
+use spreadsheet_core::{parse_assign, parse_condition};
+use spreadsheet_core::ast::{Addr, Command, Expr, InfixFunction, ParentType};
+use spreadsheet_core::cell_operations::{parse_format_options, CellColor, CellFunc, ErrorKind, FormatRule, RowFilter, ValueType};
+use spreadsheet_core::evaluate_operations::{evaluate, evaluated_cells, filtered_rows, find_function, reset_evaluated_cells, update_children};
+use spreadsheet_core::tokensexpr::LexicalError;
+use lalrpop_util::ParseError;
+use spreadsheet_core::sheet_operations::{add_format_rule, clear_format_rules, invalidate_children, shift_sheet, SheetAxis};
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    // Helper function that mirrors what the frontend does for an assignment: parse, create missing cells, evaluate.
+    fn assign(store: &mut SheetStorage, curr_sheet: u32, inp: &str) -> Result<(), String> {
+        let (cmd, deps) = parse_assign(inp, curr_sheet, store).map_err(|e| format!("{:?}", e))?;
+        let Command::AssignCmd(addr, expr) = cmd else { panic!("Not an assignment") };
+        let mut needed = vec![(addr.clone(), addr.clone())];
+        for dep in deps {
+            match dep {
+                ParentType::Single(a) => needed.push((a.clone(), a)),
+                ParentType::Range(a1, a2) => needed.push((a1, a2)),
+            }
+        }
+        for (a1, a2) in needed {
+            store.data[a1.sheet as usize].borrow().extend_range(&a1, &a2);
+        }
+        let old_func = {
+            let sheet = store.data[addr.sheet as usize].borrow();
+            let cell_rc = Rc::clone(&sheet.data[addr.col as usize].borrow()[addr.row as usize]);
+            let mut cell = cell_rc.borrow_mut();
+            let old_func = cell.cell_func.clone();
+            cell.cell_func = Some(CellFunc::new(*expr));
+            old_func
+        };
+        evaluate(&mut store.data, &addr, &old_func)
+    }
+
+    fn value_at(store: &SheetStorage, sheet: usize, col: usize, row: usize) -> ValueType {
+        store.data[sheet].borrow().val_at(col, row)
+    }
+
+    #[test]
+    fn test_dependents_recompute() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assign(&mut store, 0, "A1=2").unwrap();
+        assign(&mut store, 0, "B1=A1*3").unwrap();
+        assign(&mut store, 0, "C1=SUM(A1:B1)").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::IntegerValue(6)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(8)));
+
+        assign(&mut store, 0, "A1=5").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::IntegerValue(15)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(20)));
+    }
+
+    #[test]
+    fn test_cross_sheet_reference() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        store.new_sheet("data", 5, 5);
+        assign(&mut store, 1, "B2=4.5").unwrap();
+        assign(&mut store, 0, "A1=data.B2+1").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::FloatValue(x) if x == 5.5));
+    }
+
+    #[test]
+    fn test_unknown_sheet_is_parse_error() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assert!(parse_assign("A1=nosheet.A1", 0, &store).is_err());
+    }
+
+    #[test]
+    fn test_cycle_is_rejected() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assign(&mut store, 0, "A1=B1+1").unwrap();
+        let res = assign(&mut store, 0, "B1=A1+1");
+        assert!(res.is_err_and(|e| e.contains("Cyclic dependency")));
+    }
+
+    fn error_at(store: &SheetStorage, sheet: usize, col: usize, row: usize) -> Option<ErrorKind> {
+        match value_at(store, sheet, col, row) {
+            ValueType::Error(e) => Some(e.kind),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_error_values_propagate() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assert!(assign(&mut store, 0, "A1=1/0").is_err_and(|e| e.contains("#DIV/0!")));
+        assign(&mut store, 0, "B1=A1+1").unwrap_err();
+        assign(&mut store, 0, "C1=SUM(A1:B1)").unwrap_err();
+        assign(&mut store, 0, "D1=\"x\"*2").unwrap_err();
+        assert_eq!(error_at(&store, 0, 0, 0), Some(ErrorKind::DivByZero));
+        assert_eq!(error_at(&store, 0, 1, 0), Some(ErrorKind::DivByZero));
+        assert_eq!(error_at(&store, 0, 2, 0), Some(ErrorKind::DivByZero));
+        assert_eq!(error_at(&store, 0, 3, 0), Some(ErrorKind::TypeMismatch));
+        assert_eq!(value_at(&store, 0, 3, 0).to_string(), "#VALUE!");
+
+        // Fixing the source clears the error in every dependent.
+        assign(&mut store, 0, "A1=4").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::IntegerValue(5)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(9)));
+    }
+
+    #[test]
+    fn test_iserror_and_iferror() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assign(&mut store, 0, "A1=0").unwrap();
+        assign(&mut store, 0, "B1=ISERROR(10/A1)").unwrap();
+        assign(&mut store, 0, "C1=IFERROR(10/A1, -1)").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::BoolValue(true)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(-1)));
+
+        assign(&mut store, 0, "A1=5").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::BoolValue(false)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::FloatValue(x) if x == 2.0));
+    }
+
+    #[test]
+    fn test_removed_sheet_is_error() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        store.new_sheet("data", 5, 5);
+        assign(&mut store, 1, "A1=3").unwrap();
+        assign(&mut store, 0, "A1=data.A1*2").unwrap();
+        assign(&mut store, 0, "B1=A1+1").unwrap();
+
+        let removed = Rc::clone(&store.data[1]);
+        store.remove_sheet("data").unwrap();
+        invalidate_children(&mut store.data, &removed.borrow());
+        assert_eq!(error_at(&store, 0, 0, 0), Some(ErrorKind::DeletedSheet));
+        assert_eq!(error_at(&store, 0, 1, 0), Some(ErrorKind::DeletedSheet));
+
+        // The old reference does not break a later assignment to the same cell.
+        assign(&mut store, 0, "A1=7").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::IntegerValue(8)));
+    }
+
+    #[test]
+    fn test_lookup_functions() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 8, 8);
+        assign(&mut store, 0, "A1=\"apple\"").unwrap();
+        assign(&mut store, 0, "A2=\"pear\"").unwrap();
+        assign(&mut store, 0, "A3=7").unwrap();
+        assign(&mut store, 0, "B1=1.5").unwrap();
+        assign(&mut store, 0, "B2=2.5").unwrap();
+        assign(&mut store, 0, "B3=\"seven\"").unwrap();
+
+        assign(&mut store, 0, "D1=VLOOKUP(\"pear\", A1:B3, 2)").unwrap();
+        assign(&mut store, 0, "D2=VLOOKUP(7.0, A1:B3, 2)").unwrap();
+        assign(&mut store, 0, "D3=HLOOKUP(7, A3:B3, 1)").unwrap();
+        assign(&mut store, 0, "D4=INDEX(A1:B3, 2, 1)").unwrap();
+        assign(&mut store, 0, "D5=MATCH(\"pear\", A1:A3)").unwrap();
+        assert!(matches!(value_at(&store, 0, 3, 0), ValueType::FloatValue(x) if x == 2.5));
+        assert!(matches!(value_at(&store, 0, 3, 1), ValueType::String(s) if s == "seven"));
+        assert!(matches!(value_at(&store, 0, 3, 2), ValueType::IntegerValue(7)));
+        assert!(matches!(value_at(&store, 0, 3, 3), ValueType::String(s) if s == "pear"));
+        assert!(matches!(value_at(&store, 0, 3, 4), ValueType::IntegerValue(2)));
+
+        // Changing the table recomputes the lookups.
+        assign(&mut store, 0, "B2=9").unwrap();
+        assign(&mut store, 0, "A2=\"plum\"").unwrap_err();
+        assert_eq!(error_at(&store, 0, 3, 0), Some(ErrorKind::NotFound));
+        assert_eq!(error_at(&store, 0, 3, 4), Some(ErrorKind::NotFound));
+        assign(&mut store, 0, "A2=\"pear\"").unwrap();
+        assert!(matches!(value_at(&store, 0, 3, 0), ValueType::IntegerValue(9)));
+
+        assign(&mut store, 0, "E1=INDEX(A1:B3, 4, 1)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 4, 0), Some(ErrorKind::BadRef));
+        assign(&mut store, 0, "E3=INDEX(A1:A2, 4294967297, 1)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 4, 2), Some(ErrorKind::BadRef));
+        assign(&mut store, 0, "E2=MATCH(1, A1:B3)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 4, 1), Some(ErrorKind::TypeMismatch));
+
+        // KEY lists the distinct values of a column in sorted order, numbers first.
+        assign(&mut store, 0, "F1=KEY(A1:B3, 1, 1)").unwrap();
+        assign(&mut store, 0, "F2=KEY(A1:B3, 1, 3)").unwrap();
+        assert!(matches!(value_at(&store, 0, 5, 0), ValueType::IntegerValue(7)));
+        assert!(matches!(value_at(&store, 0, 5, 1), ValueType::String(s) if s == "pear"));
+        assign(&mut store, 0, "F3=KEY(A1:B3, 1, 4)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 5, 2), Some(ErrorKind::NotFound));
+        assign(&mut store, 0, "F4=KEY(A1:B3, 1, 0)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 5, 3), Some(ErrorKind::TypeMismatch));
+
+        // Text differing in case gives two keys, however its cells are mixed.
+        for inp in ["G1=\"West\"", "G2=\"west\"", "G3=\"West\"", "G4=\"east\""] {
+            assign(&mut store, 0, inp).unwrap();
+        }
+        assign(&mut store, 0, "H1=KEY(G1:G4, 1, 2)").unwrap();
+        assign(&mut store, 0, "H2=KEY(G1:G4, 1, 3)").unwrap();
+        assert!(matches!(value_at(&store, 0, 7, 0), ValueType::String(s) if s == "West"));
+        assert!(matches!(value_at(&store, 0, 7, 1), ValueType::String(s) if s == "west"));
+        assign(&mut store, 0, "H3=KEY(G1:G4, 1, 4)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 7, 2), Some(ErrorKind::NotFound));
+    }
+
+    #[test]
+    fn test_registry_function_calls() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assign(&mut store, 0, "A1=-4").unwrap();
+        assign(&mut store, 0, "B1=ABS(A1)+SQRT(16)").unwrap();
+        assign(&mut store, 0, "C1=CONCAT(\"x\", A1, True)").unwrap();
+        assign(&mut store, 0, "D1=LEN(UPPER(TRIM(\" ab \")))").unwrap();
+        assign(&mut store, 0, "E1=CHOOSE(2, \"a\", SUM(A1:A1), 3)").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::FloatValue(x) if x == 8.0));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::String(s) if s == "x-4true"));
+        assert!(matches!(value_at(&store, 0, 3, 0), ValueType::IntegerValue(2)));
+        assert!(matches!(value_at(&store, 0, 4, 0), ValueType::IntegerValue(-4)));
+
+        // Arguments are dependencies like any other expression.
+        assign(&mut store, 0, "A1=9").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::FloatValue(x) if x == 13.0));
+
+        // Types are checked when evaluating, names and arity when parsing.
+        assign(&mut store, 0, "A2=LEN(5)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 0, 1), Some(ErrorKind::TypeMismatch));
+        assign(&mut store, 0, "A3=SQRT(0-1)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 0, 2), Some(ErrorKind::Num));
+        assert!(matches!(parse_assign("A4=NOSUCH(1)", 0, &store), Err(ParseError::User{error: LexicalError::FunctionErr(_)})));
+        assert!(matches!(parse_assign("A4=ABS(1, 2)", 0, &store), Err(ParseError::User{error: LexicalError::FunctionErr(_)})));
+        assert!(matches!(parse_assign("A4=CONCAT()", 0, &store), Err(ParseError::User{error: LexicalError::FunctionErr(_)})));
+        assert!(find_function("ABS").is_some());
+    }
+
+    #[test]
+    fn test_references_past_999_rows() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 30, 2_000_000);
+        assign(&mut store, 0, "AB1500000=7").unwrap();
+        assign(&mut store, 0, "A1=AB1500000*2").unwrap();
+        assign(&mut store, 0, "B1=SUM(AB1499999:AB1500000)").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::IntegerValue(14)));
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::IntegerValue(7)));
+
+        // Any address in the u32 range is a valid token, even if it is outside this sheet.
+        assert!(parse_assign("A1=XFD4294967295", 0, &store).is_ok());
+        assert!(matches!(parse_assign("A1=A4294967296", 0, &store), Err(ParseError::User{error: LexicalError::AddressOutOfRange(_)})));
+        assert!(matches!(parse_assign("A1=ZZZZZZZ1+1", 0, &store), Err(ParseError::User{error: LexicalError::AddressOutOfRange(_)})));
+    }
+
+    #[test]
+    fn test_recalculation_stops_at_unchanged_values() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 200);
+        assign(&mut store, 0, "A1=1").unwrap();
+        for row in 2..=200 {
+            assign(&mut store, 0, &format!("A{}=A{}+1", row, row - 1)).unwrap();
+        }
+        assign(&mut store, 0, "B1=A1>5").unwrap();
+        assign(&mut store, 0, "C1=IFELSE(B1, 100, 0)").unwrap();
+
+        reset_evaluated_cells();
+        assign(&mut store, 0, "A1=1").unwrap();
+        assert_eq!(evaluated_cells(), 1);
+
+        // The whole chain changes, but B1 stays false so C1 is not evaluated again.
+        reset_evaluated_cells();
+        assign(&mut store, 0, "A1=2").unwrap();
+        assert_eq!(evaluated_cells(), 201);
+        assert!(matches!(value_at(&store, 0, 0, 199), ValueType::IntegerValue(201)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(0)));
+
+        reset_evaluated_cells();
+        assign(&mut store, 0, "A1=10").unwrap();
+        assert_eq!(evaluated_cells(), 202);
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(100)));
+    }
+
+    #[test]
+    fn test_million_cell_chain() {
+        const LEN: u32 = 1_000_000;
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 1, LEN as usize);
+        {
+            // A1=1, A2=A1+1, ... built directly, since parsing a million formulas would only slow the test down.
+            let sheet = store.data[0].borrow();
+            sheet.extend_range(&Addr::new(0, 0, 0), &Addr::new(0, LEN - 1, 0));
+            let column = sheet.data[0].borrow();
+            column[0].borrow_mut().cell_func = Some(CellFunc::new(Expr::Integer(1)));
+            for row in 1..LEN {
+                let parent = Addr::new(0, row - 1, 0);
+                let expr = Expr::InfixOp(Box::new(Expr::Cell(parent)), InfixFunction::Add, Box::new(Expr::Integer(1)));
+                column[row as usize].borrow_mut().cell_func = Some(CellFunc::new(expr));
+                column[row as usize - 1].borrow_mut().children.insert(Addr::new(0, row, 0));
+            }
+        }
+        update_children(&store.data, &Addr::new(0, 0, 0)).unwrap();
+        assert!(matches!(value_at(&store, 0, 0, LEN as usize - 1), ValueType::IntegerValue(n) if n == LEN as i64));
+
+        // Closing the chain into a loop is found without overflowing the stack, and the old formula is kept.
+        let err = assign(&mut store, 0, &format!("A1=A{}+1", LEN)).unwrap_err();
+        assert!(err.contains("Cyclic dependency"));
+        assign(&mut store, 0, "A1=5").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, LEN as usize - 1), ValueType::IntegerValue(n) if n == LEN as i64 + 4));
+    }
+
+    #[test]
+    fn test_integers_are_64_bit_and_checked() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assign(&mut store, 0, "A1=3000000000*3").unwrap();
+        assign(&mut store, 0, "A2=9223372036854775807").unwrap();
+        assign(&mut store, 0, "A3=SUM(A1:A1)-1").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::IntegerValue(9000000000)));
+        assert!(matches!(value_at(&store, 0, 0, 2), ValueType::IntegerValue(8999999999)));
+
+        // Overflow is an error value rather than a wrapped or panicking result.
+        assign(&mut store, 0, "B1=A2+1").unwrap_err();
+        assign(&mut store, 0, "B2=A2*2").unwrap_err();
+        assign(&mut store, 0, "B3=SUM(A1:A2)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 1, 0), Some(ErrorKind::Num));
+        assert_eq!(error_at(&store, 0, 1, 1), Some(ErrorKind::Num));
+        assert_eq!(error_at(&store, 0, 1, 2), Some(ErrorKind::Num));
+        assign(&mut store, 0, "B4=(0-A2-1)%(0-1)").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 3), ValueType::IntegerValue(0)));
+
+        // Powers that do not fit an integer become floats.
+        assign(&mut store, 0, "C1=2**62").unwrap();
+        assign(&mut store, 0, "C2=2**64").unwrap();
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::IntegerValue(4611686018427387904)));
+        assert!(matches!(value_at(&store, 0, 2, 1), ValueType::FloatValue(x) if x == 2f64.powi(64)));
+
+        assert!(parse_assign("D1=99999999999999999999", 0, &store).is_err());
+    }
+
+    #[test]
+    fn test_decimals_are_exact() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 10);
+        assign(&mut store, 0, "A1=0.1d+0.2d").unwrap();
+        assign(&mut store, 0, "A2=A1==0.3d").unwrap();
+        assign(&mut store, 0, "A3=0.1+0.2==0.3").unwrap();
+        assign(&mut store, 0, "A4=1.50d==1.5d").unwrap();
+        assert_eq!(value_at(&store, 0, 0, 0).to_string(), "0.3");
+        assert!(matches!(value_at(&store, 0, 0, 1), ValueType::BoolValue(true)));
+        assert!(matches!(value_at(&store, 0, 0, 2), ValueType::BoolValue(false)));
+        assert!(matches!(value_at(&store, 0, 0, 3), ValueType::BoolValue(true)));
+
+        // Sums and averages of decimals stay decimals and keep their digits after the point.
+        assign(&mut store, 0, "B1=19.99d").unwrap();
+        assign(&mut store, 0, "B2=5.01d").unwrap();
+        assign(&mut store, 0, "B3=SUM(B1:B2)").unwrap();
+        assign(&mut store, 0, "B4=AVG(B1:B2)").unwrap();
+        assign(&mut store, 0, "B5=B1*3-1").unwrap();
+        assign(&mut store, 0, "B6=MAX(B1:B2)").unwrap();
+        assign(&mut store, 0, "B7=B2<5").unwrap();
+        assert_eq!(value_at(&store, 0, 1, 2).to_string(), "25.00");
+        assert_eq!(value_at(&store, 0, 1, 3).to_string(), "12.50");
+        assert_eq!(value_at(&store, 0, 1, 4).to_string(), "58.97");
+        assert_eq!(value_at(&store, 0, 1, 5).to_string(), "19.99");
+        assert!(matches!(value_at(&store, 0, 1, 6), ValueType::BoolValue(false)));
+
+        // ROUND rounds half away from zero. Division keeps the digits it needs.
+        assign(&mut store, 0, "C1=ROUND(2.345d, 2)").unwrap();
+        assign(&mut store, 0, "C2=ROUND(-2.345d, 2)").unwrap();
+        assign(&mut store, 0, "C3=ROUND(10d/3, 2)").unwrap();
+        assign(&mut store, 0, "C4=1d/4").unwrap();
+        assign(&mut store, 0, "C5=1.5d**2").unwrap();
+        assert_eq!(value_at(&store, 0, 2, 0).to_string(), "2.35");
+        assert_eq!(value_at(&store, 0, 2, 1).to_string(), "-2.35");
+        assert_eq!(value_at(&store, 0, 2, 2).to_string(), "3.33");
+        assert_eq!(value_at(&store, 0, 2, 3).to_string(), "0.25");
+        assert_eq!(value_at(&store, 0, 2, 4).to_string(), "2.25");
+
+        // A float makes the result a float.
+        assign(&mut store, 0, "D1=1.5d+0.25").unwrap();
+        assert!(matches!(value_at(&store, 0, 3, 0), ValueType::FloatValue(x) if x == 1.75));
+        assign(&mut store, 0, "D2=1d/0").unwrap_err();
+        assert_eq!(error_at(&store, 0, 3, 1), Some(ErrorKind::DivByZero));
+        assign(&mut store, 0, "D3=100000000000000000000d*100000000000000000000d").unwrap_err();
+        assert_eq!(error_at(&store, 0, 3, 2), Some(ErrorKind::Num));
+    }
+
+    #[test]
+    fn test_dates() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 10);
+        assign(&mut store, 0, "A1=2024-02-28").unwrap();
+        assign(&mut store, 0, "A2=A1+1").unwrap();
+        assign(&mut store, 0, "A3=2+A1").unwrap();
+        assign(&mut store, 0, "A4=A3-A1").unwrap();
+        assign(&mut store, 0, "A5=A3-60").unwrap();
+        assert_eq!(value_at(&store, 0, 0, 1).to_string(), "2024-02-29");
+        assert_eq!(value_at(&store, 0, 0, 2).to_string(), "2024-03-01");
+        assert!(matches!(value_at(&store, 0, 0, 3), ValueType::IntegerValue(2)));
+        assert_eq!(value_at(&store, 0, 0, 4).to_string(), "2024-01-01");
+
+        // A time of day moves with fractions of a day, and makes differences fractional.
+        assign(&mut store, 0, "B1=2024-03-15T18:00+0.25").unwrap();
+        assign(&mut store, 0, "B2=B1-2024-03-15").unwrap();
+        assert_eq!(value_at(&store, 0, 1, 0).to_string(), "2024-03-16");
+        assign(&mut store, 0, "B1=2024-03-15T06:00").unwrap();
+        assert!(matches!(value_at(&store, 0, 1, 1), ValueType::FloatValue(x) if x == 0.25));
+
+        assign(&mut store, 0, "C1=DATE(2023, 12, 31)").unwrap();
+        assign(&mut store, 0, "C2=YEAR(C1)*10000+MONTH(C1)*100+DAY(C1)").unwrap();
+        assign(&mut store, 0, "C3=C1<A1").unwrap();
+        assert!(matches!(value_at(&store, 0, 2, 1), ValueType::IntegerValue(20231231)));
+        assert!(matches!(value_at(&store, 0, 2, 2), ValueType::BoolValue(true)));
+        assign(&mut store, 0, "C4=DATE(2023, 2, 29)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 2, 3), Some(ErrorKind::Num));
+        assign(&mut store, 0, "C5=A1*2").unwrap_err();
+        assert_eq!(error_at(&store, 0, 2, 4), Some(ErrorKind::TypeMismatch));
+        assign(&mut store, 0, "C6=A1+A1").unwrap_err();
+        assert!(matches!(value_at(&store, 0, 2, 5), ValueType::Error(e) if e.kind == ErrorKind::TypeMismatch && e.msg == "Two dates cannot be added"));
+
+        assign(&mut store, 0, "D1=DATEDIFF(DATE(2020, 1, 31), 2021-03-01)").unwrap();
+        assign(&mut store, 0, "D2=DATEDIFF(DATE(2020, 1, 31), 2021-03-01, \"M\")").unwrap();
+        assign(&mut store, 0, "D3=DATEDIFF(2021-03-01, DATE(2020, 1, 31), \"y\")").unwrap();
+        assign(&mut store, 0, "D4=TODAY()>2024-01-01").unwrap();
+        assert!(matches!(value_at(&store, 0, 3, 0), ValueType::IntegerValue(395)));
+        assert!(matches!(value_at(&store, 0, 3, 1), ValueType::IntegerValue(13)));
+        assert!(matches!(value_at(&store, 0, 3, 2), ValueType::IntegerValue(-1)));
+        assert!(matches!(value_at(&store, 0, 3, 3), ValueType::BoolValue(true)));
+
+        assert!(parse_assign("E1=2023-02-30", 0, &store).is_err());
+    }
+
+    #[test]
+    fn test_ranges_across_sheets() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        store.new_sheet("jan", 5, 5);
+        store.new_sheet("feb", 5, 5);
+        store.new_sheet("mar", 5, 5);
+        assign(&mut store, 1, "A1=1").unwrap();
+        assign(&mut store, 2, "A1=2").unwrap();
+        assign(&mut store, 3, "A1=4").unwrap();
+        assign(&mut store, 3, "B1=8").unwrap();
+        assign(&mut store, 0, "A1=SUM(jan.A1:mar.A1)").unwrap();
+        assign(&mut store, 0, "B1=MAX(jan.A1:mar.B1)").unwrap();
+        assign(&mut store, 0, "C1=AVG(jan.A1:mar.A1, _>1)").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::IntegerValue(7)));
+        assert!(matches!(value_at(&store, 0, 1, 0), ValueType::IntegerValue(8)));
+        assert!(matches!(value_at(&store, 0, 2, 0), ValueType::FloatValue(x) if x == 3.0));
+
+        // A sheet in the middle of the range is a dependency like the first and the last.
+        assign(&mut store, 2, "A1=20").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::IntegerValue(25)));
+
+        // Removing a sheet takes it out of the range.
+        let removed = Rc::clone(&store.data[2]);
+        store.remove_sheet("feb").unwrap();
+        invalidate_children(&mut store.data, &removed.borrow());
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::IntegerValue(5)));
+        assign(&mut store, 3, "A1=40").unwrap();
+        assert!(matches!(value_at(&store, 0, 0, 0), ValueType::IntegerValue(41)));
+
+        assign(&mut store, 0, "D1=VLOOKUP(1, jan.A1:mar.B1, 2)").unwrap_err();
+        assert_eq!(error_at(&store, 0, 3, 0), Some(ErrorKind::BadRef));
+    }
+
+    #[test]
+    fn test_conditional_formats_follow_values() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        assign(&mut store, 0, "A1=50").unwrap();
+        assign(&mut store, 0, "A2=150").unwrap();
+        assign(&mut store, 0, "B1=A1*3").unwrap();
+        assign(&mut store, 0, "B2=\"text\"").unwrap();
+        let (cond, _) = parse_condition("_>C1/10", 0, &store).unwrap();
+        assign(&mut store, 0, "C1=1000").unwrap();
+        let (start, end) = (Addr::new(0, 0, 0), Addr::new(0, 1, 1));
+        add_format_rule(&store.data, FormatRule{start: start.clone(), end: end.clone(), cond: *cond, options: parse_format_options("bg=red,bold").unwrap()});
+        let bg = |store: &SheetStorage, col: usize, row: usize| {
+            store.data[0].borrow().data[col].borrow()[row].borrow().shown_format().bg
+        };
+        assert_eq!(bg(&store, 0, 0), None);
+        assert_eq!(bg(&store, 0, 1), Some(CellColor::Red));
+        assert_eq!(bg(&store, 1, 0), Some(CellColor::Red));
+        // A condition that cannot be evaluated for a cell does not hold.
+        assert_eq!(bg(&store, 1, 1), None);
+
+        // The rule is checked again whenever a cell of its range is calculated.
+        assign(&mut store, 0, "A1=10").unwrap();
+        assert_eq!(bg(&store, 1, 0), None);
+        assign(&mut store, 0, "A1=40").unwrap();
+        assert_eq!(bg(&store, 1, 0), Some(CellColor::Red));
+        assert_eq!(bg(&store, 0, 0), None);
+
+        // So is every cell of the rule when a cell its condition refers to changes.
+        assign(&mut store, 0, "C1=2000").unwrap();
+        assert_eq!(bg(&store, 0, 1), None);
+        assert_eq!(bg(&store, 1, 0), None);
+        assign(&mut store, 0, "C1=1000").unwrap();
+        assert_eq!(bg(&store, 0, 1), Some(CellColor::Red));
+        assert_eq!(bg(&store, 1, 0), Some(CellColor::Red));
+
+        assert_eq!(clear_format_rules(&store.data, &Addr::new(0, 1, 1), &Addr::new(0, 4, 4)), 1);
+        assert_eq!(bg(&store, 0, 1), None);
+        assert_eq!(bg(&store, 1, 0), None);
+        assert!(store.data[0].borrow().rules.is_empty());
+    }
+
+    #[test]
+    fn test_filters_show_matching_rows() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 8);
+        for inp in ["B1=\"qty\"", "B2=50", "B3=200", "B5=300", "B6=\"x\"", "B8=150", "A3=B3*2"] {
+            assign(&mut store, 0, inp).unwrap();
+        }
+        assert_eq!(filtered_rows(&store.data, 0), None);
+        let set_filter = |store: &SheetStorage, cond: &str| {
+            let (cond, _) = parse_condition(cond, 0, store).unwrap();
+            store.data[0].borrow_mut().filter = Some(RowFilter{start: Addr::new(0, 1, 1), cond: *cond});
+        };
+
+        // The header above the filter stays, and a value the condition cannot compare is left out.
+        set_filter(&store, "_>100");
+        assert_eq!(filtered_rows(&store.data, 0), Some(vec![0, 2, 4, 7]));
+        // A condition reading another cell uses its value of the moment.
+        assign(&mut store, 0, "C1=160").unwrap();
+        set_filter(&store, "_>C1");
+        assert_eq!(filtered_rows(&store.data, 0), Some(vec![0, 2, 4]));
+        assign(&mut store, 0, "C1=100").unwrap();
+        assert_eq!(filtered_rows(&store.data, 0), Some(vec![0, 2, 4, 7]));
+        set_filter(&store, "_<100");
+        assert_eq!(filtered_rows(&store.data, 0), Some(vec![0, 1, 3, 6]));
+
+        // The filter follows its column when rows and columns move, and goes with it.
+        assign(&mut store, 0, "B4=20").unwrap();
+        shift_sheet(&mut store, 0, SheetAxis::Row, 0, 1, true).unwrap();
+        assert_eq!(filtered_rows(&store.data, 0), Some(vec![0, 1, 2, 4, 7]));
+        assert_eq!(store.data[0].borrow().filter.as_ref().unwrap().start, Addr::new(0, 2, 1));
+        assert_eq!(value_at(&store, 0, 0, 3), ValueType::IntegerValue(400));
+        shift_sheet(&mut store, 0, SheetAxis::Col, 1, 1, false).unwrap();
+        assert!(store.data[0].borrow().filter.is_none());
+    }
+}
+
@@ -0,0 +1,443 @@
+use spreadsheet_core::SheetStorage;
+use spreadsheet_core::parse_assign;
+use spreadsheet_core::ast::{Command, ParentType};
+use spreadsheet_core::cell_operations::{CellFunc, ValueType, MAX_COLS, MAX_ROWS};
+use spreadsheet_core::evaluate_operations::evaluate;
+use spreadsheet_core::csv_operations::{import_csv, CsvOptions};
+use spreadsheet_core::sheet_operations::{autofill_ap, copy_cell_function, copy_cell_value, copy_range_function, copy_range_value, define_name, find_cells, parse_find_spec, parse_pivot_spec, parse_sort_keys, pivot, remove_name, replace_in_formulas, shift_sheet, sort_range, SheetAxis};
+use spreadsheet_core::ast::Addr;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn assign(store: &mut SheetStorage, curr_sheet: u32, inp: &str) {
+        let (cmd, deps) = parse_assign(inp, curr_sheet, store).expect("Parsing failed");
+        let Command::AssignCmd(addr, expr) = cmd else { panic!("Not an assignment") };
+        let mut ranges = vec![(addr.clone(), addr.clone())];
+        for dep in deps {
+            match dep {
+                ParentType::Single(a) => ranges.push((a.clone(), a)),
+                ParentType::Range(a1, a2) => ranges.push((a1, a2)),
+            }
+        }
+        for (a1, a2) in ranges {
+            store.data[a1.sheet as usize].borrow().extend_range(&a1, &a2);
+        }
+        let old_func = {
+            let sheet = store.data[addr.sheet as usize].borrow();
+            let cell_rc = Rc::clone(&sheet.data[addr.col as usize].borrow()[addr.row as usize]);
+            let mut cell = cell_rc.borrow_mut();
+            let old_func = cell.cell_func.clone();
+            cell.formula = inp[inp.find('=').unwrap() + 1..].to_string();
+            cell.cell_func = Some(CellFunc::new(*expr));
+            old_func
+        };
+        let _ = evaluate(&mut store.data, &addr, &old_func);
+    }
+
+    fn define(store: &mut SheetStorage, inp: &str) -> Result<(), String> {
+        let (cmd, _) = parse_assign(inp, 0, store).expect("Parsing failed");
+        let Command::DefineName(name, def) = cmd else { panic!("Not a name definition") };
+        define_name(store, &name, def).map(|_| ())
+    }
+
+    fn value_at(store: &SheetStorage, sheet: usize, col: usize, row: usize) -> String {
+        store.data[sheet].borrow().val_at(col, row).to_string()
+    }
+
+    fn formula_at(store: &SheetStorage, sheet: usize, col: usize, row: usize) -> String {
+        store.data[sheet].borrow().data[col].borrow()[row].borrow().formula.clone()
+    }
+
+    fn valid_at(store: &SheetStorage, sheet: usize, col: usize, row: usize) -> bool {
+        !store.data[sheet].borrow().data[col].borrow()[row].borrow().is_error()
+    }
+
+    #[test]
+    fn test_insert_rows_moves_cells_and_references() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 5);
+        store.new_sheet("other", 3, 3);
+        assign(&mut store, 0, "A1=1");
+        assign(&mut store, 0, "A2=2");
+        assign(&mut store, 0, "B1=A2*10");
+        assign(&mut store, 0, "C1=SUM(A1:A2)");
+        assign(&mut store, 1, "A1=sheet0.A2+1");
+
+        shift_sheet(&mut store, 0, SheetAxis::Row, 1, 2, true).unwrap();
+        assert_eq!(store.data[0].borrow().rows, 7);
+        assert_eq!(value_at(&store, 0, 0, 3), "2");
+        assert_eq!(formula_at(&store, 0, 1, 0), "A4*10");
+        assert_eq!(formula_at(&store, 0, 2, 0), "SUM(A1:A4)");
+        assert_eq!(formula_at(&store, 1, 0, 0), "sheet0.A4+1");
+        assert_eq!(store.data[0].borrow().data[0].borrow()[3].borrow().addr.row, 3);
+
+        // The graph follows the moved cells.
+        assign(&mut store, 0, "A4=5");
+        assign(&mut store, 0, "A2=100");
+        assert_eq!(value_at(&store, 0, 1, 0), "50");
+        assert_eq!(value_at(&store, 0, 2, 0), "106");
+        assert!(matches!(store.data[1].borrow().val_at(0, 0), ValueType::IntegerValue(6)));
+    }
+
+    #[test]
+    fn test_remove_rows_marks_deleted_references() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 5);
+        assign(&mut store, 0, "A1=1");
+        assign(&mut store, 0, "A2=2");
+        assign(&mut store, 0, "A3=3");
+        assign(&mut store, 0, "B1=A2+1");
+        assign(&mut store, 0, "C1=SUM(A1:A3)");
+        assign(&mut store, 0, "C5=SUM(A2:A2)");
+        assign(&mut store, 0, "B5=A3");
+
+        shift_sheet(&mut store, 0, SheetAxis::Row, 1, 1, false).unwrap();
+        assert_eq!(store.data[0].borrow().rows, 4);
+        assert_eq!(formula_at(&store, 0, 1, 0), "#REF!+1");
+        assert!(!valid_at(&store, 0, 1, 0));
+        assert_eq!(formula_at(&store, 0, 2, 0), "SUM(A1:A2)");
+        assert_eq!(value_at(&store, 0, 2, 0), "4");
+        assert_eq!(formula_at(&store, 0, 2, 3), "#REF!");
+        assert!(!valid_at(&store, 0, 2, 3));
+        assert_eq!(formula_at(&store, 0, 1, 3), "A2");
+        assert_eq!(value_at(&store, 0, 1, 3), "3");
+
+        // A formula with a deleted reference can be parsed again, eg. when a workbook is loaded.
+        assert!(parse_assign("A1=#REF!+1", 0, &store).is_ok());
+    }
+
+    #[test]
+    fn test_columns_and_ranges() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 4, 3);
+        assign(&mut store, 0, "A1=1");
+        assign(&mut store, 0, "B1=2");
+        assign(&mut store, 0, "A2=SUM(A1:B1)");
+        assign(&mut store, 0, "D3=B1");
+
+        shift_sheet(&mut store, 0, SheetAxis::Col, 1, 1, true).unwrap();
+        assert_eq!(store.data[0].borrow().columns, 5);
+        assert_eq!(formula_at(&store, 0, 0, 1), "SUM(A1:C1)");
+        assert_eq!(formula_at(&store, 0, 4, 2), "C1");
+        assign(&mut store, 0, "B1=10");
+        assert_eq!(value_at(&store, 0, 0, 1), "13");
+
+        shift_sheet(&mut store, 0, SheetAxis::Col, 0, 2, false).unwrap();
+        assert_eq!(store.data[0].borrow().columns, 3);
+        assert_eq!(value_at(&store, 0, 0, 0), "2");
+        assert_eq!(formula_at(&store, 0, 2, 2), "A1");
+        assert_eq!(value_at(&store, 0, 2, 2), "2");
+    }
+
+    #[test]
+    fn test_shift_out_of_range() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 3);
+        assert!(shift_sheet(&mut store, 0, SheetAxis::Row, 4, 1, true).is_err());
+        assert!(shift_sheet(&mut store, 0, SheetAxis::Row, 2, 2, false).is_err());
+        assert!(shift_sheet(&mut store, 0, SheetAxis::Col, 0, 3, false).is_err());
+        assert!(shift_sheet(&mut store, 0, SheetAxis::Col, 0, 0, true).is_err());
+        assert!(shift_sheet(&mut store, 5, SheetAxis::Col, 0, 1, true).is_err());
+        assert!(shift_sheet(&mut store, 0, SheetAxis::Row, 3, 1, true).is_ok());
+
+        // Sheets cannot grow past the row and column limits.
+        assert_eq!(shift_sheet(&mut store, 0, SheetAxis::Col, 0, 200000000, true), Err("Too many columns".to_string()));
+        assert_eq!(shift_sheet(&mut store, 0, SheetAxis::Row, 0, MAX_ROWS, true), Err("Too many rows".to_string()));
+        assert!(shift_sheet(&mut store, 0, SheetAxis::Col, 3, MAX_COLS - 3, true).is_ok());
+        assert_eq!(store.data[0].borrow().columns, MAX_COLS);
+    }
+
+    #[test]
+    fn test_copy_moves_relative_references() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 4, 5);
+        assign(&mut store, 0, "A1=1");
+        assign(&mut store, 0, "A2=2");
+        assign(&mut store, 0, "A3=3");
+        assign(&mut store, 0, "C1=10");
+        assign(&mut store, 0, "B1=A1+$C$1+A$1+$A1");
+
+        copy_cell_function(Addr::new(0, 0, 1), Addr::new(0, 2, 1), &mut store).unwrap();
+        assert_eq!(formula_at(&store, 0, 1, 2), "((A3+$C$1)+A$1)+$A3");
+        assert_eq!(value_at(&store, 0, 1, 2), "17");
+
+        // Copying two columns right moves only the parts without a `$`.
+        copy_cell_function(Addr::new(0, 0, 1), Addr::new(0, 0, 3), &mut store).unwrap();
+        assert_eq!(formula_at(&store, 0, 3, 0), "((C1+$C$1)+C$1)+$A1");
+        assert_eq!(value_at(&store, 0, 3, 0), "31");
+
+        // The copies follow the cells they now point to.
+        assign(&mut store, 0, "A3=30");
+        assert_eq!(value_at(&store, 0, 1, 2), "71");
+
+        // A reference moved out of the sheet is a bad reference.
+        assign(&mut store, 0, "D3=C2*$A1");
+        copy_cell_function(Addr::new(0, 2, 3), Addr::new(0, 0, 3), &mut store).unwrap_err();
+        assert_eq!(formula_at(&store, 0, 3, 0), "#REF!*#REF!");
+        assert!(!valid_at(&store, 0, 3, 0));
+    }
+
+    #[test]
+    fn test_copy_range_and_autofill_formulas() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 4, 6);
+        assign(&mut store, 0, "A1=1");
+        assign(&mut store, 0, "A2=2");
+        assign(&mut store, 0, "A3=3");
+        assign(&mut store, 0, "B1=SUM($A$1:A1)");
+
+        autofill_ap(Addr::new(0, 0, 1), Addr::new(0, 2, 1), &mut store).unwrap();
+        assert_eq!(formula_at(&store, 0, 1, 2), "SUM($A$1:A3)");
+        assert_eq!(value_at(&store, 0, 1, 1), "3");
+        assert_eq!(value_at(&store, 0, 1, 2), "6");
+
+        copy_range_function(Addr::new(0, 0, 1), Addr::new(0, 2, 1), Addr::new(0, 3, 2), &mut store).unwrap();
+        assert_eq!(formula_at(&store, 0, 2, 3), "SUM($A$1:B4)");
+        assert_eq!(formula_at(&store, 0, 2, 5), "SUM($A$1:B6)");
+        assert_eq!(value_at(&store, 0, 2, 5), "16");
+
+        // A reversed source range or a destination past the last u32 row is turned down.
+        assert!(copy_range_function(Addr::new(0, 2, 1), Addr::new(0, 0, 1), Addr::new(0, 3, 2), &mut store).is_err());
+        assert!(copy_range_function(Addr::new(0, 0, 1), Addr::new(0, 2, 1), Addr::new(0, u32::MAX, 2), &mut store).is_err());
+        assert!(copy_range_value(Addr::new(0, 0, 1), Addr::new(0, 2, 1), Addr::new(0, u32::MAX, 2), &store.data).is_err());
+        assert!(copy_range_value(Addr::new(0, 0, 1), Addr::new(0, 0, 0), Addr::new(0, 3, 2), &store.data).is_err());
+
+        // Constants still fill a progression.
+        assign(&mut store, 0, "D1=2");
+        assign(&mut store, 0, "D2=4");
+        store.data[0].borrow().extend_range(&Addr::new(0, 0, 3), &Addr::new(0, 3, 3));
+        autofill_ap(Addr::new(0, 0, 3), Addr::new(0, 3, 3), &mut store).unwrap();
+        assert_eq!(value_at(&store, 0, 3, 3), "8");
+    }
+
+    #[test]
+    fn test_sparse_storage_only_keeps_used_cells() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 18278, 999);
+        assign(&mut store, 0, "ZZZ999=5");
+        assign(&mut store, 0, "A1=ZZZ999+1");
+        assign(&mut store, 0, "B2=SUM(C3:D4)");
+        // The two cells written to, plus the four cells of the range that hold B2 as a child.
+        assert_eq!(store.data[0].borrow().cell_count(), 7);
+        assert_eq!(value_at(&store, 0, 0, 0), "6");
+        assert_eq!(value_at(&store, 0, 5, 500), "0");
+
+        // Cells created in a gap still reach their dependents.
+        assign(&mut store, 0, "C4=7");
+        assert_eq!(value_at(&store, 0, 1, 1), "7");
+
+        shift_sheet(&mut store, 0, SheetAxis::Row, 0, 1, true).unwrap();
+        assert_eq!(store.data[0].borrow().cell_count(), 7);
+        assert_eq!(formula_at(&store, 0, 0, 1), "ZZZ1000+1");
+        assert_eq!(value_at(&store, 0, 18277, 999), "5");
+    }
+
+    #[test]
+    fn test_names_are_tracked_and_can_be_redefined() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 5);
+        assign(&mut store, 0, "A1=1");
+        assign(&mut store, 0, "A2=2");
+        assign(&mut store, 0, "A3=3");
+        define(&mut store, "total=A1:A3").unwrap();
+        define(&mut store, "rate=0.5").unwrap();
+        define(&mut store, "first=A1").unwrap();
+        assign(&mut store, 0, "B1=SUM(total)*rate");
+        assign(&mut store, 0, "B2=first+1");
+        assert_eq!(value_at(&store, 0, 1, 0), "3");
+        assert_eq!(value_at(&store, 0, 1, 1), "2");
+        assert!(parse_assign("C1=total", 0, &store).is_err());
+        assert!(parse_assign("C1=SUM(nosuch)", 0, &store).is_err());
+
+        // Redefining a name recalculates its users, and they follow the cells of the new definition.
+        define(&mut store, "total=A1:A2").unwrap();
+        define(&mut store, "rate=2").unwrap();
+        assert_eq!(value_at(&store, 0, 1, 0), "6");
+        assign(&mut store, 0, "A3=100");
+        assign(&mut store, 0, "A2=5");
+        assert_eq!(value_at(&store, 0, 1, 0), "12");
+
+        // A name used as a value cannot become a range, and a cycle leaves the name as it was.
+        assert!(define(&mut store, "first=A1:A2").is_err());
+        define(&mut store, "first=A3").unwrap();
+        assign(&mut store, 0, "A1=B2");
+        assert!(define(&mut store, "first=A1").is_err_and(|e| e.contains("Cyclic")));
+        assert_eq!(value_at(&store, 0, 1, 1), "101");
+
+        // Names move with inserted rows, and copying keeps them in place.
+        shift_sheet(&mut store, 0, SheetAxis::Row, 0, 1, true).unwrap();
+        assert_eq!(store.names["first"].to_formula(&store), "sheet0.$A$4");
+        assert_eq!(formula_at(&store, 0, 1, 2), "first+1");
+        copy_cell_function(Addr::new(0, 2, 1), Addr::new(0, 3, 2), &mut store).unwrap();
+        assert_eq!(value_at(&store, 0, 2, 3), "101");
+        assert!(remove_name(&mut store, "first").is_err());
+        assign(&mut store, 0, "B3=1");
+        assign(&mut store, 0, "C4=1");
+        assert!(remove_name(&mut store, "first").is_ok());
+    }
+
+    #[test]
+    fn test_sort_moves_rows_with_their_formulas() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 4, 8);
+        let keys = ["\"pear\"", "2.5", "10", "\"Apple\"", "True", "2024-01-01", "", "1/0"];
+        let counts = [3, 1, 2, 1, 1, 5, 0, 4];
+        for (row, (key, count)) in keys.iter().zip(counts).enumerate() {
+            if !key.is_empty() {
+                assign(&mut store, 0, &format!("A{}={}", row + 1, key));
+            }
+            assign(&mut store, 0, &format!("B{}={}", row + 1, count));
+            assign(&mut store, 0, &format!("C{}=B{}*10", row + 1, row + 1));
+        }
+        assign(&mut store, 0, "D1=C1");
+        let (start, end) = (Addr::new(0, 0, 0), Addr::new(0, 7, 2));
+
+        // Numbers, then dates, text ignoring case, booleans and errors, with the empty cell last.
+        sort_range(&mut store, &start, &end, &parse_sort_keys("A").unwrap()).unwrap();
+        let column = |store: &SheetStorage, col| (0..8).map(|row| value_at(store, 0, col, row)).collect::<Vec<_>>();
+        assert_eq!(column(&store, 1), ["1", "2", "5", "1", "3", "1", "4", "0"]);
+        assert_eq!(column(&store, 2), ["10", "20", "50", "10", "30", "10", "40", "0"]);
+        assert_eq!(formula_at(&store, 0, 2, 2), "B3*10");
+        assert_eq!(formula_at(&store, 0, 0, 6), "1/0");
+        assert_eq!(value_at(&store, 0, 3, 0), "10");
+
+        // Later keys break the ties of earlier ones, and equal rows keep their order.
+        sort_range(&mut store, &start, &end, &parse_sort_keys("b desc, a").unwrap()).unwrap();
+        assert_eq!(column(&store, 2), ["50", "40", "30", "20", "10", "10", "10", "0"]);
+        assert_eq!(column(&store, 0)[4..], ["2.5", "Apple", "true", "0"]);
+
+        // The moved formulas and the cells reading them still follow their dependencies.
+        assign(&mut store, 0, "B1=7");
+        assert_eq!(value_at(&store, 0, 2, 0), "70");
+        assert_eq!(value_at(&store, 0, 3, 0), "70");
+
+        assert!(sort_range(&mut store, &start, &end, &parse_sort_keys("D").unwrap()).is_err());
+        assert!(parse_sort_keys("A down").is_err());
+        assert!(parse_sort_keys(" , ").is_err());
+    }
+
+    #[test]
+    fn test_pivot_writes_a_live_summary_sheet() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 6);
+        let rows = [("east", "q1", 10), ("west", "q1", 20), ("east", "q2", 30), ("north", "q2", 40), ("West", "q2", 50), ("west", "q2", 60)];
+        for (row, (region, quarter, amount)) in rows.iter().enumerate() {
+            assign(&mut store, 0, &format!("A{}=\"{}\"", row + 1, region));
+            assign(&mut store, 0, &format!("B{}=\"{}\"", row + 1, quarter));
+            assign(&mut store, 0, &format!("C{}={}", row + 1, amount));
+        }
+        let (start, end) = (Addr::new(0, 0, 0), Addr::new(0, 5, 2));
+
+        // Each key is listed once, in sorted order, with text matched as written.
+        let sums = pivot(&mut store, &start, &end, &parse_pivot_spec("rows=A,sum=C").unwrap(), "sums").unwrap();
+        assert_eq!(store.num_from_name("sums"), Some(sums));
+        let column = |store: &SheetStorage, sheet, col| (0..5).map(|row| value_at(store, sheet, col, row)).collect::<Vec<_>>();
+        assert_eq!(column(&store, sums, 0), ["A", "east", "north", "west", "West"]);
+        assert_eq!(column(&store, sums, 1), ["SUM of C", "40", "40", "80", "50"]);
+        assert_eq!(formula_at(&store, sums, 1, 1), "SUMBY(sheet0.$A$1:sheet0.$C$6, 3, 1, $A2)");
+
+        let grid = pivot(&mut store, &start, &end, &parse_pivot_spec("rows=A, cols=B, max=C").unwrap(), "grid").unwrap();
+        assert_eq!(column(&store, grid, 0), ["MAX of C", "east", "north", "west", "West"]);
+        assert_eq!(column(&store, grid, 2), ["q2", "30", "40", "60", "50"]);
+
+        // The summary follows later edits of the source cells.
+        assign(&mut store, 0, "C1=100");
+        assert_eq!(value_at(&store, sums, 1, 1), "130");
+        assert_eq!(value_at(&store, grid, 1, 1), "100");
+
+        // Keys are formulas too, so changing a key moves its rows to the new key.
+        assert_eq!(formula_at(&store, sums, 0, 2), "KEY(sheet0.$A$1:sheet0.$C$6, 1, 2)");
+        assign(&mut store, 0, "A2=\"north\"");
+        assign(&mut store, 0, "A6=\"south\"");
+        assert_eq!(column(&store, sums, 0), ["A", "east", "north", "south", "West"]);
+        assert_eq!(column(&store, sums, 1), ["SUM of C", "130", "60", "60", "50"]);
+        assert_eq!(column(&store, grid, 2), ["q2", "30", "40", "60", "50"]);
+        assign(&mut store, 0, "A5=\"east\"");
+        assert_eq!(column(&store, sums, 1)[1..4], ["180", "60", "60"]);
+        assert!(!valid_at(&store, sums, 0, 4));
+        assert!(!valid_at(&store, sums, 1, 4));
+
+        assert!(pivot(&mut store, &start, &end, &parse_pivot_spec("rows=A,sum=D").unwrap(), "other").is_err());
+        assert!(pivot(&mut store, &start, &end, &parse_pivot_spec("rows=A,sum=C").unwrap(), "grid").is_err());
+        assert!(parse_pivot_spec("rows=A").is_err());
+        assert!(parse_pivot_spec("rows=A,median=C").is_err());
+    }
+
+    #[test]
+    fn test_find_and_replace_in_formulas() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 4);
+        store.new_sheet("other", 2, 2);
+        assign(&mut store, 0, "A1=10");
+        assign(&mut store, 0, "A2=A1*2");
+        assign(&mut store, 0, "B1=\"east side\"");
+        assign(&mut store, 0, "B2=SUM(A1:A2)");
+        assign(&mut store, 1, "A1=sheet0.A1+1");
+        let cells = |found: Vec<Addr>| found.iter().map(|a| (a.sheet, a.row, a.col)).collect::<Vec<_>>();
+
+        // Values are matched as shown, formulas as written, row by row and then on the following sheets.
+        assert_eq!(cells(find_cells(&store, 0, &parse_find_spec("0", "").unwrap())), [(0, 0, 0), (0, 1, 0), (0, 1, 1)]);
+        assert_eq!(cells(find_cells(&store, 0, &parse_find_spec("A1", "formulas, all").unwrap())), [(0, 1, 0), (0, 1, 1), (1, 0, 0)]);
+        assert_eq!(cells(find_cells(&store, 0, &parse_find_spec("^e.*e$", "regex").unwrap())), [(0, 0, 1)]);
+        assert!(find_cells(&store, 0, &parse_find_spec("A1:", "").unwrap()).is_empty());
+
+        let spec = parse_find_spec(r"A(\d)", "regex,formulas,all").unwrap();
+        let found = find_cells(&store, 0, &spec);
+        assert_eq!(replace_in_formulas(&mut store, &found, &spec, "C$1"), Ok(3));
+        assert_eq!(formula_at(&store, 0, 1, 1), "SUM(C1:C2)");
+        assert_eq!(formula_at(&store, 1, 0, 0), "sheet0.C1+1");
+        assign(&mut store, 0, "C1=4");
+        assert_eq!(value_at(&store, 0, 0, 1), "8");
+        assert_eq!(value_at(&store, 1, 0, 0), "5");
+
+        // Nothing changes when one of the new formulas is invalid, and a cycle only keeps its own cell as it was.
+        let spec = parse_find_spec("C1", "formulas").unwrap();
+        let found = find_cells(&store, 0, &spec);
+        assert!(replace_in_formulas(&mut store, &found, &spec, "C9").is_err());
+        assert!(replace_in_formulas(&mut store, &found, &spec, "C1+").is_err());
+        assert_eq!(formula_at(&store, 0, 0, 1), "C1*2");
+        assert!(replace_in_formulas(&mut store, &found, &spec, "B2").is_err());
+        assert_eq!(formula_at(&store, 0, 0, 1), "B2*2");
+        assert_eq!(formula_at(&store, 0, 1, 1), "SUM(C1:C2)");
+        assert_eq!(value_at(&store, 0, 0, 1), "8");
+
+        assert!(parse_find_spec("", "").is_err());
+        assert!(parse_find_spec("a", "words").is_err());
+        assert!(parse_find_spec("(", "regex").is_err());
+        assert!(parse_find_spec("(", "").is_ok());
+    }
+
+    #[test]
+    fn test_replace_in_imported_and_copied_values() {
+        let path = std::env::temp_dir().join("spreadsheet_core_replace_import.csv");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "bolt,12,true\nnut,2.0,bolt \"m8\"\n").unwrap();
+        let mut store = SheetStorage::new();
+        store.add_sheet("parts", import_csv(path, 0, &CsvOptions::default()).unwrap());
+        std::fs::remove_file(path).unwrap();
+        store.new_sheet("copy", 3, 1);
+        store.data[1].borrow().extend_range(&Addr::new(1, 0, 0), &Addr::new(1, 0, 2));
+        for col in 0..3 {
+            copy_cell_value(Addr::new(0, 1, col), Addr::new(1, 0, col), &store.data);
+        }
+        copy_cell_value(Addr::new(0, 0, 2), Addr::new(1, 0, 0), &store.data);
+
+        // Imported and copied values hold formulas that parse back to the same values.
+        let spec = parse_find_spec("bolt", "formulas, all").unwrap();
+        let found = find_cells(&store, 0, &spec);
+        assert_eq!(replace_in_formulas(&mut store, &found, &spec, "screw"), Ok(3));
+        assert_eq!(value_at(&store, 0, 0, 0), "screw");
+        assert_eq!(value_at(&store, 0, 2, 1), "screw \"m8\"");
+        assert_eq!(value_at(&store, 1, 2, 0), "screw \"m8\"");
+        assert_eq!(formula_at(&store, 1, 0, 0), "True");
+        assert_eq!(formula_at(&store, 1, 1, 0), "2.0");
+        let spec = parse_find_spec("True", "formulas, all").unwrap();
+        let found = find_cells(&store, 0, &spec);
+        assert_eq!(replace_in_formulas(&mut store, &found, &spec, "False"), Ok(2));
+        assert_eq!(value_at(&store, 0, 2, 0), value_at(&store, 1, 0, 0));
+        assert!(matches!(store.data[1].borrow().val_at(0, 0), ValueType::BoolValue(false)));
+        assert!(matches!(store.data[1].borrow().val_at(1, 0), ValueType::FloatValue(_)));
+    }
+}
@@ -0,0 +1,216 @@
+use spreadsheet_core::SheetStorage;
+use spreadsheet_core::{parse_assign, parse_condition};
+use spreadsheet_core::ast::{Addr, Command, ParentType};
+use spreadsheet_core::csv_operations::{import_csv, CsvOptions};
+use spreadsheet_core::cell_operations::{parse_format_options, Align, CellColor, CellFormat, CellFunc, Decimal, FormatRule, ValueType};
+use spreadsheet_core::evaluate_operations::evaluate;
+use spreadsheet_core::sheet_operations::{add_format_rule, define_name};
+use spreadsheet_core::workbook_operations::{save_workbook, load_workbook};
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::rc::Rc;
+
+    fn assign(store: &mut SheetStorage, curr_sheet: u32, inp: &str) {
+        let (cmd, deps) = parse_assign(inp, curr_sheet, store).expect("Parsing failed");
+        let Command::AssignCmd(addr, expr) = cmd else { panic!("Not an assignment") };
+        let mut ranges = vec![(addr.clone(), addr.clone())];
+        for dep in deps {
+            match dep {
+                ParentType::Single(a) => ranges.push((a.clone(), a)),
+                ParentType::Range(a1, a2) => ranges.push((a1, a2)),
+            }
+        }
+        for (a1, a2) in ranges {
+            store.data[a1.sheet as usize].borrow().extend_range(&a1, &a2);
+        }
+        let old_func = {
+            let sheet = store.data[addr.sheet as usize].borrow();
+            let cell_rc = Rc::clone(&sheet.data[addr.col as usize].borrow()[addr.row as usize]);
+            let mut cell = cell_rc.borrow_mut();
+            let old_func = cell.cell_func.clone();
+            cell.cell_func = Some(CellFunc::new(*expr));
+            old_func
+        };
+        let _ = evaluate(&mut store.data, &addr, &old_func);
+    }
+
+    fn value_at(store: &SheetStorage, sheet: usize, col: usize, row: usize) -> String {
+        store.data[sheet].borrow().val_at(col, row).to_string()
+    }
+
+    #[test]
+    fn test_to_formula_round_trip() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 5, 5);
+        store.new_sheet("data", 5, 5);
+        let (Command::DefineName(name, def), _) = parse_assign("table=data.A1:data.B3", 0, &store).unwrap() else { panic!() };
+        define_name(&mut store, &name, def).unwrap();
+        let formulas = [
+            "A1=(B1+2)*C1",
+            "A1=B1-(C1-D1)",
+            "A1=data.A1**-2.5",
+            "A1=SUM(B1:C3)",
+            "A1=MAX(B1:C3, _>2)",
+            "A1=IFELSE(B1>=2, \"yes\", 'say \"no\"')",
+            "A1=ROUND(B1/3, 2)",
+            "A1=NOT(B1==C1)",
+            "A1=VLOOKUP(\"k\", B1:C3, 2)+INDEX(data.A1:data.B2, 1, 2)",
+            "A1=MATCH(B1, C1:C4)",
+            "A1=ABCD123456+data.XFD1048576",
+            "A1=$B$1+B$2+$C3+SUM(data.$A$1:data.A2)",
+            "A1=CONCAT(ABS(B1-2), \"x\", data.A1)",
+            "A1=DATEDIFF(2024-01-31, B1+1.5, \"M\")+YEAR(2024-03-15T09:30:00-B1)",
+            "A1=ROUND(12.50d*B1, 2)-0.10d+-3d",
+            "A1=SUM(table, _>1)+VLOOKUP(B1, table, 2)",
+            "A1=SUM(sheet0.B1:data.C2)+AVG(data.A1:data.A1)",
+            "A1=CONCAT(\"it's \"\"x\"\"\", B1)",
+            "A1=KEY(B1:C4, 2, B1+1)",
+        ];
+        for inp in formulas {
+            let (Command::AssignCmd(_, expr), _) = parse_assign(inp, 0, &store).unwrap() else { panic!() };
+            let printed = format!("A1={}", expr.to_formula(0, &store));
+            let (Command::AssignCmd(_, reparsed), _) = parse_assign(&printed, 0, &store).unwrap() else { panic!() };
+            assert_eq!(format!("{:?}", expr), format!("{:?}", reparsed), "{}", printed);
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_keeps_formulas() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 4, 6);
+        store.new_sheet("data", 3, 3);
+        assign(&mut store, 1, "A1=4");
+        assign(&mut store, 1, "A2=2.5");
+        assign(&mut store, 0, "A1=data.A1*2");
+        assign(&mut store, 0, "B1=SUM(data.A1:data.A2)");
+        assign(&mut store, 0, "C1=\"hello\"");
+        assign(&mut store, 0, "D1=A1/0");
+        let (Command::DefineName(name, def), _) = parse_assign("rate=data.A2", 0, &store).unwrap() else { panic!() };
+        define_name(&mut store, &name, def).unwrap();
+        assign(&mut store, 0, "C2=rate*2");
+
+        let path = std::env::temp_dir().join("spreadsheet_core_workbook_test.wb");
+        let path = path.to_str().unwrap();
+        save_workbook(&store, path).unwrap();
+        let loaded = load_workbook(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.list_names(), vec!["sheet0".to_string(), "data".to_string()]);
+        assert_eq!(loaded.data[0].borrow().rows, 6);
+        assert_eq!(loaded.data[0].borrow().columns, 4);
+        assert_eq!(value_at(&loaded, 0, 0, 0), "8");
+        assert_eq!(value_at(&loaded, 0, 1, 0), "6.5");
+        assert_eq!(value_at(&loaded, 0, 2, 0), "hello");
+        assert_eq!(value_at(&loaded, 0, 3, 0), "#DIV/0!");
+        assert_eq!(value_at(&loaded, 0, 2, 1), "5");
+
+        // The dependency graph is rebuilt, so changing a parent recomputes the loaded formulas.
+        let mut loaded = loaded;
+        assign(&mut loaded, 1, "A1=10");
+        assert_eq!(value_at(&loaded, 0, 0, 0), "20");
+        assert_eq!(value_at(&loaded, 0, 1, 0), "12.5");
+        assert!(matches!(loaded.data[1].borrow().val_at(0, 0), ValueType::IntegerValue(10)));
+    }
+
+    #[test]
+    fn test_save_and_load_keeps_text_with_quotes_and_line_breaks() {
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 2);
+        assign(&mut store, 0, "A1=\"line one\nline two\"");
+        assign(&mut store, 0, "B1=\"say \"\"hi\"\", it's\"");
+        assign(&mut store, 0, "A2=\"C:\\new\\\"");
+        assign(&mut store, 0, "B2=\"\r\n\"");
+        assign(&mut store, 0, "C1=CONCAT('a \"b\"', \"\\n\")");
+        let (Command::DefineName(name, def), _) = parse_assign("greeting=\"hi\nthere\"", 0, &store).unwrap() else { panic!() };
+        define_name(&mut store, &name, def).unwrap();
+        assign(&mut store, 0, "C2=greeting");
+
+        let path = std::env::temp_dir().join("spreadsheet_core_workbook_text.wb");
+        let path = path.to_str().unwrap();
+        save_workbook(&store, path).unwrap();
+        let loaded = load_workbook(path).unwrap();
+        fs::remove_file(path).unwrap();
+        for (col, row) in [(0, 0), (1, 0), (0, 1), (1, 1), (2, 0), (2, 1)] {
+            assert_eq!(value_at(&loaded, 0, col, row), value_at(&store, 0, col, row));
+        }
+        assert_eq!(value_at(&loaded, 0, 0, 0), "line one\nline two");
+        assert_eq!(value_at(&loaded, 0, 1, 0), "say \"hi\", it's");
+        assert_eq!(value_at(&loaded, 0, 0, 1), "C:\\new\\");
+        assert_eq!(value_at(&loaded, 0, 2, 0), "a \"b\"\\n");
+        assert_eq!(value_at(&loaded, 0, 2, 1), "hi\nthere");
+
+        // Text read from a csv is kept the same way.
+        let csv_path = std::env::temp_dir().join("spreadsheet_core_workbook_text.csv");
+        let csv_path = csv_path.to_str().unwrap();
+        fs::write(csv_path, "\"line one\nline two\",\"say \"\"hi\"\", it's\"\nC:\\new\\,\"\r\n\"\n").unwrap();
+        let mut store = SheetStorage::new();
+        store.add_sheet("sheet0", import_csv(csv_path, 0, &CsvOptions::default()).unwrap());
+        fs::remove_file(csv_path).unwrap();
+        save_workbook(&store, path).unwrap();
+        let loaded = load_workbook(path).unwrap();
+        fs::remove_file(path).unwrap();
+        for (col, row) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            assert_eq!(value_at(&loaded, 0, col, row), value_at(&store, 0, col, row));
+        }
+        assert_eq!(value_at(&loaded, 0, 0, 0), "line one\nline two");
+        assert_eq!(value_at(&loaded, 0, 0, 1), "C:\\new\\");
+    }
+
+    #[test]
+    fn test_formats_render_and_are_saved() {
+        let format = |spec: &str| {
+            let mut format = CellFormat::default();
+            parse_format_options(spec).unwrap().iter().for_each(|option| format.apply(option));
+            format
+        };
+        assert_eq!(format("decimals=2,thousands").render(&ValueType::FloatValue(1234567.891)), "1,234,567.89");
+        assert_eq!(format("decimals=2").render(&ValueType::IntegerValue(-5)), "-5.00");
+        assert_eq!(format("percent,decimals=1").render(&ValueType::FloatValue(0.1234)), "12.3%");
+        assert_eq!(format("percent").render(&ValueType::DecimalValue(Decimal::parse("0.125").unwrap())), "12.500%");
+        assert_eq!(format("decimals=3,thousands").render(&ValueType::DecimalValue(Decimal::parse("-1234.5").unwrap())), "-1,234.500");
+        assert_eq!(format("decimals=2,thousands").render(&ValueType::String("1234".to_string())), "1234");
+        assert_eq!(format("align=right,fg=red,bg=#1e90ff,bold").bg, Some(CellColor::Rgb(0x1e, 0x90, 0xff)));
+        assert!(parse_format_options("decimals=x").is_err());
+        assert!(parse_format_options("fg=#12345").is_err());
+        assert!(parse_format_options("italic").is_err());
+
+        let mut store = SheetStorage::new();
+        store.new_sheet("sheet0", 3, 3);
+        assign(&mut store, 0, "A1=1234.5");
+        let (a1, b2) = (Addr::new(0, 0, 0), Addr::new(0, 1, 1));
+        store.data[0].borrow().format_range(&a1, &b2, Some(&parse_format_options("thousands,align=center").unwrap()));
+        store.data[0].borrow().format_range(&a1, &a1, Some(&parse_format_options("decimals=2,fg=#00ff00").unwrap()));
+        store.data[0].borrow().format_range(&b2, &b2, None);
+        let (cond, _) = parse_condition("_>=1000", 0, &store).unwrap();
+        add_format_rule(&store.data, FormatRule{start: a1.clone(), end: b2.clone(), cond: *cond, options: parse_format_options("fg=red").unwrap()});
+
+        let path = std::env::temp_dir().join("spreadsheet_core_workbook_format_test.wb");
+        let path = path.to_str().unwrap();
+        save_workbook(&store, path).unwrap();
+        let loaded = load_workbook(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let sheet = loaded.data[0].borrow();
+        let format_at = |col: usize, row: usize| sheet.data[col].borrow().get(row).map(|cell| cell.borrow().format.clone()).unwrap_or_default();
+        assert_eq!(format_at(0, 0).to_string(), "decimals=2,thousands,align=center,fg=#00ff00");
+        assert_eq!(format_at(0, 0).render(&sheet.val_at(0, 0)), "1,234.50");
+        assert_eq!(format_at(1, 0).align, Some(Align::Center));
+        assert!(format_at(1, 1).is_default());
+        assert_eq!(sheet.rules.len(), 1);
+        assert_eq!(sheet.data[0].borrow()[0].borrow().shown_format().fg, Some(CellColor::Red));
+        assert_eq!(sheet.data[1].borrow()[0].borrow().shown_format().fg, None);
+    }
+
+    #[test]
+    fn test_load_rejects_other_files() {
+        let path = std::env::temp_dir().join("spreadsheet_core_not_a_workbook.wb");
+        let path = path.to_str().unwrap();
+        fs::write(path, "1,2,3\n").unwrap();
+        assert!(load_workbook(path).is_err());
+        fs::write(path, "SPREADSHEET_WORKBOOK\t99\n").unwrap();
+        assert!(load_workbook(path).is_err_and(|e| e.contains("version")));
+        fs::remove_file(path).unwrap();
+    }
+}